@@ -0,0 +1,40 @@
+//! Compares the cost of immutable borrows of a frozen cell against those of an ordinary cell
+//! while several threads read it at once. Run with `cargo bench --bench frozen`.
+
+use std::hint::black_box;
+use std::thread;
+use std::time::{Duration, Instant};
+use sync_rw_cell::*;
+
+/// The number of threads reading the cell at once.
+const THREADS: usize = 4;
+
+/// The number of borrows that each thread performs.
+const ITERATIONS: usize = 1_000_000;
+
+/// Measures how long it takes for every thread to borrow `cell` [`ITERATIONS`] times.
+fn contended_reads(cell: &RwCell<u64>) -> Duration {
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|| {
+                for _ in 0..ITERATIONS {
+                    black_box(*cell.borrow());
+                }
+            });
+        }
+    });
+    start.elapsed()
+}
+
+fn main() {
+    let tracked = RwCell::new(0);
+    let frozen = RwCell::new(0);
+    frozen.freeze().unwrap();
+
+    let tracked = contended_reads(&tracked);
+    let frozen = contended_reads(&frozen);
+    let per_borrow = |total: Duration| total.as_nanos() as f64 / (THREADS * ITERATIONS) as f64;
+    println!("tracked: {tracked:?} ({:.2} ns per borrow)", per_borrow(tracked));
+    println!("frozen:  {frozen:?} ({:.2} ns per borrow)", per_borrow(frozen));
+}
@@ -0,0 +1,61 @@
+//! Compares borrows of neighboring cells with and without cache padding while each thread
+//! borrows its own cell. Run with `cargo bench --bench padded`.
+
+use std::hint::black_box;
+use std::thread;
+use std::time::{Duration, Instant};
+use sync_rw_cell::*;
+
+/// The number of threads, each of which borrows a different cell.
+const THREADS: usize = 4;
+
+/// The number of borrows that each thread performs.
+const ITERATIONS: usize = 1_000_000;
+
+/// Measures how long it takes for each thread to mutably borrow its own cell [`ITERATIONS`] times.
+fn neighboring_writes(cells: [&RwCell<u64>; THREADS]) -> Duration {
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for cell in cells {
+            scope.spawn(move || {
+                for _ in 0..ITERATIONS {
+                    *cell.borrow_mut() += 1;
+                }
+            });
+        }
+    });
+    start.elapsed()
+}
+
+/// Measures how long it takes for each thread to immutably borrow its own cell [`ITERATIONS`] times.
+fn neighboring_reads(cells: [&RwCell<u64>; THREADS]) -> Duration {
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for cell in cells {
+            scope.spawn(move || {
+                for _ in 0..ITERATIONS {
+                    black_box(*cell.borrow());
+                }
+            });
+        }
+    });
+    start.elapsed()
+}
+
+/// Prints the average time taken by each borrow.
+fn report(name: &str, total: Duration) {
+    let per_borrow = total.as_nanos() as f64 / (THREADS * ITERATIONS) as f64;
+    println!("{name:<16} {total:>12.2?} ({per_borrow:.2} ns per borrow)");
+}
+
+fn main() {
+    let unpadded: [RwCell<u64>; THREADS] = Default::default();
+    let padded: [CachePaddedRwCell<u64>; THREADS] = Default::default();
+    let unpadded = unpadded.each_ref();
+    let padded = padded.each_ref().map(|cell| &**cell);
+
+    report("unpadded writes", neighboring_writes(unpadded));
+    report("padded writes", neighboring_writes(padded));
+    report("unpadded reads", neighboring_reads(unpadded));
+    report("padded reads", neighboring_reads(padded));
+}
@@ -0,0 +1,401 @@
+use crate::*;
+use alloc::sync::{Arc, Weak};
+use core::pin::Pin;
+
+/// A reference-counted, shared [`RwCell`]. Borrows of this cell may be owned, so that they
+/// keep the cell alive and are not tied to any lifetime.
+#[derive(Debug, Default)]
+pub struct ArcRwCell<T> {
+    /// The shared cell.
+    inner: Arc<RwCell<T>>,
+}
+
+impl<T> ArcRwCell<T> {
+    /// Creates a new shared cell that wraps the provided value.
+    #[inline(always)]
+    pub fn new(value: T) -> Self {
+        Self {
+            inner: Arc::new(RwCell::new(value)),
+        }
+    }
+
+    /// Creates a new shared cell whose value is given a weak handle to the cell itself. The
+    /// handle cannot be upgraded until this function returns.
+    ///
+    /// This can be used to build a pair of nodes that link to each other, where each node holds
+    /// a strong handle to the next node and a weak handle to the previous one:
+    ///
+    /// ```
+    /// # use sync_rw_cell::*;
+    /// struct Node {
+    ///     name: &'static str,
+    ///     prev: WeakRwCell<Node>,
+    ///     next: Option<ArcRwCell<Node>>,
+    /// }
+    ///
+    /// let first = ArcRwCell::new_cyclic(|first| Node {
+    ///     name: "first",
+    ///     prev: WeakRwCell::default(),
+    ///     next: Some(ArcRwCell::new(Node {
+    ///         name: "second",
+    ///         prev: first.clone(),
+    ///         next: None,
+    ///     })),
+    /// });
+    ///
+    /// let second = first.borrow().next.clone().unwrap();
+    /// assert_eq!(second.borrow().name, "second");
+    /// assert_eq!(second.borrow().prev.upgrade().unwrap().borrow().name, "first");
+    ///
+    /// drop(first);
+    /// assert!(second.borrow().prev.upgrade().is_none());
+    /// ```
+    #[inline(always)]
+    pub fn new_cyclic(f: impl FnOnce(&WeakRwCell<T>) -> T) -> Self {
+        Self {
+            inner: Arc::new_cyclic(|inner| RwCell::new(f(&WeakRwCell { inner: inner.clone() }))),
+        }
+    }
+
+    /// Immutably borrows the value of this cell, returning a guard that keeps the cell alive.
+    #[inline(always)]
+    pub fn borrow_owned(&self) -> OwnedRwCellGuard<Const, T> {
+        unsafe { OwnedRwCellGuard::new(self.inner.borrow(), self.inner.clone()) }
+    }
+
+    /// Attempts to immutably borrow the value of this cell, returning a guard that keeps the
+    /// cell alive, or an error if the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn try_borrow_owned(&self) -> Result<OwnedRwCellGuard<Const, T>, BorrowError> {
+        unsafe { Ok(OwnedRwCellGuard::new(self.inner.try_borrow()?, self.inner.clone())) }
+    }
+
+    /// Mutably borrows the value of this cell, returning a guard that keeps the cell alive.
+    #[inline(always)]
+    pub fn borrow_mut_owned(&self) -> OwnedRwCellGuard<Mut, T> {
+        unsafe { OwnedRwCellGuard::new(self.inner.borrow_mut(), self.inner.clone()) }
+    }
+
+    /// Attempts to mutably borrow the value of this cell, returning a guard that keeps the
+    /// cell alive, or an error if any other borrows exist.
+    #[inline(always)]
+    pub fn try_borrow_mut_owned(&self) -> Result<OwnedRwCellGuard<Mut, T>, BorrowMutError> {
+        unsafe { Ok(OwnedRwCellGuard::new(self.inner.try_borrow_mut()?, self.inner.clone())) }
+    }
+
+    /// Returns the value of the cell if this is the only strong handle to it. Fails and returns
+    /// the handle if other strong handles or owned guards exist, or if the cell is still
+    /// borrowed, which can occur when a leaked guard is alive. On failure, the same handle is
+    /// returned, so weak handles to the cell remain connected to it.
+    #[inline(always)]
+    pub fn try_unwrap(this: Self) -> Result<T, Self> {
+        if Arc::strong_count(&this.inner) != 1 || !this.free() {
+            return Err(this);
+        }
+
+        Arc::try_unwrap(this.inner).map(RwCell::into_inner).map_err(|inner| Self { inner })
+    }
+
+    /// Returns the value of the cell if this is the only strong handle to it. Otherwise, the
+    /// handle is dropped and `None` is returned. If the last strong handle is dropped while the
+    /// cell is still borrowed, the value is dropped and `None` is returned.
+    #[inline(always)]
+    pub fn into_inner(this: Self) -> Option<T> {
+        Arc::into_inner(this.inner).and_then(|cell| cell.try_into_inner().ok())
+    }
+
+    /// Creates a weak handle to this cell, which does not keep the cell alive.
+    #[inline(always)]
+    pub fn downgrade(this: &Self) -> WeakRwCell<T> {
+        WeakRwCell {
+            inner: Arc::downgrade(&this.inner),
+        }
+    }
+
+    /// Determines whether two handles refer to the same cell.
+    #[inline(always)]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.inner, &other.inner)
+    }
+}
+
+impl<T> Clone for ArcRwCell<T> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Deref for ArcRwCell<T> {
+    type Target = RwCell<T>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T> From<T> for ArcRwCell<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+/// A weak handle to an [`ArcRwCell`], which does not keep the cell alive.
+#[derive(Debug)]
+pub struct WeakRwCell<T> {
+    /// The weakly-referenced cell.
+    inner: Weak<RwCell<T>>,
+}
+
+impl<T> WeakRwCell<T> {
+    /// Creates a new weak handle that does not refer to any cell.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            inner: Weak::new(),
+        }
+    }
+
+    /// Attempts to get a strong handle to the cell, returning `None` if the cell has been dropped.
+    #[inline(always)]
+    pub fn upgrade(&self) -> Option<ArcRwCell<T>> {
+        self.inner.upgrade().map(|inner| ArcRwCell { inner })
+    }
+
+    /// Attempts to immutably borrow the value of the cell, returning a guard that keeps the cell
+    /// alive. Returns `None` if the cell has been dropped or is mutably borrowed.
+    #[inline(always)]
+    pub fn try_borrow_owned(&self) -> Option<OwnedRwCellGuard<Const, T>> {
+        self.upgrade()?.try_borrow_owned().ok()
+    }
+
+    /// Attempts to mutably borrow the value of the cell, returning a guard that keeps the cell
+    /// alive. Returns `None` if the cell has been dropped or any other borrows exist.
+    #[inline(always)]
+    pub fn try_borrow_mut_owned(&self) -> Option<OwnedRwCellGuard<Mut, T>> {
+        self.upgrade()?.try_borrow_mut_owned().ok()
+    }
+}
+
+impl<T> Clone for WeakRwCell<T> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Default for WeakRwCell<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A guard for a borrow of an [`ArcRwCell`], which keeps the cell alive for as long as
+/// the borrow exists. Mapping the guard to a portion of the cell's value changes the type `U`
+/// of the borrowed value, while the guard still refers to the whole cell of type `T`.
+pub struct OwnedRwCellGuard<M: Mutability, T, U: ?Sized = T> {
+    /// The borrowed value.
+    value: NonNull<U>,
+    /// The borrow state of the cell.
+    state: StateRef<'static>,
+    /// The cell which is borrowed. This is dropped after the borrow is released.
+    cell: Arc<RwCell<T>>,
+    /// Marks the mutability of the borrow.
+    marker: PhantomData<M>,
+}
+
+impl<M: Mutability, T, U: ?Sized> OwnedRwCellGuard<M, T, U> {
+    /// Creates a new owned guard from a borrow of the given cell.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, `guard` must borrow the value of `cell`.
+    #[inline(always)]
+    unsafe fn new(guard: RwCellGuard<'_, M, U>, cell: Arc<RwCell<T>>) -> Self {
+        let (value, state) = guard.into_parts();
+        Self {
+            value,
+            state: StateRef {
+                pointer: state.pointer,
+                marker: PhantomData,
+            },
+            cell,
+            marker: PhantomData,
+        }
+    }
+
+    /// Gets a handle to the cell that this guard borrows.
+    #[inline(always)]
+    pub fn cell(orig: &Self) -> ArcRwCell<T> {
+        ArcRwCell {
+            inner: orig.cell.clone(),
+        }
+    }
+
+    /// Replaces the borrowed value of a guard with a portion of it, without releasing the borrow.
+    #[inline(always)]
+    fn with_value<V: ?Sized>(orig: Self, value: NonNull<V>) -> OwnedRwCellGuard<M, T, V> {
+        let orig = ManuallyDrop::new(orig);
+        OwnedRwCellGuard {
+            value,
+            state: orig.state.projected(),
+            cell: unsafe { addr_of!(orig.cell).read() },
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T, U: ?Sized> OwnedRwCellGuard<Const, T, U> {
+    /// Creates a guard for a specific portion of a value.
+    #[inline(always)]
+    pub fn map<V, F>(orig: Self, f: F) -> OwnedRwCellGuard<Const, T, V>
+    where
+        F: FnOnce(&U) -> &V,
+        V: ?Sized,
+    {
+        let value = NonNull::from(f(&orig));
+        Self::with_value(orig, value)
+    }
+
+    /// Creates a guard for an optional portion of a value. If the portion is absent,
+    /// the original guard is returned.
+    #[inline(always)]
+    pub fn filter_map<V, F>(orig: Self, f: F) -> Result<OwnedRwCellGuard<Const, T, V>, Self>
+    where
+        F: FnOnce(&U) -> Option<&V>,
+        V: ?Sized,
+    {
+        match f(&orig).map(NonNull::from) {
+            Some(value) => Ok(Self::with_value(orig, value)),
+            None => Err(orig),
+        }
+    }
+}
+
+impl<T, U: ?Sized> OwnedRwCellGuard<Mut, T, U> {
+    /// Creates a guard for a specific portion of a value.
+    #[inline(always)]
+    pub fn map<V, F>(mut orig: Self, f: F) -> OwnedRwCellGuard<Mut, T, V>
+    where
+        F: FnOnce(&mut U) -> &mut V,
+        V: ?Sized,
+    {
+        let value = NonNull::from(f(&mut orig));
+        Self::with_value(orig, value)
+    }
+
+    /// Creates a guard for an optional portion of a value. If the portion is absent,
+    /// the original guard is returned.
+    #[inline(always)]
+    pub fn filter_map<V, F>(mut orig: Self, f: F) -> Result<OwnedRwCellGuard<Mut, T, V>, Self>
+    where
+        F: FnOnce(&mut U) -> Option<&mut V>,
+        V: ?Sized,
+    {
+        match f(&mut orig).map(NonNull::from) {
+            Some(value) => Ok(Self::with_value(orig, value)),
+            None => Err(orig),
+        }
+    }
+}
+
+impl<M: Mutability, T, U: ?Sized> Deref for OwnedRwCellGuard<M, T, U> {
+    type Target = U;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<T, U: ?Sized> DerefMut for OwnedRwCellGuard<Mut, T, U> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<M: Mutability, T, U: ?Sized> Drop for OwnedRwCellGuard<M, T, U> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.state.get().release::<M>();
+    }
+}
+
+impl<M: Mutability, T, U: ?Sized + fmt::Debug> fmt::Debug for OwnedRwCellGuard<M, T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+unsafe impl<T: Send + Sync, U: ?Sized + Sync> Send for OwnedRwCellGuard<Const, T, U> {}
+unsafe impl<T: Send + Sync, U: ?Sized + Sync> Sync for OwnedRwCellGuard<Const, T, U> {}
+unsafe impl<T: Send + Sync, U: ?Sized + Send> Send for OwnedRwCellGuard<Mut, T, U> {}
+unsafe impl<T: Send + Sync, U: ?Sized + Sync> Sync for OwnedRwCellGuard<Mut, T, U> {}
+
+/// A heap-allocated cell which is never moved, and which can be borrowed with guards that are
+/// not tied to the lifetime of the cell. The allocation is freed once the cell and all of its
+/// detached guards have been dropped.
+#[derive(Debug, Default)]
+pub struct PinnedRwCell<T> {
+    /// The shared cell.
+    inner: ArcRwCell<T>,
+}
+
+impl<T> PinnedRwCell<T> {
+    /// Creates a new pinned cell that wraps the provided value.
+    #[inline(always)]
+    pub fn new(value: T) -> Self {
+        Self {
+            inner: ArcRwCell::new(value),
+        }
+    }
+
+    /// Gets a pinned reference to the underlying cell.
+    #[inline(always)]
+    pub fn as_pin(&self) -> Pin<&RwCell<T>> {
+        unsafe { Pin::new_unchecked(&self.inner) }
+    }
+
+    /// Immutably borrows the value of this cell, returning a guard that keeps the allocation alive.
+    #[inline(always)]
+    pub fn borrow_detached(&self) -> OwnedRwCellGuard<Const, T> {
+        self.inner.borrow_owned()
+    }
+
+    /// Mutably borrows the value of this cell, returning a guard that keeps the allocation alive.
+    #[inline(always)]
+    pub fn borrow_mut_detached(&self) -> OwnedRwCellGuard<Mut, T> {
+        self.inner.borrow_mut_owned()
+    }
+
+    /// Consumes this cell, returning the wrapped value. Fails and returns the cell if any
+    /// detached guards still exist.
+    #[inline(always)]
+    pub fn try_into_inner(self) -> Result<T, Self> {
+        ArcRwCell::try_unwrap(self.inner).map_err(|inner| Self { inner })
+    }
+}
+
+impl<T> Deref for PinnedRwCell<T> {
+    type Target = RwCell<T>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T> From<T> for PinnedRwCell<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
@@ -0,0 +1,392 @@
+use crate::*;
+
+/// An array stored contiguously, with an independent borrow counter for each element. Borrows
+/// only conflict when they access the same element, while the whole array can be borrowed
+/// at once when none of its elements are borrowed. Each element only adds a bare counter to
+/// the array, and threads waiting on an element park in the same global table as those waiting
+/// on a [`RwCell`].
+pub struct RwCellArray<T, const N: usize> {
+    /// The elements of the array.
+    values: UnsafeCell<[T; N]>,
+    /// The borrow counter of each element.
+    counters: [AtomicU16; N],
+}
+
+impl<T, const N: usize> RwCellArray<T, N> {
+    /// Creates a new array that wraps the provided values.
+    #[inline(always)]
+    pub const fn new(values: [T; N]) -> Self {
+        Self {
+            values: UnsafeCell::new(values),
+            counters: [const { AtomicU16::new(0) }; N],
+        }
+    }
+
+    /// Consumes this array, returning the wrapped values.
+    #[inline(always)]
+    pub fn into_inner(self) -> [T; N] {
+        self.values.into_inner()
+    }
+
+    /// Gets a mutable reference to the values. This requires no borrow accounting,
+    /// since the array is mutably borrowed.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut [T; N] {
+        self.values.get_mut()
+    }
+
+    /// Gets the number of elements in the array.
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        N
+    }
+
+    /// Determines whether the array has no elements.
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Immutably borrows the element at an index. Panics if the index is out of bounds.
+    #[inline(always)]
+    pub fn borrow_element(&self, index: usize) -> RwCellElementGuard<'_, Const, T> {
+        self.state(index).acquire_read();
+        unsafe { self.element_guard(index) }
+    }
+
+    /// Attempts to immutably borrow the element at an index, returning an error instead of
+    /// aborting if the element is mutably borrowed. Panics if the index is out of bounds.
+    #[inline(always)]
+    pub fn try_borrow_element(&self, index: usize) -> Result<RwCellElementGuard<'_, Const, T>, BorrowError> {
+        if self.state(index).try_acquire_read() {
+            unsafe { Ok(self.element_guard(index)) }
+        }
+        else {
+            Err(BorrowError)
+        }
+    }
+
+    /// Mutably borrows the element at an index. Panics if the index is out of bounds.
+    #[inline(always)]
+    pub fn borrow_element_mut(&self, index: usize) -> RwCellElementGuard<'_, Mut, T> {
+        self.state(index).acquire_write();
+        unsafe { self.element_guard(index) }
+    }
+
+    /// Attempts to mutably borrow the element at an index, returning an error instead of
+    /// aborting if the element is borrowed. Panics if the index is out of bounds.
+    #[inline(always)]
+    pub fn try_borrow_element_mut(&self, index: usize) -> Result<RwCellElementGuard<'_, Mut, T>, BorrowMutError> {
+        self.state(index).try_acquire_write()?;
+        unsafe { Ok(self.element_guard(index)) }
+    }
+
+    /// Immutably borrows the element at an index, parking the current thread until any mutable
+    /// borrow of the element is released instead of aborting. Panics if the index is out of bounds.
+    #[cfg(feature = "std")]
+    pub fn borrow_element_wait(&self, index: usize) -> RwCellElementGuard<'_, Const, T> {
+        let state = self.state(index);
+        ParkingBucket::park_until(state.counter, || state.try_acquire_read().then(|| unsafe { self.element_guard(index) }))
+    }
+
+    /// Mutably borrows the element at an index, parking the current thread until all other
+    /// borrows of the element are released instead of aborting. Panics if the index is out of bounds.
+    #[cfg(feature = "std")]
+    pub fn borrow_element_mut_wait(&self, index: usize) -> RwCellElementGuard<'_, Mut, T> {
+        let state = self.state(index);
+        ParkingBucket::park_until(state.counter, || state.try_acquire_write().ok().map(|()| unsafe { self.element_guard(index) }))
+    }
+
+    /// Mutably borrows every element of the array. Aborts if any element is borrowed.
+    #[inline(always)]
+    pub fn borrow_all_mut(&self) -> RwCellArrayGuard<'_, T, N> {
+        match self.try_borrow_all_mut() {
+            Ok(guard) => guard,
+            Err(_) => AbortPanic::abort("Attempted to mutably borrow array while other borrows already existed."),
+        }
+    }
+
+    /// Attempts to mutably borrow every element of the array, returning an error instead of
+    /// aborting if any element is borrowed.
+    #[inline(always)]
+    pub fn try_borrow_all_mut(&self) -> Result<RwCellArrayGuard<'_, T, N>, BorrowMutError> {
+        for i in 0..N {
+            if let Err(error) = self.state(i).try_acquire_write() {
+                for acquired in 0..i {
+                    self.state(acquired).release_write();
+                }
+                return Err(error);
+            }
+        }
+
+        Ok(RwCellArrayGuard { array: self })
+    }
+
+    /// Gets the borrow state of the element at an index. Panics if the index is out of bounds.
+    #[inline(always)]
+    fn state(&self, index: usize) -> ElementState<'_> {
+        ElementState { counter: &self.counters[index] }
+    }
+
+    /// Creates a guard for the element at an index.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, the caller must own a borrow of the element with the
+    /// given mutability.
+    #[inline(always)]
+    unsafe fn element_guard<M: Mutability>(&self, index: usize) -> RwCellElementGuard<'_, M, T> {
+        RwCellElementGuard {
+            value: NonNull::new_unchecked(self.values.get().cast::<T>().add(index)),
+            state: self.state(index),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Default, const N: usize> Default for RwCellArray<T, N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(core::array::from_fn(|_| T::default()))
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for RwCellArray<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RwCellArray").field("len", &N).finish_non_exhaustive()
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for RwCellArray<T, N> {
+    #[inline(always)]
+    fn from(values: [T; N]) -> Self {
+        Self::new(values)
+    }
+}
+
+unsafe impl<T: Send + Sync, const N: usize> Sync for RwCellArray<T, N> {}
+
+/// A guard for a mutable borrow of every element of a [`RwCellArray`].
+pub struct RwCellArrayGuard<'a, T, const N: usize> {
+    /// The borrowed array.
+    array: &'a RwCellArray<T, N>,
+}
+
+impl<'a, T, const N: usize> Deref for RwCellArrayGuard<'a, T, N> {
+    type Target = [T; N];
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.array.values.get() }
+    }
+}
+
+impl<'a, T, const N: usize> DerefMut for RwCellArrayGuard<'a, T, N> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.array.values.get() }
+    }
+}
+
+impl<'a, T, const N: usize> Drop for RwCellArrayGuard<'a, T, N> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        for i in 0..N {
+            self.array.state(i).release_write();
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug, const N: usize> fmt::Debug for RwCellArrayGuard<'a, T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+/// The borrow counter of one element of a [`RwCellArray`]. Counter values are encoded in the
+/// same way as those of a [`RwCell`], without the upgradable, reserved, or frozen states.
+#[derive(Copy, Clone)]
+struct ElementState<'a> {
+    /// The borrow counter of the element.
+    counter: &'a AtomicU16,
+}
+
+impl<'a> ElementState<'a> {
+    /// Attempts to acquire an immutable borrow of the element, failing if it is mutably borrowed.
+    #[inline(always)]
+    fn try_acquire_read(self) -> bool {
+        let mut state = self.counter.load(Ordering::Acquire);
+        loop {
+            if is_writing(state) || state & MAX_READERS == MAX_READERS {
+                return false;
+            }
+
+            match self.counter.compare_exchange_weak(state, state + 1, Ordering::Acquire, Ordering::Acquire) {
+                Ok(_) => return true,
+                Err(current) => state = current,
+            }
+        }
+    }
+
+    /// Acquires an immutable borrow of the element, aborting if it is mutably borrowed.
+    #[inline(always)]
+    fn acquire_read(self) {
+        let previous = self.counter.fetch_add(1, Ordering::AcqRel);
+        if is_writing(previous) {
+            AbortPanic::abort("Attempted to immutably borrow array element while it was mutably borrowed.");
+        }
+        else if previous & MAX_READERS == MAX_READERS {
+            AbortPanic::abort("Attempted to immutably borrow array element too many times.");
+        }
+    }
+
+    /// Attempts to acquire a mutable borrow of the element, failing if any other borrows exist.
+    #[inline(always)]
+    fn try_acquire_write(self) -> Result<(), BorrowMutError> {
+        match transition(self.counter, 0, WRITER, Ordering::Acquire) {
+            Ok(_) => Ok(()),
+            Err(state) if is_writing(state) => Err(BorrowMutError::Writing),
+            Err(_) => Err(BorrowMutError::Reading),
+        }
+    }
+
+    /// Acquires a mutable borrow of the element, aborting if any other borrows exist.
+    #[inline(always)]
+    fn acquire_write(self) {
+        let previous = self.counter.swap(WRITER, Ordering::AcqRel);
+        if previous & !PARKED != 0 {
+            AbortPanic::abort("Attempted to mutably borrow array element while other borrows already existed.");
+        }
+        else if previous & PARKED != 0 {
+            self.counter.fetch_or(PARKED, Ordering::Relaxed);
+        }
+    }
+
+    /// Releases a borrow of the element with the given mutability.
+    #[inline(always)]
+    fn release<M: Mutability>(self) {
+        if TypeId::of::<M>() == TypeId::of::<Mut>() {
+            self.release_write();
+        }
+        else {
+            self.release_read();
+        }
+    }
+
+    /// Releases an immutable borrow of the element.
+    #[inline(always)]
+    fn release_read(self) {
+        let previous = self.counter.fetch_sub(1, Ordering::Release);
+        let readers = previous & MAX_READERS;
+        if readers == 1 || readers == MAX_READERS {
+            self.unpark(previous);
+        }
+    }
+
+    /// Releases a mutable borrow of the element.
+    #[inline(always)]
+    fn release_write(self) {
+        let previous = self.counter.fetch_and(PARKED, Ordering::Release);
+        self.unpark(previous);
+    }
+
+    /// Wakes the threads parked on the element if the parked bit was set in `previous`, the
+    /// counter value that a release replaced.
+    #[inline(always)]
+    fn unpark(self, previous: u16) {
+        #[cfg(feature = "std")]
+        if previous & PARKED != 0 {
+            ParkingBucket::unpark(self.counter);
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = previous;
+    }
+}
+
+/// A guard for a borrow of a single element of a [`RwCellArray`]. Guards are [`Send`] and
+/// [`Sync`] under the same conditions as a [`RwCellGuard`].
+pub struct RwCellElementGuard<'a, M: Mutability, T: 'a + ?Sized> {
+    /// The borrowed value.
+    value: NonNull<T>,
+    /// The borrow state of the element.
+    state: ElementState<'a>,
+    /// Marks the lifetime and mutability of the borrow.
+    marker: PhantomData<M::Ref<'a, T>>,
+}
+
+impl<'a, T: 'a + ?Sized> RwCellElementGuard<'a, Const, T> {
+    /// Creates a reference to a specific portion of a value. The borrow is released if `f` unwinds.
+    #[inline(always)]
+    pub fn map<U, F>(orig: Self, f: F) -> RwCellElementGuard<'a, Const, U>
+    where
+        F: FnOnce(&T) -> &U,
+        U: ?Sized,
+    {
+        let value = NonNull::from(f(&orig));
+        let orig = ManuallyDrop::new(orig);
+        RwCellElementGuard {
+            value,
+            state: orig.state,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: 'a + ?Sized> RwCellElementGuard<'a, Mut, T> {
+    /// Creates a reference to a specific portion of a value. The borrow is released if `f` unwinds.
+    #[inline(always)]
+    pub fn map<U, F>(mut orig: Self, f: F) -> RwCellElementGuard<'a, Mut, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+        U: ?Sized,
+    {
+        let value = NonNull::from(f(&mut orig));
+        let orig = ManuallyDrop::new(orig);
+        RwCellElementGuard {
+            value,
+            state: orig.state,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized> Deref for RwCellElementGuard<'a, M, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<'a, T: 'a + ?Sized> DerefMut for RwCellElementGuard<'a, Mut, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized> Drop for RwCellElementGuard<'a, M, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.state.release::<M>();
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized + fmt::Debug> fmt::Debug for RwCellElementGuard<'a, M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized + fmt::Display> fmt::Display for RwCellElementGuard<'a, M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+unsafe impl<'a, T: 'a + ?Sized + Sync> Send for RwCellElementGuard<'a, Const, T> {}
+unsafe impl<'a, T: 'a + ?Sized + Sync> Sync for RwCellElementGuard<'a, Const, T> {}
+unsafe impl<'a, T: 'a + ?Sized + Send> Send for RwCellElementGuard<'a, Mut, T> {}
+unsafe impl<'a, T: 'a + ?Sized + Sync> Sync for RwCellElementGuard<'a, Mut, T> {}
+
@@ -0,0 +1,960 @@
+use crate::*;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::*;
+use core::time::Duration;
+
+/// A cell whose borrows are acquired asynchronously. Instead of aborting when borrows
+/// conflict, acquisition futures wait until the conflicting guards are dropped.
+///
+/// Acquisition futures hold no borrow while they are pending, so dropping one at any time
+/// is safe and removes its waker from the cell.
+///
+/// The cell does not dereference to a [`RwCell`], since guards obtained from it would release
+/// their borrows without waking the waiting tasks.
+#[derive(Debug, Default)]
+pub struct AsyncRwCell<T> {
+    /// The underlying cell.
+    cell: RwCell<T>,
+    /// The tasks waiting for borrows to be released or for the value to change.
+    waiters: AsyncWaiters,
+}
+
+impl<T> AsyncRwCell<T> {
+    /// Creates a new cell that wraps the provided value.
+    #[inline(always)]
+    pub const fn new(value: T) -> Self {
+        Self {
+            cell: RwCell::new(value),
+            waiters: AsyncWaiters::new(),
+        }
+    }
+
+    /// Consumes this cell, returning the wrapped value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.cell.into_inner()
+    }
+
+    /// Gets a mutable reference to the value. This requires no borrow accounting,
+    /// since the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.cell.get_mut()
+    }
+
+    /// Gets a raw pointer to the wrapped value.
+    #[inline(always)]
+    pub fn as_ptr(&self) -> *mut T {
+        self.cell.as_ptr()
+    }
+
+    /// Determines whether this cell is free to be borrowed.
+    #[inline(always)]
+    pub fn free(&self) -> bool {
+        self.cell.free()
+    }
+
+    /// Takes a snapshot of this cell's borrow state. The result is racy in the same way as
+    /// [`RwCell::borrow_state`].
+    #[inline(always)]
+    pub fn borrow_state(&self) -> BorrowState {
+        self.cell.borrow_state()
+    }
+
+    /// Gets the number of immutable borrows of this cell. This is zero if the cell is
+    /// mutably borrowed.
+    #[inline(always)]
+    pub fn reader_count(&self) -> usize {
+        self.cell.reader_count()
+    }
+
+    /// Determines whether this cell is mutably borrowed.
+    #[inline(always)]
+    pub fn is_locked_exclusive(&self) -> bool {
+        self.cell.is_locked_exclusive()
+    }
+
+    /// Immutably borrows the value of this cell, waiting until any mutable borrow is released.
+    #[inline(always)]
+    pub fn read(&self) -> AsyncBorrow<'_, Const, T> {
+        AsyncBorrow::new(self)
+    }
+
+    /// Mutably borrows the value of this cell, waiting until all other borrows are released.
+    /// The future aborts when polled if the cell is frozen, since it can never be mutably borrowed.
+    #[inline(always)]
+    pub fn write(&self) -> AsyncBorrow<'_, Mut, T> {
+        AsyncBorrow::new(self)
+    }
+
+    /// Immutably borrows the value of this cell, waiting until any mutable borrow is released
+    /// or `timeout` elapses according to `timer`. The future outputs [`None`] on timeout.
+    #[inline(always)]
+    pub fn read_timeout<R: Timer>(&self, timeout: Duration, mut timer: R) -> AsyncBorrowTimeout<'_, Const, T, R::Delay> {
+        AsyncBorrowTimeout {
+            borrow: self.read(),
+            delay: timer.delay(timeout),
+        }
+    }
+
+    /// Mutably borrows the value of this cell, waiting until all other borrows are released
+    /// or `timeout` elapses according to `timer`. The future outputs [`None`] on timeout.
+    #[inline(always)]
+    pub fn write_timeout<R: Timer>(&self, timeout: Duration, mut timer: R) -> AsyncBorrowTimeout<'_, Mut, T, R::Delay> {
+        AsyncBorrowTimeout {
+            borrow: self.write(),
+            delay: timer.delay(timeout),
+        }
+    }
+
+    /// Waits until the next time that a mutable borrow of this cell is released. Mutable
+    /// borrows released after this call, but before the future is first polled, are counted.
+    #[inline(always)]
+    pub fn changed(&self) -> AsyncChanged<'_, T> {
+        AsyncChanged::new(self, self.waiters.version.load(Ordering::Acquire), None)
+    }
+
+    /// Creates a subscriber that tracks which changes to this cell it has seen.
+    #[inline(always)]
+    pub fn subscribe(&self) -> AsyncRwCellSubscriber<'_, T> {
+        AsyncRwCellSubscriber {
+            cell: self,
+            seen: self.waiters.version.load(Ordering::Acquire),
+        }
+    }
+
+    /// Attempts to immutably borrow the value of this cell without waiting, returning an error
+    /// if the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn try_read(&self) -> Result<AsyncReadGuard<'_, T>, BorrowError> {
+        let guard = self.cell.try_borrow()?;
+        Ok(AsyncRwCellGuard::new(guard, &self.waiters))
+    }
+
+    /// Attempts to mutably borrow the value of this cell without waiting, returning an error
+    /// if any other borrows exist.
+    #[inline(always)]
+    pub fn try_write(&self) -> Result<AsyncWriteGuard<'_, T>, BorrowMutError> {
+        let guard = self.cell.try_borrow_mut()?;
+        Ok(AsyncRwCellGuard::new(guard, &self.waiters))
+    }
+
+    /// Immutably borrows the value of a shared cell, waiting until any mutable borrow is
+    /// released. The guard keeps the cell alive, so it may be held across tasks.
+    #[inline(always)]
+    pub fn read_owned(self: &Arc<Self>) -> AsyncBorrowOwned<Const, T> {
+        AsyncBorrowOwned::new(self.clone())
+    }
+
+    /// Mutably borrows the value of a shared cell, waiting until all other borrows are
+    /// released. The guard keeps the cell alive, so it may be held across tasks.
+    #[inline(always)]
+    pub fn write_owned(self: &Arc<Self>) -> AsyncBorrowOwned<Mut, T> {
+        AsyncBorrowOwned::new(self.clone())
+    }
+
+    /// Attempts to immutably borrow the value of a shared cell without waiting, returning an
+    /// error if the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn try_read_owned(self: &Arc<Self>) -> Result<OwnedAsyncReadGuard<T>, BorrowError> {
+        let guard = self.cell.try_borrow()?;
+        unsafe { Ok(OwnedAsyncRwCellGuard::new(guard, self.clone())) }
+    }
+
+    /// Attempts to mutably borrow the value of a shared cell without waiting, returning an
+    /// error if any other borrows exist.
+    #[inline(always)]
+    pub fn try_write_owned(self: &Arc<Self>) -> Result<OwnedAsyncWriteGuard<T>, BorrowMutError> {
+        let guard = self.cell.try_borrow_mut()?;
+        unsafe { Ok(OwnedAsyncRwCellGuard::new(guard, self.clone())) }
+    }
+
+    /// Polls for a borrow of this cell on behalf of a future, registering the future's waker
+    /// if the borrow cannot be acquired yet.
+    #[inline(always)]
+    fn poll_borrow<M: Mutability>(&self, key: &mut Option<usize>, cx: &mut Context<'_>) -> Poll<RwCellGuard<'_, M, T>> {
+        if let Some(guard) = self.cell.try_borrow_waiting() {
+            self.waiters.borrows.unregister(key.take());
+            return Poll::Ready(guard);
+        }
+
+        self.waiters.borrows.register(key, cx.waker());
+        fence(Ordering::SeqCst);
+        match self.cell.try_borrow_waiting() {
+            Some(guard) => {
+                self.waiters.borrows.unregister(key.take());
+                Poll::Ready(guard)
+            },
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<T> From<T> for AsyncRwCell<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+/// A future that borrows an [`AsyncRwCell`] with the given mutability.
+#[must_use = "futures do nothing unless polled"]
+pub struct AsyncBorrow<'a, M: Mutability, T> {
+    /// The cell to borrow.
+    cell: &'a AsyncRwCell<T>,
+    /// The key of this future's waker, if one is registered.
+    key: Option<usize>,
+    /// Marks the mutability of the borrow.
+    marker: PhantomData<fn() -> M>,
+}
+
+impl<'a, M: Mutability, T> AsyncBorrow<'a, M, T> {
+    /// Creates a new future for the given cell.
+    #[inline(always)]
+    fn new(cell: &'a AsyncRwCell<T>) -> Self {
+        Self {
+            cell,
+            key: None,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, M: Mutability, T> Future for AsyncBorrow<'a, M, T> {
+    type Output = AsyncRwCellGuard<'a, M, T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let cell = this.cell;
+        cell.poll_borrow(&mut this.key, cx).map(|guard| AsyncRwCellGuard::new(guard, &cell.waiters))
+    }
+}
+
+impl<'a, M: Mutability, T> Drop for AsyncBorrow<'a, M, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.cell.waiters.borrows.unregister(self.key.take());
+    }
+}
+
+impl<'a, M: Mutability, T> fmt::Debug for AsyncBorrow<'a, M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncBorrow").field("registered", &self.key.is_some()).finish_non_exhaustive()
+    }
+}
+
+/// A guard for an immutable borrow of an [`AsyncRwCell`].
+pub type AsyncReadGuard<'a, T> = AsyncRwCellGuard<'a, Const, T>;
+
+/// A guard for a mutable borrow of an [`AsyncRwCell`].
+pub type AsyncWriteGuard<'a, T> = AsyncRwCellGuard<'a, Mut, T>;
+
+/// A guard for a borrow of an [`AsyncRwCell`], which wakes waiting tasks when dropped.
+pub struct AsyncRwCellGuard<'a, M: Mutability, T: 'a + ?Sized> {
+    /// The underlying borrow.
+    guard: ManuallyDrop<RwCellGuard<'a, M, T>>,
+    /// The tasks to wake when the borrow is released.
+    waiters: &'a AsyncWaiters,
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized> AsyncRwCellGuard<'a, M, T> {
+    /// Wraps a borrow of a cell with the queue of the cell's waiting tasks.
+    #[inline(always)]
+    fn new(guard: RwCellGuard<'a, M, T>, waiters: &'a AsyncWaiters) -> Self {
+        Self {
+            guard: ManuallyDrop::new(guard),
+            waiters,
+        }
+    }
+
+    /// Reassembles a guard from a borrow of a cell and the release of the cell's waiting tasks.
+    #[inline(always)]
+    fn from_parts<N: Mutability>(guard: RwCellGuard<'a, M, T>, release: WaitersRelease<'a, N>) -> Self {
+        let waiters = release.waiters;
+        forget(release);
+        Self::new(guard, waiters)
+    }
+
+    /// Splits a guard into its underlying borrow and a release that wakes the cell's waiting
+    /// tasks if it is dropped, so that they are still woken if mapping the borrow unwinds.
+    #[inline(always)]
+    fn into_parts(orig: Self) -> (RwCellGuard<'a, M, T>, WaitersRelease<'a, M>) {
+        let mut orig = ManuallyDrop::new(orig);
+        let guard = unsafe { ManuallyDrop::take(&mut orig.guard) };
+        (guard, WaitersRelease::new(orig.waiters))
+    }
+}
+
+impl<'a, T: 'a + ?Sized> AsyncRwCellGuard<'a, Const, T> {
+    /// Creates a guard for a specific portion of a value.
+    #[inline(always)]
+    pub fn map<U, F>(orig: Self, f: F) -> AsyncRwCellGuard<'a, Const, U>
+    where
+        F: FnOnce(&T) -> &U,
+        U: ?Sized,
+    {
+        let (guard, release) = Self::into_parts(orig);
+        AsyncRwCellGuard::from_parts(RwCellGuard::<Const, _>::map(guard, f), release)
+    }
+
+    /// Creates a guard for an optional portion of a value. If the portion is absent,
+    /// the original guard is returned.
+    #[inline(always)]
+    pub fn filter_map<U, F>(orig: Self, f: F) -> Result<AsyncRwCellGuard<'a, Const, U>, Self>
+    where
+        F: FnOnce(&T) -> Option<&U>,
+        U: ?Sized,
+    {
+        let (guard, release) = Self::into_parts(orig);
+        match RwCellGuard::<Const, _>::filter_map(guard, f) {
+            Ok(guard) => Ok(AsyncRwCellGuard::from_parts(guard, release)),
+            Err(guard) => Err(Self::from_parts(guard, release)),
+        }
+    }
+}
+
+impl<'a, T: 'a + ?Sized> AsyncRwCellGuard<'a, Mut, T> {
+    /// Creates a guard for a specific portion of a value. The borrow is released and waiting
+    /// tasks are woken if `f` unwinds.
+    #[inline(always)]
+    pub fn map<U, F>(orig: Self, f: F) -> AsyncRwCellGuard<'a, Mut, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+        U: ?Sized,
+    {
+        let (guard, release) = Self::into_parts(orig);
+        AsyncRwCellGuard::from_parts(RwCellGuard::<Mut, _>::map(guard, f), release)
+    }
+
+    /// Creates a guard for an optional portion of a value. If the portion is absent,
+    /// the original guard is returned. The borrow is released and waiting tasks are woken
+    /// if `f` unwinds.
+    #[inline(always)]
+    pub fn filter_map<U, F>(orig: Self, f: F) -> Result<AsyncRwCellGuard<'a, Mut, U>, Self>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+        U: ?Sized,
+    {
+        let (guard, release) = Self::into_parts(orig);
+        match RwCellGuard::<Mut, _>::filter_map(guard, f) {
+            Ok(guard) => Ok(AsyncRwCellGuard::from_parts(guard, release)),
+            Err(guard) => Err(Self::from_parts(guard, release)),
+        }
+    }
+
+    /// Converts a mutable guard into an immutable guard without releasing the borrow, so no
+    /// other mutable borrow can occur in between. Tasks waiting to immutably borrow the cell
+    /// are woken. Aborts if the guard was produced by splitting another guard, and the other
+    /// parts still exist.
+    #[inline(always)]
+    pub fn downgrade(orig: Self) -> AsyncRwCellGuard<'a, Const, T> {
+        let (guard, release) = Self::into_parts(orig);
+        let guard = RwCellGuard::<Mut, _>::downgrade(guard);
+        let waiters = release.waiters;
+        drop(release);
+        AsyncRwCellGuard::new(guard, waiters)
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized> Deref for AsyncRwCellGuard<'a, M, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<'a, T: 'a + ?Sized> DerefMut for AsyncRwCellGuard<'a, Mut, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized> Drop for AsyncRwCellGuard<'a, M, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        unsafe { ManuallyDrop::drop(&mut self.guard) };
+        self.waiters.release::<M>();
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized + fmt::Debug> fmt::Debug for AsyncRwCellGuard<'a, M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+/// Tracks the changes to an [`AsyncRwCell`] that have been seen. A change is recorded each
+/// time that a mutable borrow of the cell is released, while immutable borrows are ignored.
+#[derive(Debug)]
+pub struct AsyncRwCellSubscriber<'a, T> {
+    /// The observed cell.
+    cell: &'a AsyncRwCell<T>,
+    /// The version of the cell that was last seen.
+    seen: usize,
+}
+
+impl<'a, T> AsyncRwCellSubscriber<'a, T> {
+    /// Determines whether the cell has changed since it was last seen.
+    #[inline(always)]
+    pub fn has_changed(&self) -> bool {
+        self.cell.waiters.version.load(Ordering::Acquire) != self.seen
+    }
+
+    /// Marks the current state of the cell as seen.
+    #[inline(always)]
+    pub fn mark_seen(&mut self) {
+        self.seen = self.cell.waiters.version.load(Ordering::Acquire);
+    }
+
+    /// Waits until the cell has changed since it was last seen, then marks it as seen. Completes
+    /// immediately if a change has already occurred.
+    #[inline(always)]
+    pub fn changed(&mut self) -> AsyncChanged<'_, T> {
+        AsyncChanged::new(self.cell, self.seen, Some(&mut self.seen))
+    }
+
+    /// Marks the current state of the cell as seen, then immutably borrows its value. A change
+    /// that occurs before the borrow is acquired will be reported again by the subscriber.
+    #[inline(always)]
+    pub fn borrow_and_mark_seen(&mut self) -> AsyncBorrow<'a, Const, T> {
+        self.mark_seen();
+        self.cell.read()
+    }
+}
+
+impl<'a, T> Clone for AsyncRwCellSubscriber<'a, T> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            cell: self.cell,
+            seen: self.seen,
+        }
+    }
+}
+
+/// A future that completes once an [`AsyncRwCell`] changes from a given version.
+#[must_use = "futures do nothing unless polled"]
+pub struct AsyncChanged<'a, T> {
+    /// The observed cell.
+    cell: &'a AsyncRwCell<T>,
+    /// The version from which the cell must change.
+    seen: usize,
+    /// The version to update once the change is observed.
+    mark: Option<&'a mut usize>,
+    /// The key of this future's waker, if one is registered.
+    key: Option<usize>,
+}
+
+impl<'a, T> AsyncChanged<'a, T> {
+    /// Creates a new future for the given cell and version.
+    #[inline(always)]
+    fn new(cell: &'a AsyncRwCell<T>, seen: usize, mark: Option<&'a mut usize>) -> Self {
+        Self {
+            cell,
+            seen,
+            mark,
+            key: None,
+        }
+    }
+
+    /// Determines whether the cell has changed, updating the seen version if so.
+    #[inline(always)]
+    fn check(&mut self) -> bool {
+        let version = self.cell.waiters.version.load(Ordering::Acquire);
+        let changed = version != self.seen;
+        if changed {
+            if let Some(mark) = self.mark.take() {
+                *mark = version;
+            }
+            self.cell.waiters.changes.unregister(self.key.take());
+        }
+        changed
+    }
+}
+
+impl<'a, T> Future for AsyncChanged<'a, T> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if this.check() {
+            return Poll::Ready(());
+        }
+
+        this.cell.waiters.changes.register(&mut this.key, cx.waker());
+        fence(Ordering::SeqCst);
+        if this.check() {
+            Poll::Ready(())
+        }
+        else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<'a, T> Drop for AsyncChanged<'a, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.cell.waiters.changes.unregister(self.key.take());
+    }
+}
+
+impl<'a, T> fmt::Debug for AsyncChanged<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncChanged").field("seen", &self.seen).finish_non_exhaustive()
+    }
+}
+
+/// A source of delays for timed borrows of an [`AsyncRwCell`], which allows any async
+/// runtime's clock to be used. Closures that create delay futures implement this trait, so a
+/// function such as `tokio::time::sleep` may be passed directly. The `tokio` and
+/// `embassy-time` features provide `TokioTimer` and `EmbassyTimer`, which use the clocks of
+/// those runtimes.
+pub trait Timer {
+    /// The future that completes once a delay has elapsed.
+    type Delay: Future<Output = ()>;
+
+    /// Creates a future that completes once `duration` has elapsed.
+    fn delay(&mut self, duration: Duration) -> Self::Delay;
+}
+
+impl<F: FnMut(Duration) -> D, D: Future<Output = ()>> Timer for F {
+    type Delay = D;
+
+    #[inline(always)]
+    fn delay(&mut self, duration: Duration) -> Self::Delay {
+        self(duration)
+    }
+}
+
+/// A timer that creates delays with `tokio::time::sleep`, so it must be used from within a Tokio
+/// runtime that has its time driver enabled.
+#[cfg(feature = "tokio")]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TokioTimer;
+
+#[cfg(feature = "tokio")]
+impl Timer for TokioTimer {
+    type Delay = tokio::time::Sleep;
+
+    #[inline(always)]
+    fn delay(&mut self, duration: Duration) -> Self::Delay {
+        tokio::time::sleep(duration)
+    }
+}
+
+/// A timer that creates delays with `embassy_time::Timer`, using whichever time driver the final
+/// binary links in. Delays too long to be represented by the driver never complete.
+#[cfg(feature = "embassy-time")]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EmbassyTimer;
+
+#[cfg(feature = "embassy-time")]
+impl Timer for EmbassyTimer {
+    type Delay = embassy_time::Timer;
+
+    #[inline(always)]
+    fn delay(&mut self, duration: Duration) -> Self::Delay {
+        let deadline = embassy_time::Duration::try_from(duration).ok().and_then(|duration| embassy_time::Instant::now().checked_add(duration));
+        embassy_time::Timer::at(deadline.unwrap_or(embassy_time::Instant::MAX))
+    }
+}
+
+/// A future that borrows an [`AsyncRwCell`] with the given mutability, or gives up once its
+/// delay completes. The waker of the borrow is removed from the cell if the delay completes first.
+#[must_use = "futures do nothing unless polled"]
+pub struct AsyncBorrowTimeout<'a, M: Mutability, T, D> {
+    /// The borrow being awaited.
+    borrow: AsyncBorrow<'a, M, T>,
+    /// The delay after which the borrow times out.
+    delay: D,
+}
+
+impl<'a, M: Mutability, T, D: Future<Output = ()>> Future for AsyncBorrowTimeout<'a, M, T, D> {
+    type Output = Option<AsyncRwCellGuard<'a, M, T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        if let Poll::Ready(guard) = Pin::new(&mut this.borrow).poll(cx) {
+            return Poll::Ready(Some(guard));
+        }
+
+        match unsafe { Pin::new_unchecked(&mut this.delay) }.poll(cx) {
+            Poll::Ready(()) => {
+                this.borrow.cell.waiters.borrows.unregister(this.borrow.key.take());
+                Poll::Ready(None)
+            },
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<'a, M: Mutability, T, D> fmt::Debug for AsyncBorrowTimeout<'a, M, T, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncBorrowTimeout").field("borrow", &self.borrow).finish_non_exhaustive()
+    }
+}
+
+/// A future that borrows a shared [`AsyncRwCell`] with the given mutability, producing a
+/// guard that keeps the cell alive.
+#[must_use = "futures do nothing unless polled"]
+pub struct AsyncBorrowOwned<M: Mutability, T> {
+    /// The cell to borrow.
+    cell: Arc<AsyncRwCell<T>>,
+    /// The key of this future's waker, if one is registered.
+    key: Option<usize>,
+    /// Marks the mutability of the borrow.
+    marker: PhantomData<fn() -> M>,
+}
+
+impl<M: Mutability, T> AsyncBorrowOwned<M, T> {
+    /// Creates a new future for the given cell.
+    #[inline(always)]
+    fn new(cell: Arc<AsyncRwCell<T>>) -> Self {
+        Self {
+            cell,
+            key: None,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<M: Mutability, T> Future for AsyncBorrowOwned<M, T> {
+    type Output = OwnedAsyncRwCellGuard<M, T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        match this.cell.poll_borrow(&mut this.key, cx) {
+            Poll::Ready(guard) => unsafe { Poll::Ready(OwnedAsyncRwCellGuard::new(guard, this.cell.clone())) },
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<M: Mutability, T> Drop for AsyncBorrowOwned<M, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.cell.waiters.borrows.unregister(self.key.take());
+    }
+}
+
+impl<M: Mutability, T> fmt::Debug for AsyncBorrowOwned<M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncBorrowOwned").field("registered", &self.key.is_some()).finish_non_exhaustive()
+    }
+}
+
+/// An owned guard for an immutable borrow of a shared [`AsyncRwCell`].
+pub type OwnedAsyncReadGuard<T, U = T> = OwnedAsyncRwCellGuard<Const, T, U>;
+
+/// An owned guard for a mutable borrow of a shared [`AsyncRwCell`].
+pub type OwnedAsyncWriteGuard<T, U = T> = OwnedAsyncRwCellGuard<Mut, T, U>;
+
+/// A guard for a borrow of a shared [`AsyncRwCell`], which keeps the cell alive for as long as
+/// the borrow exists and wakes waiting tasks when dropped. Mapping the guard to a portion of the
+/// cell's value changes the type `U` of the borrowed value, while the guard still refers to the
+/// whole cell of type `T`.
+pub struct OwnedAsyncRwCellGuard<M: Mutability, T, U: ?Sized = T> {
+    /// The borrowed value.
+    value: NonNull<U>,
+    /// The borrow state of the cell.
+    state: StateRef<'static>,
+    /// The cell which is borrowed. This is dropped after the borrow is released.
+    cell: Arc<AsyncRwCell<T>>,
+    /// Marks the mutability of the borrow.
+    marker: PhantomData<M>,
+}
+
+impl<M: Mutability, T, U: ?Sized> OwnedAsyncRwCellGuard<M, T, U> {
+    /// Creates a new owned guard from a borrow of the given cell.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, `guard` must borrow the value of `cell`.
+    #[inline(always)]
+    unsafe fn new(guard: RwCellGuard<'_, M, U>, cell: Arc<AsyncRwCell<T>>) -> Self {
+        let (value, state) = guard.into_parts();
+        Self {
+            value,
+            state: StateRef {
+                pointer: state.pointer,
+                marker: PhantomData,
+            },
+            cell,
+            marker: PhantomData,
+        }
+    }
+
+    /// Gets a handle to the cell that this guard borrows.
+    #[inline(always)]
+    pub fn cell(orig: &Self) -> Arc<AsyncRwCell<T>> {
+        orig.cell.clone()
+    }
+
+    /// Replaces the borrowed value of a guard with a portion of it, without releasing the borrow.
+    #[inline(always)]
+    fn with_value<V: ?Sized>(orig: Self, value: NonNull<V>) -> OwnedAsyncRwCellGuard<M, T, V> {
+        let orig = ManuallyDrop::new(orig);
+        OwnedAsyncRwCellGuard {
+            value,
+            state: orig.state.projected(),
+            cell: unsafe { addr_of!(orig.cell).read() },
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T, U: ?Sized> OwnedAsyncRwCellGuard<Const, T, U> {
+    /// Creates a guard for a specific portion of a value.
+    #[inline(always)]
+    pub fn map<V, F>(orig: Self, f: F) -> OwnedAsyncRwCellGuard<Const, T, V>
+    where
+        F: FnOnce(&U) -> &V,
+        V: ?Sized,
+    {
+        let value = NonNull::from(f(&orig));
+        Self::with_value(orig, value)
+    }
+
+    /// Creates a guard for an optional portion of a value. If the portion is absent,
+    /// the original guard is returned.
+    #[inline(always)]
+    pub fn filter_map<V, F>(orig: Self, f: F) -> Result<OwnedAsyncRwCellGuard<Const, T, V>, Self>
+    where
+        F: FnOnce(&U) -> Option<&V>,
+        V: ?Sized,
+    {
+        match f(&orig).map(NonNull::from) {
+            Some(value) => Ok(Self::with_value(orig, value)),
+            None => Err(orig),
+        }
+    }
+}
+
+impl<T, U: ?Sized> OwnedAsyncRwCellGuard<Mut, T, U> {
+    /// Creates a guard for a specific portion of a value.
+    #[inline(always)]
+    pub fn map<V, F>(mut orig: Self, f: F) -> OwnedAsyncRwCellGuard<Mut, T, V>
+    where
+        F: FnOnce(&mut U) -> &mut V,
+        V: ?Sized,
+    {
+        let value = NonNull::from(f(&mut orig));
+        Self::with_value(orig, value)
+    }
+
+    /// Creates a guard for an optional portion of a value. If the portion is absent,
+    /// the original guard is returned.
+    #[inline(always)]
+    pub fn filter_map<V, F>(mut orig: Self, f: F) -> Result<OwnedAsyncRwCellGuard<Mut, T, V>, Self>
+    where
+        F: FnOnce(&mut U) -> Option<&mut V>,
+        V: ?Sized,
+    {
+        match f(&mut orig).map(NonNull::from) {
+            Some(value) => Ok(Self::with_value(orig, value)),
+            None => Err(orig),
+        }
+    }
+
+    /// Converts a mutable guard into an immutable guard without releasing the borrow, so no
+    /// other mutable borrow can occur in between. Tasks waiting to immutably borrow the cell
+    /// are woken.
+    #[inline(always)]
+    pub fn downgrade(orig: Self) -> OwnedAsyncReadGuard<T, U> {
+        let orig = ManuallyDrop::new(orig);
+        orig.state.get().downgrade_write();
+        orig.cell.waiters.release::<Mut>();
+        OwnedAsyncRwCellGuard {
+            value: orig.value,
+            state: orig.state,
+            cell: unsafe { addr_of!(orig.cell).read() },
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<M: Mutability, T, U: ?Sized> Deref for OwnedAsyncRwCellGuard<M, T, U> {
+    type Target = U;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<T, U: ?Sized> DerefMut for OwnedAsyncRwCellGuard<Mut, T, U> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<M: Mutability, T, U: ?Sized> Drop for OwnedAsyncRwCellGuard<M, T, U> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.state.get().release::<M>();
+        self.cell.waiters.release::<M>();
+    }
+}
+
+impl<M: Mutability, T, U: ?Sized + fmt::Debug> fmt::Debug for OwnedAsyncRwCellGuard<M, T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+unsafe impl<T: Send + Sync, U: ?Sized + Sync> Send for OwnedAsyncRwCellGuard<Const, T, U> {}
+unsafe impl<T: Send + Sync, U: ?Sized + Sync> Sync for OwnedAsyncRwCellGuard<Const, T, U> {}
+unsafe impl<T: Send + Sync, U: ?Sized + Send> Send for OwnedAsyncRwCellGuard<Mut, T, U> {}
+unsafe impl<T: Send + Sync, U: ?Sized + Sync> Sync for OwnedAsyncRwCellGuard<Mut, T, U> {}
+
+/// The tasks waiting on an [`AsyncRwCell`].
+#[derive(Debug, Default)]
+struct AsyncWaiters {
+    /// The tasks waiting for borrows to be released.
+    borrows: WakerQueue,
+    /// The number of mutable borrows that have been released, wrapping on overflow. Every
+    /// guard of the cell is released through [`AsyncWaiters::release`], which is the only place
+    /// that this is incremented, since the underlying cell is never borrowed in any other way.
+    version: AtomicUsize,
+    /// The tasks waiting for the version to change.
+    changes: WakerQueue,
+}
+
+impl AsyncWaiters {
+    /// Creates a new set of waiters for an unborrowed cell.
+    #[inline(always)]
+    const fn new() -> Self {
+        Self {
+            borrows: WakerQueue::new(),
+            version: AtomicUsize::new(0),
+            changes: WakerQueue::new(),
+        }
+    }
+
+    /// Wakes the appropriate tasks after a borrow with the given mutability is released.
+    #[inline(always)]
+    fn release<M: Mutability>(&self) {
+        if TypeId::of::<M>() == TypeId::of::<Mut>() {
+            self.version.fetch_add(1, Ordering::AcqRel);
+            self.changes.wake_all();
+        }
+
+        self.borrows.wake_all();
+    }
+}
+
+/// Wakes the waiting tasks of an [`AsyncRwCell`] when dropped, as though a borrow with the
+/// given mutability was released.
+struct WaitersRelease<'a, M: Mutability> {
+    /// The tasks to wake.
+    waiters: &'a AsyncWaiters,
+    /// Marks the mutability of the borrow.
+    marker: PhantomData<M>,
+}
+
+impl<'a, M: Mutability> WaitersRelease<'a, M> {
+    /// Creates a new release for the given waiting tasks.
+    #[inline(always)]
+    fn new(waiters: &'a AsyncWaiters) -> Self {
+        Self {
+            waiters,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, M: Mutability> Drop for WaitersRelease<'a, M> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.waiters.release::<M>();
+    }
+}
+
+/// A list of tasks that are waiting for a cell to be released.
+#[derive(Debug, Default)]
+struct WakerQueue {
+    /// The number of registered wakers.
+    waiters: AtomicUsize,
+    /// The registered wakers, which are only borrowed briefly.
+    list: RwCell<WakerList>,
+}
+
+impl WakerQueue {
+    /// Creates a new, empty queue.
+    #[inline(always)]
+    const fn new() -> Self {
+        Self {
+            waiters: AtomicUsize::new(0),
+            list: RwCell::new(WakerList {
+                next_key: 0,
+                entries: Vec::new(),
+            }),
+        }
+    }
+
+    /// Registers a waker, or replaces the waker previously registered with the same key.
+    /// The waiter count is updated before returning, so the caller must check the cell again
+    /// afterward to avoid missing a release.
+    fn register(&self, key: &mut Option<usize>, waker: &Waker) {
+        let mut list = self.list.borrow_mut_blocking();
+        if let Some(entry) = key.and_then(|key| list.entries.iter_mut().find(|(k, _)| *k == key)) {
+            entry.1.clone_from(waker);
+        }
+        else {
+            let new_key = *key.get_or_insert_with(|| {
+                let next = list.next_key;
+                list.next_key = next.wrapping_add(1);
+                next
+            });
+            list.entries.push((new_key, waker.clone()));
+        }
+
+        self.waiters.store(list.entries.len(), Ordering::SeqCst);
+    }
+
+    /// Removes the waker registered with the given key, if any.
+    fn unregister(&self, key: Option<usize>) {
+        if let Some(key) = key {
+            let mut list = self.list.borrow_mut_blocking();
+            if let Some(index) = list.entries.iter().position(|(k, _)| *k == key) {
+                list.entries.swap_remove(index);
+                self.waiters.store(list.entries.len(), Ordering::SeqCst);
+            }
+        }
+    }
+
+    /// Wakes and removes every registered waker. The wakers are invoked after the list is
+    /// released, so that tasks polled during waking may register again.
+    #[inline(always)]
+    fn wake_all(&self) {
+        fence(Ordering::SeqCst);
+        if self.waiters.load(Ordering::SeqCst) != 0 {
+            let entries = {
+                let mut list = self.list.borrow_mut_blocking();
+                self.waiters.store(0, Ordering::SeqCst);
+                take(&mut list.entries)
+            };
+
+            for (_, waker) in entries {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// The wakers registered with a [`WakerQueue`].
+#[derive(Debug, Default)]
+struct WakerList {
+    /// The key to assign to the next registered waker.
+    next_key: usize,
+    /// The registered wakers and their keys.
+    entries: Vec<(usize, Waker)>,
+}
@@ -0,0 +1,360 @@
+use crate::*;
+
+/// A value that can be stored in an [`AtomicRwCell`].
+///
+/// # Safety
+///
+/// For this trait to be implemented soundly, the type must not contain any padding or
+/// otherwise uninitialized bytes, since the lock-free representation reinterprets values
+/// as integers.
+pub unsafe trait AtomicValue: Copy {
+    /// How an [`AtomicRwCell`] stores this type. This is the atomic integer type with the same
+    /// size, such as [`AtomicU32`], for the lock-free representation, or [`Locked`] otherwise.
+    /// Naming an atomic integer of a different size fails to compile when a cell is created.
+    type Storage: AtomicStorage;
+}
+
+unsafe impl AtomicValue for bool {
+    type Storage = AtomicU8;
+}
+
+unsafe impl AtomicValue for char {
+    type Storage = AtomicU32;
+}
+
+unsafe impl AtomicValue for f32 {
+    type Storage = AtomicU32;
+}
+
+unsafe impl AtomicValue for f64 {
+    #[cfg(target_has_atomic = "64")]
+    type Storage = AtomicU64;
+    #[cfg(not(target_has_atomic = "64"))]
+    type Storage = Locked;
+}
+
+unsafe impl AtomicValue for i8 {
+    type Storage = AtomicU8;
+}
+
+unsafe impl AtomicValue for i16 {
+    type Storage = AtomicU16;
+}
+
+unsafe impl AtomicValue for i32 {
+    type Storage = AtomicU32;
+}
+
+unsafe impl AtomicValue for i64 {
+    #[cfg(target_has_atomic = "64")]
+    type Storage = AtomicU64;
+    #[cfg(not(target_has_atomic = "64"))]
+    type Storage = Locked;
+}
+
+unsafe impl AtomicValue for i128 {
+    type Storage = Locked;
+}
+
+unsafe impl AtomicValue for isize {
+    type Storage = AtomicUsize;
+}
+
+unsafe impl AtomicValue for u8 {
+    type Storage = AtomicU8;
+}
+
+unsafe impl AtomicValue for u16 {
+    type Storage = AtomicU16;
+}
+
+unsafe impl AtomicValue for u32 {
+    type Storage = AtomicU32;
+}
+
+unsafe impl AtomicValue for u64 {
+    #[cfg(target_has_atomic = "64")]
+    type Storage = AtomicU64;
+    #[cfg(not(target_has_atomic = "64"))]
+    type Storage = Locked;
+}
+
+unsafe impl AtomicValue for u128 {
+    type Storage = Locked;
+}
+
+unsafe impl AtomicValue for usize {
+    type Storage = AtomicUsize;
+}
+
+unsafe impl AtomicValue for () {
+    type Storage = Locked;
+}
+
+unsafe impl<T: AtomicValue, const N: usize> AtomicValue for [T; N] {
+    type Storage = Locked;
+}
+
+/// A representation of an [`AtomicRwCell`], selected by [`AtomicValue::Storage`]. This is
+/// implemented by the atomic integer types and by [`Locked`], and cannot be implemented
+/// outside of this crate.
+pub trait AtomicStorage: sealed::Storage {}
+
+/// Selects the locking representation of an [`AtomicRwCell`], in which the value is guarded by
+/// a borrow counter and operations spin while the value is borrowed by another thread.
+#[derive(Debug)]
+pub enum Locked {}
+
+/// Defines the operations of an [`AtomicStorage`], which are private to this crate.
+mod sealed {
+    use super::*;
+
+    /// The operations of an [`AtomicStorage`]. Each operation takes the value of the cell along
+    /// with the lock that guards it.
+    pub trait Storage {
+        /// The state that guards the value, which is empty for the lock-free representation.
+        type Lock;
+        /// The state that guards the value of a new cell.
+        const LOCK: Self::Lock;
+        /// The size of the atomic integer that stores the value, or `None` for the locking
+        /// representation.
+        const SIZE: Option<usize>;
+
+        /// Gets a copy of the value.
+        ///
+        /// # Safety
+        ///
+        /// For this function to be sound, the value must have the size of [`Storage::SIZE`],
+        /// and be aligned for the atomic integer.
+        unsafe fn load<T: Copy>(value: &UnsafeCell<T>, lock: &Self::Lock) -> T;
+
+        /// Replaces the value, returning the previous value.
+        ///
+        /// # Safety
+        ///
+        /// For this function to be sound, the same conditions as [`Storage::load`] must hold.
+        unsafe fn swap<T: Copy>(value: &UnsafeCell<T>, lock: &Self::Lock, new: T) -> T;
+
+        /// Replaces the value if it is bitwise equal to `current`. Returns the previous value on
+        /// success, or the current value on failure.
+        ///
+        /// # Safety
+        ///
+        /// For this function to be sound, the same conditions as [`Storage::load`] must hold.
+        unsafe fn compare_exchange<T: Copy>(value: &UnsafeCell<T>, lock: &Self::Lock, current: T, new: T) -> Result<T, T>;
+    }
+
+    /// The borrow counter that guards the value of a cell with the [`Locked`] representation.
+    pub struct BorrowLock(RwCellState);
+
+    impl Storage for Locked {
+        type Lock = BorrowLock;
+        #[allow(clippy::declare_interior_mutable_const)]
+        const LOCK: BorrowLock = BorrowLock(RwCellState::new());
+        const SIZE: Option<usize> = None;
+
+        #[inline(always)]
+        unsafe fn load<T: Copy>(value: &UnsafeCell<T>, BorrowLock(lock): &BorrowLock) -> T {
+            lock.spin_acquire_read();
+            let result = *value.get();
+            lock.release::<Const>();
+            result
+        }
+
+        #[inline(always)]
+        unsafe fn swap<T: Copy>(value: &UnsafeCell<T>, BorrowLock(lock): &BorrowLock, new: T) -> T {
+            lock.spin_acquire_write();
+            let result = replace(&mut *value.get(), new);
+            lock.release::<Mut>();
+            result
+        }
+
+        #[inline(always)]
+        unsafe fn compare_exchange<T: Copy>(value: &UnsafeCell<T>, BorrowLock(lock): &BorrowLock, current: T, new: T) -> Result<T, T> {
+            lock.spin_acquire_write();
+            let value = &mut *value.get();
+            let result = if bits_eq(value, &current) { Ok(replace(value, new)) } else { Err(*value) };
+            lock.release::<Mut>();
+            result
+        }
+    }
+
+    /// Determines whether two values are bitwise equal.
+    #[inline(always)]
+    fn bits_eq<T>(a: &T, b: &T) -> bool {
+        unsafe {
+            core::slice::from_raw_parts((a as *const T).cast::<u8>(), size_of::<T>())
+                == core::slice::from_raw_parts((b as *const T).cast::<u8>(), size_of::<T>())
+        }
+    }
+}
+
+/// Implements the lock-free representation for atomic integer types.
+macro_rules! impl_atomic_storage {
+    ($($(#[$attr: meta])* $atomic: ident),*) => {
+        $(
+            $(#[$attr])*
+            impl AtomicStorage for $atomic {}
+
+            $(#[$attr])*
+            impl sealed::Storage for $atomic {
+                type Lock = ();
+                const LOCK: () = ();
+                const SIZE: Option<usize> = Some(size_of::<$atomic>());
+
+                #[inline(always)]
+                unsafe fn load<T: Copy>(value: &UnsafeCell<T>, _: &()) -> T {
+                    transmute_copy(&$atomic::from_ptr(value.get().cast()).load(Ordering::Acquire))
+                }
+
+                #[inline(always)]
+                unsafe fn swap<T: Copy>(value: &UnsafeCell<T>, _: &(), new: T) -> T {
+                    transmute_copy(&$atomic::from_ptr(value.get().cast()).swap(transmute_copy(&new), Ordering::AcqRel))
+                }
+
+                #[inline(always)]
+                unsafe fn compare_exchange<T: Copy>(value: &UnsafeCell<T>, _: &(), current: T, new: T) -> Result<T, T> {
+                    $atomic::from_ptr(value.get().cast())
+                        .compare_exchange(transmute_copy(&current), transmute_copy(&new), Ordering::AcqRel, Ordering::Acquire)
+                        .map(|x| transmute_copy(&x))
+                        .map_err(|x| transmute_copy(&x))
+                }
+            }
+        )*
+    };
+}
+
+impl_atomic_storage!(
+    AtomicU8,
+    AtomicU16,
+    AtomicU32,
+    #[cfg(target_has_atomic = "64")]
+    AtomicU64,
+    AtomicUsize
+);
+
+impl AtomicStorage for Locked {}
+
+/// A cell for small [`Copy`] values, which never aborts. The representation is chosen by
+/// [`AtomicValue::Storage`]. When the type is stored in an atomic integer, the cell is the same
+/// size as the value, and every operation is a single lock-free atomic operation. Otherwise,
+/// the value is guarded by a borrow counter, and operations spin while the value is borrowed
+/// by another thread.
+///
+/// Values are compared bitwise by [`AtomicRwCell::compare_exchange`] and
+/// [`AtomicRwCell::fetch_update`].
+#[repr(C)]
+pub struct AtomicRwCell<T: AtomicValue> {
+    /// Aligns the value for the atomic integer that stores it.
+    align: [T::Storage; 0],
+    /// The value.
+    value: UnsafeCell<T>,
+    /// The state that guards the value on the locking path.
+    lock: <T::Storage as sealed::Storage>::Lock,
+}
+
+impl<T: AtomicValue> AtomicRwCell<T> {
+    /// Whether the lock-free representation is used for this type.
+    const LOCK_FREE: bool = <T::Storage as sealed::Storage>::SIZE.is_some();
+    /// Fails to compile if the atomic integer that stores the value has a different size.
+    const CHECK_SIZE: () = assert!(
+        match <T::Storage as sealed::Storage>::SIZE {
+            Some(size) => size == size_of::<T>(),
+            None => true,
+        },
+        "The atomic storage of a value must have the same size as the value."
+    );
+
+    /// Creates a new cell that wraps the provided value.
+    #[inline(always)]
+    pub const fn new(value: T) -> Self {
+        let () = Self::CHECK_SIZE;
+        Self {
+            align: [],
+            value: UnsafeCell::new(value),
+            lock: <T::Storage as sealed::Storage>::LOCK,
+        }
+    }
+
+    /// Determines whether operations on this cell are lock-free.
+    #[inline(always)]
+    pub const fn is_lock_free() -> bool {
+        Self::LOCK_FREE
+    }
+
+    /// Consumes this cell, returning the wrapped value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    /// Gets a mutable reference to the value. This requires no synchronization,
+    /// since the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// Gets a copy of the value.
+    #[inline(always)]
+    pub fn get(&self) -> T {
+        unsafe { <T::Storage as sealed::Storage>::load(&self.value, &self.lock) }
+    }
+
+    /// Sets the value.
+    #[inline(always)]
+    pub fn set(&self, value: T) {
+        self.swap(value);
+    }
+
+    /// Sets the value, returning the previous value.
+    #[inline(always)]
+    pub fn swap(&self, value: T) -> T {
+        unsafe { <T::Storage as sealed::Storage>::swap(&self.value, &self.lock, value) }
+    }
+
+    /// Sets the value to `new` if it is bitwise equal to `current`. Returns the previous value
+    /// on success, or the current value on failure.
+    #[inline(always)]
+    pub fn compare_exchange(&self, current: T, new: T) -> Result<T, T> {
+        unsafe { <T::Storage as sealed::Storage>::compare_exchange(&self.value, &self.lock, current, new) }
+    }
+
+    /// Repeatedly applies `f` to the value and attempts to store the result, until the value
+    /// is unchanged in between. Returns the previous value on success, or the current value
+    /// if `f` returns `None`.
+    #[inline(always)]
+    pub fn fetch_update(&self, mut f: impl FnMut(T) -> Option<T>) -> Result<T, T> {
+        let mut current = self.get();
+        while let Some(new) = f(current) {
+            match self.compare_exchange(current, new) {
+                Ok(previous) => return Ok(previous),
+                Err(actual) => current = actual,
+            }
+        }
+
+        Err(current)
+    }
+}
+
+impl<T: AtomicValue + Default> Default for AtomicRwCell<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: AtomicValue + fmt::Debug> fmt::Debug for AtomicRwCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AtomicRwCell").field(&self.get()).finish()
+    }
+}
+
+impl<T: AtomicValue> From<T> for AtomicRwCell<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+unsafe impl<T: AtomicValue + Send> Sync for AtomicRwCell<T> {}
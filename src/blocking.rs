@@ -0,0 +1,227 @@
+use crate::*;
+
+/// Determines which borrows of a [`BlockingRwCell`] are granted first when readers and
+/// writers are waiting at the same time.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FairnessPolicy {
+    /// Once a writer is waiting, new immutable borrows wait behind it, so that a continuous
+    /// stream of readers cannot starve writers.
+    #[default]
+    WritePreferring,
+    /// Immutable borrows are granted whenever the cell is not mutably borrowed, even while
+    /// writers are waiting. This maximizes read throughput, but writers may be starved.
+    ReadPreferring,
+}
+
+/// A cell whose borrows park the current thread until they can be acquired, rather than
+/// aborting. The order in which waiting borrows are granted is chosen by a [`FairnessPolicy`].
+///
+/// Under the write-preferring policy, a thread that immutably borrows the cell again while
+/// already holding an immutable borrow will deadlock if a writer begins waiting in between.
+///
+/// The cell does not dereference to a [`RwCell`], since immutable borrows made through it would
+/// ignore the fairness policy.
+#[derive(Debug, Default)]
+pub struct BlockingRwCell<T> {
+    /// The underlying cell.
+    cell: RwCell<T>,
+    /// The fairness policy of the cell.
+    policy: FairnessPolicy,
+    /// The number of threads waiting to mutably borrow the cell.
+    waiting_writers: AtomicUsize,
+}
+
+impl<T> BlockingRwCell<T> {
+    /// Creates a new cell that wraps the provided value, using the default policy. This is the
+    /// same as [`BlockingRwCell::new_fair`].
+    #[inline(always)]
+    pub const fn new(value: T) -> Self {
+        Self::new_fair(value)
+    }
+
+    /// Creates a new cell that wraps the provided value, using the write-preferring policy.
+    #[inline(always)]
+    pub const fn new_fair(value: T) -> Self {
+        Self::with_policy(value, FairnessPolicy::WritePreferring)
+    }
+
+    /// Creates a new cell that wraps the provided value, using the read-preferring policy.
+    #[inline(always)]
+    pub const fn new_read_preferring(value: T) -> Self {
+        Self::with_policy(value, FairnessPolicy::ReadPreferring)
+    }
+
+    /// Creates a new cell that wraps the provided value, using the given policy.
+    #[inline(always)]
+    pub const fn with_policy(value: T, policy: FairnessPolicy) -> Self {
+        Self {
+            cell: RwCell::new(value),
+            policy,
+            waiting_writers: AtomicUsize::new(0),
+        }
+    }
+
+    /// Gets the fairness policy of this cell.
+    #[inline(always)]
+    pub fn policy(&self) -> FairnessPolicy {
+        self.policy
+    }
+
+    /// Consumes this cell, returning the wrapped value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.cell.into_inner()
+    }
+
+    /// Gets a mutable reference to the value. This requires no borrow accounting,
+    /// since the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.cell.get_mut()
+    }
+
+    /// Determines whether this cell is free to be borrowed.
+    #[inline(always)]
+    pub fn free(&self) -> bool {
+        self.cell.free()
+    }
+
+    /// Takes a snapshot of this cell's borrow state. The result is racy in the same way as
+    /// [`RwCell::borrow_state`].
+    #[inline(always)]
+    pub fn borrow_state(&self) -> BorrowState {
+        self.cell.borrow_state()
+    }
+
+    /// Gets the number of immutable borrows of this cell. This is zero if the cell is
+    /// mutably borrowed.
+    #[inline(always)]
+    pub fn reader_count(&self) -> usize {
+        self.cell.reader_count()
+    }
+
+    /// Determines whether this cell is mutably borrowed.
+    #[inline(always)]
+    pub fn is_locked_exclusive(&self) -> bool {
+        self.cell.is_locked_exclusive()
+    }
+
+    /// Gets the number of threads that are currently waiting to mutably borrow this cell. The
+    /// result may be outdated as soon as it is returned.
+    #[inline(always)]
+    pub fn waiting_writers(&self) -> usize {
+        self.waiting_writers.load(Ordering::Relaxed)
+    }
+
+    /// Attempts to immutably borrow the value of this cell without parking. This fails if the
+    /// cell is mutably borrowed, or if the fairness policy requires the borrow to wait behind
+    /// a writer.
+    #[inline(always)]
+    pub fn try_borrow(&self) -> Result<RwCellGuard<'_, Const, T>, BorrowError> {
+        match self.policy {
+            FairnessPolicy::WritePreferring => self.try_borrow_fair().ok_or(BorrowError),
+            FairnessPolicy::ReadPreferring => self.cell.try_borrow(),
+        }
+    }
+
+    /// Attempts to mutably borrow the value of this cell without parking, returning an error
+    /// if any other borrows exist.
+    #[inline(always)]
+    pub fn try_borrow_mut(&self) -> Result<RwCellGuard<'_, Mut, T>, BorrowMutError> {
+        self.cell.try_borrow_mut()
+    }
+
+    /// Immutably borrows the value of this cell, parking the current thread until the borrow
+    /// is granted by the fairness policy.
+    #[inline(always)]
+    pub fn borrow(&self) -> RwCellGuard<'_, Const, T> {
+        match self.policy {
+            FairnessPolicy::WritePreferring => ParkingBucket::park_until(&self.cell.inner.state.counter, || self.try_borrow_fair()),
+            FairnessPolicy::ReadPreferring => self.cell.borrow_wait(),
+        }
+    }
+
+    /// Mutably borrows the value of this cell, parking the current thread until all other
+    /// borrows are released. Aborts if the cell is frozen.
+    #[inline(always)]
+    pub fn borrow_mut(&self) -> RwCellGuard<'_, Mut, T> {
+        let _registration = WaiterRegistration::new(&self.waiting_writers);
+        self.cell.borrow_mut_wait()
+    }
+
+    /// Attempts to immutably borrow the value of this cell, parking the current thread for up
+    /// to `timeout` until the borrow is granted by the fairness policy. Returns [`None`] if the
+    /// borrow could not be acquired in time.
+    #[inline(always)]
+    pub fn try_borrow_for(&self, timeout: core::time::Duration) -> Option<RwCellGuard<'_, Const, T>> {
+        self.try_borrow_deadline(std::time::Instant::now().checked_add(timeout))
+    }
+
+    /// Attempts to immutably borrow the value of this cell, parking the current thread until
+    /// `deadline` until the borrow is granted by the fairness policy. Returns [`None`] if the
+    /// borrow could not be acquired in time.
+    #[inline(always)]
+    pub fn try_borrow_until(&self, deadline: std::time::Instant) -> Option<RwCellGuard<'_, Const, T>> {
+        self.try_borrow_deadline(Some(deadline))
+    }
+
+    /// Attempts to mutably borrow the value of this cell, parking the current thread for up
+    /// to `timeout` while other borrows exist. Returns [`None`] if the borrow could not be
+    /// acquired in time, or if the cell is frozen.
+    #[inline(always)]
+    pub fn try_borrow_mut_for(&self, timeout: core::time::Duration) -> Option<RwCellGuard<'_, Mut, T>> {
+        self.try_borrow_mut_deadline(std::time::Instant::now().checked_add(timeout))
+    }
+
+    /// Attempts to mutably borrow the value of this cell, parking the current thread until
+    /// `deadline` while other borrows exist. Returns [`None`] if the borrow could not be
+    /// acquired in time, or if the cell is frozen.
+    #[inline(always)]
+    pub fn try_borrow_mut_until(&self, deadline: std::time::Instant) -> Option<RwCellGuard<'_, Mut, T>> {
+        self.try_borrow_mut_deadline(Some(deadline))
+    }
+
+    /// Attempts to immutably borrow the value of this cell if no writers are waiting.
+    #[inline(always)]
+    fn try_borrow_fair(&self) -> Option<RwCellGuard<'_, Const, T>> {
+        if self.waiting_writers.load(Ordering::SeqCst) == 0 {
+            self.cell.try_borrow().ok()
+        }
+        else {
+            None
+        }
+    }
+
+    /// Attempts to immutably borrow the value of this cell, parking the current thread until
+    /// the deadline passes. A missing deadline waits forever.
+    #[inline(always)]
+    fn try_borrow_deadline(&self, deadline: Option<std::time::Instant>) -> Option<RwCellGuard<'_, Const, T>> {
+        match self.policy {
+            FairnessPolicy::WritePreferring => ParkingBucket::park_until_deadline(&self.cell.inner.state.counter, deadline, || self.try_borrow_fair()),
+            FairnessPolicy::ReadPreferring => self.cell.try_borrow_deadline(deadline),
+        }
+    }
+
+    /// Attempts to mutably borrow the value of this cell, parking the current thread until
+    /// the deadline passes. A missing deadline waits forever. If the borrow times out, readers
+    /// that were waiting behind this writer are woken.
+    #[inline(always)]
+    fn try_borrow_mut_deadline(&self, deadline: Option<std::time::Instant>) -> Option<RwCellGuard<'_, Mut, T>> {
+        let registration = WaiterRegistration::new(&self.waiting_writers);
+        let result = self.cell.try_borrow_mut_deadline(deadline);
+        drop(registration);
+
+        if result.is_none() {
+            ParkingBucket::unpark(&self.cell.inner.state.counter);
+        }
+
+        result
+    }
+}
+
+impl<T> From<T> for BlockingRwCell<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
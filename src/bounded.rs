@@ -0,0 +1,244 @@
+use crate::*;
+
+/// A cell that allows at most a configured number of immutable borrows at once, so that it can
+/// limit concurrent access to a resource. Once the limit is reached, further immutable borrows
+/// conflict exactly as they would while the cell is mutably borrowed. The limit is checked by
+/// the same atomic update that records each borrow, so it holds under contention.
+///
+/// The cell does not dereference to a [`RwCell`], and its borrows return [`BoundedRwCellGuard`]s
+/// rather than ordinary guards, since cloning or splitting an ordinary guard would add
+/// immutable borrows that bypass the limit.
+#[derive(Debug)]
+pub struct BoundedRwCell<T> {
+    /// The underlying cell.
+    cell: RwCell<T>,
+    /// The largest number of immutable borrows that may exist at once.
+    max_readers: u16,
+}
+
+impl<T> BoundedRwCell<T> {
+    /// Creates a new cell that wraps the provided value, and allows at most `max_readers`
+    /// immutable borrows at once. The limit is capped at the number of immutable borrows that
+    /// a [`RwCell`] supports.
+    #[inline(always)]
+    pub const fn with_max_readers(value: T, max_readers: usize) -> Self {
+        Self {
+            cell: RwCell::new(value),
+            max_readers: if max_readers < MAX_READERS as usize { max_readers as u16 } else { MAX_READERS },
+        }
+    }
+
+    /// Consumes this cell, returning the wrapped value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.cell.into_inner()
+    }
+
+    /// Gets a mutable reference to the value. This requires no borrow accounting,
+    /// since the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.cell.get_mut()
+    }
+
+    /// Gets the largest number of immutable borrows that this cell allows at once.
+    #[inline(always)]
+    pub fn max_readers(&self) -> usize {
+        self.max_readers as usize
+    }
+
+    /// Gets the number of immutable borrows of this cell, which never exceeds
+    /// [`BoundedRwCell::max_readers`]. This is zero if the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn reader_count(&self) -> usize {
+        self.cell.reader_count()
+    }
+
+    /// Gets the current borrow state of this cell. The result is racy in the same way as
+    /// [`RwCell::borrow_state`].
+    #[inline(always)]
+    pub fn borrow_state(&self) -> BorrowState {
+        self.cell.borrow_state()
+    }
+
+    /// Immutably borrows the value of this cell. Aborts if the cell is mutably borrowed, or
+    /// already has the maximum number of immutable borrows.
+    #[inline(always)]
+    pub fn borrow(&self) -> BoundedRwCellGuard<'_, Const, T> {
+        match self.try_borrow() {
+            Ok(guard) => guard,
+            Err(_) if self.cell.is_locked_exclusive() => AbortPanic::abort("Attempted to immutably borrow cell while it was mutably borrowed."),
+            Err(_) => AbortPanic::abort("Attempted to immutably borrow cell too many times."),
+        }
+    }
+
+    /// Attempts to immutably borrow the value of this cell, returning an error instead of
+    /// aborting if the cell is mutably borrowed, or already has the maximum number of
+    /// immutable borrows.
+    #[inline(always)]
+    pub fn try_borrow(&self) -> Result<BoundedRwCellGuard<'_, Const, T>, BorrowError> {
+        if self.cell.inner.state.try_acquire_read_bounded(self.max_readers) {
+            unsafe { Ok(self.bounded(self.cell.read_guard())) }
+        }
+        else {
+            Err(BorrowError)
+        }
+    }
+
+    /// Mutably borrows the value of this cell. Aborts if any other borrows exist.
+    #[inline(always)]
+    pub fn borrow_mut(&self) -> BoundedRwCellGuard<'_, Mut, T> {
+        self.bounded(self.cell.borrow_mut())
+    }
+
+    /// Attempts to mutably borrow the value of this cell, returning an error
+    /// instead of aborting if any other borrows exist.
+    #[inline(always)]
+    pub fn try_borrow_mut(&self) -> Result<BoundedRwCellGuard<'_, Mut, T>, BorrowMutError> {
+        Ok(self.bounded(self.cell.try_borrow_mut()?))
+    }
+
+    /// Wraps a guard for this cell so that it enforces the cell's limit.
+    #[inline(always)]
+    fn bounded<'a, M: Mutability>(&'a self, guard: RwCellGuard<'a, M, T>) -> BoundedRwCellGuard<'a, M, T> {
+        BoundedRwCellGuard {
+            guard,
+            max_readers: self.max_readers,
+        }
+    }
+}
+
+/// A guard for a borrow of a [`BoundedRwCell`]. Unlike an ordinary guard, it cannot be split, and
+/// cloning it respects the limit of the cell.
+pub struct BoundedRwCellGuard<'a, M: Mutability, T: 'a + ?Sized> {
+    /// The underlying borrow.
+    guard: RwCellGuard<'a, M, T>,
+    /// The largest number of immutable borrows that the cell allows at once.
+    max_readers: u16,
+}
+
+impl<'a, T: 'a + ?Sized> BoundedRwCellGuard<'a, Const, T> {
+    /// Creates a reference to a specific portion of a value.
+    #[inline(always)]
+    pub fn map<U, F>(orig: Self, f: F) -> BoundedRwCellGuard<'a, Const, U>
+    where
+        F: FnOnce(&T) -> &U,
+        U: ?Sized,
+    {
+        BoundedRwCellGuard {
+            guard: RwCellGuard::<Const, _>::map(orig.guard, f),
+            max_readers: orig.max_readers,
+        }
+    }
+
+    /// Creates a reference to an optional portion of a value. If the portion is absent,
+    /// the original guard is returned.
+    #[inline(always)]
+    pub fn filter_map<U, F>(orig: Self, f: F) -> Result<BoundedRwCellGuard<'a, Const, U>, Self>
+    where
+        F: FnOnce(&T) -> Option<&U>,
+        U: ?Sized,
+    {
+        let max_readers = orig.max_readers;
+        match RwCellGuard::<Const, _>::filter_map(orig.guard, f) {
+            Ok(guard) => Ok(BoundedRwCellGuard { guard, max_readers }),
+            Err(guard) => Err(Self { guard, max_readers }),
+        }
+    }
+
+    /// Creates another guard for the same value. This adds an immutable borrow to the cell, so
+    /// it aborts if the cell already has the maximum number of immutable borrows.
+    #[allow(clippy::should_implement_trait)]
+    #[inline(always)]
+    pub fn clone(orig: &Self) -> Self {
+        match Self::try_clone(orig) {
+            Ok(guard) => guard,
+            Err(_) => AbortPanic::abort("Attempted to immutably borrow cell too many times."),
+        }
+    }
+
+    /// Attempts to create another guard for the same value, returning an error instead of
+    /// aborting if the cell already has the maximum number of immutable borrows.
+    #[inline(always)]
+    pub fn try_clone(orig: &Self) -> Result<Self, BorrowError> {
+        let state = orig.guard.state;
+        if state.get().try_acquire_read_bounded(orig.max_readers) {
+            Ok(Self {
+                guard: unsafe { RwCellGuard::from_parts(orig.guard.value, state) },
+                max_readers: orig.max_readers,
+            })
+        }
+        else {
+            Err(BorrowError)
+        }
+    }
+}
+
+impl<'a, T: 'a + ?Sized> BoundedRwCellGuard<'a, Mut, T> {
+    /// Creates a reference to a specific portion of a value.
+    #[inline(always)]
+    pub fn map<U, F>(orig: Self, f: F) -> BoundedRwCellGuard<'a, Mut, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+        U: ?Sized,
+    {
+        BoundedRwCellGuard {
+            guard: RwCellGuard::<Mut, _>::map(orig.guard, f),
+            max_readers: orig.max_readers,
+        }
+    }
+
+    /// Creates a reference to an optional portion of a value. If the portion is absent,
+    /// the original guard is returned.
+    #[inline(always)]
+    pub fn filter_map<U, F>(orig: Self, f: F) -> Result<BoundedRwCellGuard<'a, Mut, U>, Self>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+        U: ?Sized,
+    {
+        let max_readers = orig.max_readers;
+        match RwCellGuard::<Mut, _>::filter_map(orig.guard, f) {
+            Ok(guard) => Ok(BoundedRwCellGuard { guard, max_readers }),
+            Err(guard) => Err(Self { guard, max_readers }),
+        }
+    }
+
+    /// Converts a mutable guard into an immutable guard without releasing the borrow, so no
+    /// other mutable borrow can occur in between. Aborts if the guard was produced by
+    /// splitting another guard, and the other parts still exist.
+    #[inline(always)]
+    pub fn downgrade(orig: Self) -> BoundedRwCellGuard<'a, Const, T> {
+        BoundedRwCellGuard {
+            guard: RwCellGuard::<Mut, _>::downgrade(orig.guard),
+            max_readers: orig.max_readers,
+        }
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized> Deref for BoundedRwCellGuard<'a, M, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<'a, T: 'a + ?Sized> DerefMut for BoundedRwCellGuard<'a, Mut, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized + fmt::Debug> fmt::Debug for BoundedRwCellGuard<'a, M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized + fmt::Display> fmt::Display for BoundedRwCellGuard<'a, M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
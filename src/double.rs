@@ -0,0 +1,190 @@
+use crate::*;
+
+/// A cell with two buffers, where readers always borrow the front buffer and a writer mutates
+/// the back buffer. Dropping the write guard publishes the back buffer by swapping the two, so
+/// readers never conflict with the writer. A reader that is still borrowing the old front
+/// buffer keeps it pinned: the next writer waits for that reader to finish, instead of the
+/// reader being aborted.
+///
+/// The back buffer holds the value that was published before the current front one, so writers
+/// usually overwrite it entirely, or first copy the front value with
+/// [`DoubleBufferedWriteGuard::front`].
+#[derive(Default)]
+pub struct DoubleBufferedCell<T> {
+    /// The two buffers.
+    buffers: [RwCell<T>; 2],
+    /// The index of the front buffer.
+    front: AtomicUsize,
+    /// Whether a writer currently holds the back buffer.
+    writing: AtomicBool,
+}
+
+impl<T> DoubleBufferedCell<T> {
+    /// Creates a new cell with the given front and back buffers.
+    #[inline(always)]
+    pub const fn from_buffers(front: T, back: T) -> Self {
+        Self {
+            buffers: [RwCell::new(front), RwCell::new(back)],
+            front: AtomicUsize::new(0),
+            writing: AtomicBool::new(false),
+        }
+    }
+
+    /// Creates a new cell where both buffers hold the provided value.
+    #[inline(always)]
+    pub fn new(value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_buffers(value.clone(), value)
+    }
+
+    /// Consumes this cell, returning the value of the front buffer.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.into_buffers().0
+    }
+
+    /// Consumes this cell, returning the values of the front and back buffers.
+    #[inline(always)]
+    pub fn into_buffers(self) -> (T, T) {
+        let front = self.front.into_inner();
+        let [first, second] = self.buffers.map(RwCell::into_inner);
+        if front == 0 {
+            (first, second)
+        }
+        else {
+            (second, first)
+        }
+    }
+
+    /// Gets a mutable reference to the value of the front buffer. This requires no borrow
+    /// accounting, since the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T {
+        let front = *self.front.get_mut();
+        self.buffers[front].get_mut()
+    }
+
+    /// Immutably borrows the front buffer. This never conflicts with the writer, and only
+    /// retries if the buffers are swapped while the borrow is being acquired.
+    #[inline(always)]
+    pub fn read(&self) -> RwCellGuard<'_, Const, T> {
+        loop {
+            let front = self.front.load(Ordering::Acquire);
+            if let Ok(guard) = self.buffers[front].try_borrow() {
+                if self.front.load(Ordering::Acquire) == front {
+                    return guard;
+                }
+            }
+
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Mutably borrows the back buffer, spinning until any readers that still borrow it from
+    /// before the last swap are finished. Aborts if another write guard exists.
+    #[inline(always)]
+    pub fn write(&self) -> DoubleBufferedWriteGuard<'_, T> {
+        if self.writing.swap(true, Ordering::Acquire) {
+            AbortPanic::abort("Attempted to write to double-buffered cell while it was already being written.");
+        }
+
+        let back = self.buffers[1 - self.front.load(Ordering::Relaxed)].borrow_mut_blocking();
+        DoubleBufferedWriteGuard {
+            cell: self,
+            guard: ManuallyDrop::new(back),
+        }
+    }
+
+    /// Attempts to mutably borrow the back buffer, returning an error if another write guard
+    /// exists, or if readers still borrow the back buffer from before the last swap.
+    #[inline(always)]
+    pub fn try_write(&self) -> Result<DoubleBufferedWriteGuard<'_, T>, BorrowMutError> {
+        if self.writing.swap(true, Ordering::Acquire) {
+            return Err(BorrowMutError::Writing);
+        }
+
+        match self.buffers[1 - self.front.load(Ordering::Relaxed)].try_borrow_mut() {
+            Ok(back) => Ok(DoubleBufferedWriteGuard {
+                cell: self,
+                guard: ManuallyDrop::new(back),
+            }),
+            Err(error) => {
+                self.writing.store(false, Ordering::Release);
+                Err(error)
+            },
+        }
+    }
+}
+
+impl<T> fmt::Debug for DoubleBufferedCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DoubleBufferedCell").field("writing", &self.writing.load(Ordering::Relaxed)).finish_non_exhaustive()
+    }
+}
+
+impl<T: Clone> From<T> for DoubleBufferedCell<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+/// A guard for the back buffer of a [`DoubleBufferedCell`], which publishes the buffer to
+/// readers when dropped.
+pub struct DoubleBufferedWriteGuard<'a, T> {
+    /// The borrowed cell.
+    cell: &'a DoubleBufferedCell<T>,
+    /// The borrow of the back buffer.
+    guard: ManuallyDrop<RwCellGuard<'a, Mut, T>>,
+}
+
+impl<'a, T> DoubleBufferedWriteGuard<'a, T> {
+    /// Gets the value of the front buffer, which readers may be borrowing at the same time.
+    /// The front buffer cannot change while the guard exists.
+    #[inline(always)]
+    pub fn front(orig: &Self) -> &T {
+        let front = orig.cell.front.load(Ordering::Relaxed);
+        unsafe { &*orig.cell.buffers[front].as_ptr() }
+    }
+
+    /// Publishes the back buffer, swapping it with the front buffer. This is equivalent to
+    /// dropping the guard.
+    #[inline(always)]
+    pub fn publish(orig: Self) {
+        drop(orig);
+    }
+}
+
+impl<'a, T> Deref for DoubleBufferedWriteGuard<'a, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<'a, T> DerefMut for DoubleBufferedWriteGuard<'a, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+impl<'a, T> Drop for DoubleBufferedWriteGuard<'a, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        unsafe { ManuallyDrop::drop(&mut self.guard) };
+        let front = self.cell.front.load(Ordering::Relaxed);
+        self.cell.front.store(1 - front, Ordering::Release);
+        self.cell.writing.store(false, Ordering::Release);
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for DoubleBufferedWriteGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
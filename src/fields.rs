@@ -0,0 +1,269 @@
+use crate::*;
+
+/// The number of bits of borrow state that each field uses.
+const FIELD_BITS: u32 = 8;
+/// The mask of the borrow state of a single field.
+const FIELD_MASK: u32 = (1 << FIELD_BITS) - 1;
+/// The state of a mutably-borrowed field.
+const FIELD_WRITER: u32 = FIELD_MASK;
+/// The largest number of immutable borrows that a field may have at once.
+const FIELD_MAX_READERS: u32 = FIELD_WRITER - 1;
+
+/// Gives access to the field at index `I` of a multi-field cell, such as [`RwCell2`].
+pub trait CellField<const I: usize> {
+    /// The type of the field.
+    type Value;
+
+    /// Gets a pointer to the value of the field.
+    fn field_ptr(&self) -> *mut Self::Value;
+}
+
+/// Defines a cell with several fields that are borrowed independently, and an implementation
+/// of [`CellField`] for each of its fields.
+macro_rules! multi_field_cell {
+    ($(#[$attr:meta])* $name:ident { $($index:tt => $field:ident: $ty:ident),* }) => {
+        $(#[$attr])*
+        pub struct $name<$($ty),*> {
+            /// The packed borrow states of the fields.
+            state: AtomicU32,
+            /// The values of the fields.
+            fields: ($(UnsafeCell<$ty>,)*),
+        }
+
+        impl<$($ty),*> $name<$($ty),*> {
+            /// Creates a new cell that wraps the provided values.
+            #[inline(always)]
+            pub const fn new($($field: $ty),*) -> Self {
+                Self {
+                    state: AtomicU32::new(0),
+                    fields: ($(UnsafeCell::new($field),)*),
+                }
+            }
+
+            /// Consumes this cell, returning the wrapped values.
+            #[inline(always)]
+            pub fn into_inner(self) -> ($($ty,)*) {
+                ($(self.fields.$index.into_inner(),)*)
+            }
+
+            /// Gets mutable references to every field. This requires no borrow accounting,
+            /// since the cell is mutably borrowed.
+            #[inline(always)]
+            pub fn get_mut(&mut self) -> ($(&mut $ty,)*) {
+                ($(self.fields.$index.get_mut(),)*)
+            }
+
+            /// Immutably borrows the field at index `I`. Aborts if the field is mutably borrowed, or
+            /// if it already has the largest number of immutable borrows that its 8 bits of state
+            /// can count.
+            #[inline(always)]
+            pub fn borrow_field<const I: usize>(&self) -> FieldGuard<'_, Const, <Self as CellField<I>>::Value>
+            where
+                Self: CellField<I>,
+            {
+                let shift = I as u32 * FIELD_BITS;
+                match self.acquire_field_read(shift) {
+                    Ok(()) => unsafe { FieldGuard::new(<Self as CellField<I>>::field_ptr(self), &self.state, shift) },
+                    Err(state) if state >> shift & FIELD_MASK == FIELD_WRITER => AbortPanic::abort("Attempted to immutably borrow cell while it was mutably borrowed."),
+                    Err(_) => AbortPanic::abort("Attempted to immutably borrow field of cell more than 254 times at once."),
+                }
+            }
+
+            /// Attempts to immutably borrow the field at index `I`, returning an error instead of
+            /// aborting if the field is mutably borrowed or has too many immutable borrows.
+            #[inline(always)]
+            pub fn try_borrow_field<const I: usize>(&self) -> Result<FieldGuard<'_, Const, <Self as CellField<I>>::Value>, BorrowError>
+            where
+                Self: CellField<I>,
+            {
+                let shift = I as u32 * FIELD_BITS;
+                self.acquire_field_read(shift).map_err(|_| BorrowError)?;
+                unsafe { Ok(FieldGuard::new(<Self as CellField<I>>::field_ptr(self), &self.state, shift)) }
+            }
+
+            /// Attempts to add an immutable borrow to the field whose state begins at bit `shift`.
+            /// On failure, returns the state that prevented the borrow.
+            #[inline(always)]
+            fn acquire_field_read(&self, shift: u32) -> Result<(), u32> {
+                self.state
+                    .fetch_update(Ordering::Acquire, Ordering::Relaxed, |state| {
+                        (state >> shift & FIELD_MASK < FIELD_MAX_READERS).then_some(state + (1 << shift))
+                    })
+                    .map(|_| ())
+            }
+
+            /// Mutably borrows the field at index `I`. Aborts if any other borrows of the field exist.
+            #[inline(always)]
+            pub fn borrow_field_mut<const I: usize>(&self) -> FieldGuard<'_, Mut, <Self as CellField<I>>::Value>
+            where
+                Self: CellField<I>,
+            {
+                match self.try_borrow_field_mut::<I>() {
+                    Ok(guard) => guard,
+                    Err(_) => AbortPanic::abort("Attempted to mutably borrow cell while other borrows already existed."),
+                }
+            }
+
+            /// Attempts to mutably borrow the field at index `I`, returning an error instead of
+            /// aborting if any other borrows of the field exist.
+            #[inline(always)]
+            pub fn try_borrow_field_mut<const I: usize>(&self) -> Result<FieldGuard<'_, Mut, <Self as CellField<I>>::Value>, BorrowMutError>
+            where
+                Self: CellField<I>,
+            {
+                let shift = I as u32 * FIELD_BITS;
+                match self.state.fetch_update(Ordering::Acquire, Ordering::Relaxed, |state| {
+                    (state >> shift & FIELD_MASK == 0).then_some(state | FIELD_WRITER << shift)
+                }) {
+                    Ok(_) => unsafe { Ok(FieldGuard::new(<Self as CellField<I>>::field_ptr(self), &self.state, shift)) },
+                    Err(state) if state >> shift & FIELD_MASK == FIELD_WRITER => Err(BorrowMutError::Writing),
+                    Err(_) => Err(BorrowMutError::Reading),
+                }
+            }
+
+            /// Mutably borrows every field at once. Aborts if any field is already borrowed.
+            #[inline(always)]
+            pub fn borrow_all_mut(&self) -> ($(FieldGuard<'_, Mut, $ty>,)*) {
+                match self.try_borrow_all_mut() {
+                    Ok(guards) => guards,
+                    Err(_) => AbortPanic::abort("Attempted to mutably borrow cell while other borrows already existed."),
+                }
+            }
+
+            /// Attempts to mutably borrow every field at once, returning an error instead of
+            /// aborting if any field is already borrowed. Either every field is borrowed, or none.
+            #[inline(always)]
+            pub fn try_borrow_all_mut(&self) -> Result<($(FieldGuard<'_, Mut, $ty>,)*), BorrowMutError> {
+                let all = 0 $(| FIELD_WRITER << ($index * FIELD_BITS))*;
+                match self.state.compare_exchange(0, all, Ordering::Acquire, Ordering::Relaxed) {
+                    Ok(_) => unsafe { Ok(($(FieldGuard::new(self.fields.$index.get(), &self.state, $index * FIELD_BITS),)*)) },
+                    Err(state) if false $(|| state >> ($index * FIELD_BITS) & FIELD_MASK == FIELD_WRITER)* => Err(BorrowMutError::Writing),
+                    Err(_) => Err(BorrowMutError::Reading),
+                }
+            }
+        }
+
+        impl<$($ty: Default),*> Default for $name<$($ty),*> {
+            #[inline(always)]
+            fn default() -> Self {
+                Self::new($(<$ty>::default()),*)
+            }
+        }
+
+        impl<$($ty),*> fmt::Debug for $name<$($ty),*> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($name)).finish_non_exhaustive()
+            }
+        }
+
+        unsafe impl<$($ty: Send + Sync),*> Sync for $name<$($ty),*> {}
+
+        multi_field_cell!(@fields $name [$($ty),*]; $($index => $ty),*);
+    };
+    (@fields $name:ident $generics:tt;) => {};
+    (@fields $name:ident [$($generic:ident),*]; $index:tt => $ty:ident $(, $rest_index:tt => $rest_ty:ident)*) => {
+        impl<$($generic),*> CellField<$index> for $name<$($generic),*> {
+            type Value = $ty;
+
+            #[inline(always)]
+            fn field_ptr(&self) -> *mut Self::Value {
+                self.fields.$index.get()
+            }
+        }
+
+        multi_field_cell!(@fields $name [$($generic),*]; $($rest_index => $rest_ty),*);
+    };
+}
+
+multi_field_cell! {
+    /// A cell with two fields that share a single borrow counter, but are borrowed independently.
+    /// A borrow of one field never conflicts with a borrow of the other, so different threads
+    /// may mutate different fields at the same time. Each field allows up to 254 immutable
+    /// borrows at once.
+    RwCell2 { 0 => a: A, 1 => b: B }
+}
+
+multi_field_cell! {
+    /// A cell with three fields that share a single borrow counter, but are borrowed independently.
+    /// A borrow of one field never conflicts with a borrow of another, so different threads
+    /// may mutate different fields at the same time. Each field allows up to 254 immutable
+    /// borrows at once.
+    RwCell3 { 0 => a: A, 1 => b: B, 2 => c: C }
+}
+
+multi_field_cell! {
+    /// A cell with four fields that share a single borrow counter, but are borrowed independently.
+    /// A borrow of one field never conflicts with a borrow of another, so different threads
+    /// may mutate different fields at the same time. Each field allows up to 254 immutable
+    /// borrows at once.
+    RwCell4 { 0 => a: A, 1 => b: B, 2 => c: C, 3 => d: D }
+}
+
+/// A guard for a borrow of a single field of a multi-field cell, such as [`RwCell2`].
+pub struct FieldGuard<'a, M: Mutability, T: 'a> {
+    /// The borrowed field.
+    value: NonNull<T>,
+    /// The packed borrow state of the cell.
+    state: &'a AtomicU32,
+    /// The position of the field's borrow state within the packed state.
+    shift: u32,
+    /// Marks the lifetime and mutability of the borrow.
+    marker: PhantomData<M::Ref<'a, T>>,
+}
+
+impl<'a, M: Mutability, T: 'a> FieldGuard<'a, M, T> {
+    /// Creates a guard for a field.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, the caller must have acquired a borrow of the field with
+    /// the given mutability, and `value` must point to the field.
+    #[inline(always)]
+    unsafe fn new(value: *mut T, state: &'a AtomicU32, shift: u32) -> Self {
+        Self {
+            value: NonNull::new_unchecked(value),
+            state,
+            shift,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, M: Mutability, T: 'a> Deref for FieldGuard<'a, M, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<'a, T: 'a> DerefMut for FieldGuard<'a, Mut, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<'a, M: Mutability, T: 'a> Drop for FieldGuard<'a, M, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if TypeId::of::<M>() == TypeId::of::<Mut>() {
+            self.state.fetch_and(!(FIELD_MASK << self.shift), Ordering::Release);
+        }
+        else {
+            self.state.fetch_sub(1 << self.shift, Ordering::Release);
+        }
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + fmt::Debug> fmt::Debug for FieldGuard<'a, M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+unsafe impl<'a, T: 'a + Sync> Send for FieldGuard<'a, Const, T> {}
+unsafe impl<'a, T: 'a + Sync> Sync for FieldGuard<'a, Const, T> {}
+unsafe impl<'a, T: 'a + Send> Send for FieldGuard<'a, Mut, T> {}
+unsafe impl<'a, T: 'a + Sync> Sync for FieldGuard<'a, Mut, T> {}
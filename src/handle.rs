@@ -0,0 +1,253 @@
+use crate::*;
+
+impl<T> RwCell<T> {
+    /// Splits access to this cell into a handle that may only immutably borrow it, and a handle
+    /// that may only mutably borrow it. The handles are ordinary borrows of the cell, so their
+    /// guards still conflict with each other and with borrows made through the cell directly.
+    #[inline(always)]
+    pub fn split_handles(&self) -> (ReadHandle<'_, T>, WriteHandle<'_, T>) {
+        (
+            ReadHandle {
+                cell: self,
+                project: |value| value,
+                marker: PhantomData,
+            },
+            WriteHandle {
+                cell: self,
+                project: |value| value,
+                marker: PhantomData,
+            },
+        )
+    }
+}
+
+/// A handle to a cell which may only be used to immutably borrow a portion `U` of its value.
+/// Handles may be cloned and given to code that should never mutate the cell. The guards that
+/// a handle returns are ordinary guards of the cell, which may be mapped or detached as usual.
+///
+/// A handle may be sent to or shared with other threads whenever `T` is [`Sync`], even if `T`
+/// is not [`Send`], since the value can never be mutated or moved through it.
+pub struct ReadHandle<'a, T, U: ?Sized = T, F = fn(&T) -> &T> {
+    /// The underlying cell.
+    cell: &'a RwCell<T>,
+    /// Selects the portion of the value that the handle may borrow.
+    project: F,
+    /// Marks the type of the borrowable portion.
+    marker: PhantomData<fn(&T) -> &U>,
+}
+
+impl<'a, T, U: ?Sized, F: Projection<'a, T, U>> ReadHandle<'a, T, U, F> {
+    /// Immutably borrows the portion of the cell's value that this handle refers to. Aborts if
+    /// the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn borrow(&self) -> RwCellGuard<'a, Const, U> {
+        self.project_guard(self.cell.borrow())
+    }
+
+    /// Attempts to immutably borrow the portion of the cell's value that this handle refers to,
+    /// returning an error instead of aborting if the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn try_borrow(&self) -> Result<RwCellGuard<'a, Const, U>, BorrowError> {
+        Ok(self.project_guard(self.cell.try_borrow()?))
+    }
+
+    /// Narrows this handle to a specific portion of the value. The projection runs each time
+    /// the handle is borrowed, so it may follow references that change when the value is mutated.
+    #[inline(always)]
+    pub fn map<V: ?Sized, G: Fn(&U) -> &V>(self, f: G) -> ReadHandle<'a, T, V, Composed<F, G, U>>
+    where
+        U: 'a,
+    {
+        ReadHandle {
+            cell: self.cell,
+            project: Composed::new(self.project, f),
+            marker: PhantomData,
+        }
+    }
+
+    /// Maps a borrow of the whole cell to the portion that this handle refers to. The borrow is
+    /// released if the projection unwinds.
+    #[inline(always)]
+    fn project_guard(&self, guard: RwCellGuard<'a, Const, T>) -> RwCellGuard<'a, Const, U> {
+        let value = NonNull::from(self.project.project(&guard));
+        let (_, state) = guard.into_parts();
+        unsafe { RwCellGuard::from_parts(value, state.projected()) }
+    }
+}
+
+impl<'a, T, U: ?Sized, F: Clone> Clone for ReadHandle<'a, T, U, F> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            cell: self.cell,
+            project: self.project.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, U: ?Sized, F> fmt::Debug for ReadHandle<'a, T, U, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadHandle").field("state", &self.cell.borrow_state()).finish_non_exhaustive()
+    }
+}
+
+unsafe impl<'a, T: Sync, U: ?Sized, F: Send> Send for ReadHandle<'a, T, U, F> {}
+unsafe impl<'a, T: Sync, U: ?Sized, F: Sync> Sync for ReadHandle<'a, T, U, F> {}
+
+/// A handle to a cell which may only be used to mutably borrow a portion `U` of its value.
+/// Unlike a [`ReadHandle`], it cannot be cloned. The guards that a handle returns are ordinary
+/// guards of the cell, which may be mapped, downgraded, or detached as usual.
+///
+/// A handle may be sent to or shared with other threads whenever `T` is [`Send`], since the
+/// value can only be accessed exclusively through it.
+pub struct WriteHandle<'a, T, U: ?Sized = T, F = fn(&mut T) -> &mut T> {
+    /// The underlying cell.
+    cell: &'a RwCell<T>,
+    /// Selects the portion of the value that the handle may borrow.
+    project: F,
+    /// Marks the type of the borrowable portion.
+    marker: PhantomData<fn(&mut T) -> &mut U>,
+}
+
+impl<'a, T, U: ?Sized, F: ProjectionMut<'a, T, U>> WriteHandle<'a, T, U, F> {
+    /// Mutably borrows the portion of the cell's value that this handle refers to. Aborts if
+    /// any other borrows of the cell exist.
+    #[inline(always)]
+    pub fn borrow_mut(&self) -> RwCellGuard<'a, Mut, U> {
+        self.project_guard(self.cell.borrow_mut())
+    }
+
+    /// Attempts to mutably borrow the portion of the cell's value that this handle refers to,
+    /// returning an error instead of aborting if any other borrows of the cell exist.
+    #[inline(always)]
+    pub fn try_borrow_mut(&self) -> Result<RwCellGuard<'a, Mut, U>, BorrowMutError> {
+        Ok(self.project_guard(self.cell.try_borrow_mut()?))
+    }
+
+    /// Narrows this handle to a specific portion of the value. The projection runs each time
+    /// the handle is borrowed, so it may follow references that change when the value is mutated.
+    #[inline(always)]
+    pub fn map<V: ?Sized, G: Fn(&mut U) -> &mut V>(self, f: G) -> WriteHandle<'a, T, V, Composed<F, G, U>>
+    where
+        U: 'a,
+    {
+        WriteHandle {
+            cell: self.cell,
+            project: Composed::new(self.project, f),
+            marker: PhantomData,
+        }
+    }
+
+    /// Maps a borrow of the whole cell to the portion that this handle refers to. The borrow is
+    /// released if the projection unwinds.
+    #[inline(always)]
+    fn project_guard(&self, mut guard: RwCellGuard<'a, Mut, T>) -> RwCellGuard<'a, Mut, U> {
+        let value = NonNull::from(self.project.project_mut(&mut guard));
+        let (_, state) = guard.into_parts();
+        unsafe { RwCellGuard::from_parts(value, state.projected()) }
+    }
+}
+
+impl<'a, T, U: ?Sized, F> fmt::Debug for WriteHandle<'a, T, U, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WriteHandle").field("state", &self.cell.borrow_state()).finish_non_exhaustive()
+    }
+}
+
+unsafe impl<'a, T: Send, U: ?Sized, F: Send> Send for WriteHandle<'a, T, U, F> {}
+unsafe impl<'a, T: Send, U: ?Sized, F: Sync> Sync for WriteHandle<'a, T, U, F> {}
+
+/// Selects a portion of a borrowed value, so that a [`ReadHandle`] can be narrowed to it. This
+/// is implemented for closures, and for combinations of them created by mapping a handle.
+pub trait Projection<'a, T: ?Sized, U: ?Sized> {
+    /// Gets the selected portion of the value.
+    fn project<'b>(&self, value: &'b T) -> &'b U
+    where
+        'a: 'b;
+}
+
+impl<'a, T: ?Sized, U: ?Sized, F: Fn(&T) -> &U> Projection<'a, T, U> for F {
+    #[inline(always)]
+    fn project<'b>(&self, value: &'b T) -> &'b U
+    where
+        'a: 'b,
+    {
+        self(value)
+    }
+}
+
+/// Selects a portion of a mutably borrowed value, so that a [`WriteHandle`] can be narrowed to
+/// it. This is implemented for closures, and for combinations of them created by mapping a handle.
+pub trait ProjectionMut<'a, T: ?Sized, U: ?Sized> {
+    /// Gets the selected portion of the value.
+    fn project_mut<'b>(&self, value: &'b mut T) -> &'b mut U
+    where
+        'a: 'b;
+}
+
+impl<'a, T: ?Sized, U: ?Sized, F: Fn(&mut T) -> &mut U> ProjectionMut<'a, T, U> for F {
+    #[inline(always)]
+    fn project_mut<'b>(&self, value: &'b mut T) -> &'b mut U
+    where
+        'a: 'b,
+    {
+        self(value)
+    }
+}
+
+/// A projection that applies one projection onto an intermediate portion `U` of a value, and
+/// then another.
+pub struct Composed<F, G, U: ?Sized> {
+    /// The projection that is applied first.
+    first: F,
+    /// The projection that is applied to the result of the first.
+    second: G,
+    /// Marks the type of the intermediate portion.
+    marker: PhantomData<fn(&U)>,
+}
+
+impl<F, G, U: ?Sized> Composed<F, G, U> {
+    /// Combines two projections.
+    #[inline(always)]
+    fn new(first: F, second: G) -> Self {
+        Self {
+            first,
+            second,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: ?Sized, U: 'a + ?Sized, V: ?Sized, F: Projection<'a, T, U>, G: Projection<'a, U, V>> Projection<'a, T, V> for Composed<F, G, U> {
+    #[inline(always)]
+    fn project<'b>(&self, value: &'b T) -> &'b V
+    where
+        'a: 'b,
+    {
+        self.second.project(self.first.project(value))
+    }
+}
+
+impl<'a, T: ?Sized, U: 'a + ?Sized, V: ?Sized, F: ProjectionMut<'a, T, U>, G: ProjectionMut<'a, U, V>> ProjectionMut<'a, T, V> for Composed<F, G, U> {
+    #[inline(always)]
+    fn project_mut<'b>(&self, value: &'b mut T) -> &'b mut V
+    where
+        'a: 'b,
+    {
+        self.second.project_mut(self.first.project_mut(value))
+    }
+}
+
+impl<F: Clone, G: Clone, U: ?Sized> Clone for Composed<F, G, U> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self::new(self.first.clone(), self.second.clone())
+    }
+}
+
+impl<F, G, U: ?Sized> fmt::Debug for Composed<F, G, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Composed").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,115 @@
+use crate::*;
+
+impl<T, P: ConflictPolicy> RwCell<T, P> {
+    /// Announces the intent to mutably borrow this cell. Existing immutable borrows are
+    /// unaffected, and new ones may still be acquired, but they can observe the pending write
+    /// with [`RwCell::is_write_pending`] and defer themselves. The intent is later converted into
+    /// a mutable borrow with [`WriteIntent::acquire`]. Only one intent may exist at a time, and
+    /// it excludes upgradable borrows. Aborts if the cell is mutably borrowed, frozen, has an
+    /// upgradable borrow, or already has a pending write.
+    #[inline(always)]
+    pub fn reserve_write(&self) -> WriteIntent<'_, T> {
+        match self.try_reserve_write() {
+            Ok(intent) => intent,
+            Err(BorrowMutError::Frozen) => AbortPanic::abort("Attempted to reserve write to cell after it was frozen."),
+            Err(BorrowMutError::Upgradable) => AbortPanic::abort("Attempted to reserve write to cell while it had an upgradable borrow."),
+            Err(BorrowMutError::Reserved) => AbortPanic::abort("Attempted to reserve write to cell while another write was pending."),
+            Err(_) => AbortPanic::abort("Attempted to reserve write to cell while it was mutably borrowed."),
+        }
+    }
+
+    /// Attempts to announce the intent to mutably borrow this cell, returning an error instead
+    /// of aborting if the cell is mutably borrowed, frozen, has an upgradable borrow, or already
+    /// has a pending write.
+    #[inline(always)]
+    pub fn try_reserve_write(&self) -> Result<WriteIntent<'_, T>, BorrowMutError> {
+        self.inner.state.try_acquire_intent()?;
+        Ok(WriteIntent {
+            value: unsafe { NonNull::new_unchecked(self.inner.value.get()) },
+            state: &self.inner.state,
+        })
+    }
+
+    /// Determines whether a write to this cell is pending, so that readers may defer their
+    /// borrows until the write has finished. This is only true while a [`WriteIntent`] exists,
+    /// and not for an upgradable borrow, which may never be upgraded. The result is racy in the
+    /// same way as [`RwCell::borrow_state`].
+    #[inline(always)]
+    pub fn is_write_pending(&self) -> bool {
+        let state = self.inner.state.counter.load(Ordering::Acquire);
+        !is_writing(state) && !is_frozen(state) && state & INTENT != 0
+    }
+}
+
+/// An announced intent to mutably borrow a cell, created by [`RwCell::reserve_write`]. While
+/// the intent exists, no other intent, upgradable borrow, or mutable borrow of the cell can be
+/// acquired, but immutable borrows may continue. The intent is counted as an immutable borrow
+/// by [`RwCell::borrow_state`].
+pub struct WriteIntent<'a, T> {
+    /// The value of the cell.
+    value: NonNull<T>,
+    /// The borrow state of the cell.
+    state: &'a RwCellState,
+}
+
+impl<'a, T> WriteIntent<'a, T> {
+    /// Gets the number of immutable borrows of the cell that must still be released before the
+    /// intent can be converted into a mutable borrow. The result is racy in the same way as
+    /// [`RwCell::borrow_state`].
+    #[inline(always)]
+    pub fn readers(&self) -> usize {
+        (self.state.counter.load(Ordering::Acquire) & MAX_READERS) as usize
+    }
+
+    /// Converts the intent into a mutable borrow. Aborts if any immutable borrows of the cell
+    /// still exist.
+    #[inline(always)]
+    pub fn acquire(self) -> RwCellGuard<'a, Mut, T> {
+        match self.try_acquire() {
+            Ok(guard) => guard,
+            Err(_) => AbortPanic::abort("Attempted to acquire reserved write to cell while it was immutably borrowed."),
+        }
+    }
+
+    /// Attempts to convert the intent into a mutable borrow. This fails and returns the intent
+    /// if any immutable borrows of the cell still exist.
+    #[inline(always)]
+    pub fn try_acquire(self) -> Result<RwCellGuard<'a, Mut, T>, Self> {
+        if self.state.try_acquire_reserved() {
+            let intent = ManuallyDrop::new(self);
+            unsafe { Ok(RwCellGuard::from_parts(intent.value, StateRef::new(intent.state))) }
+        }
+        else {
+            Err(self)
+        }
+    }
+
+    /// Converts the intent into a mutable borrow, spinning until every immutable borrow of the
+    /// cell has been released.
+    #[inline(always)]
+    pub fn acquire_blocking(mut self) -> RwCellGuard<'a, Mut, T> {
+        loop {
+            match self.try_acquire() {
+                Ok(guard) => return guard,
+                Err(intent) => self = intent,
+            }
+            core::hint::spin_loop();
+        }
+    }
+}
+
+impl<'a, T> Drop for WriteIntent<'a, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.state.release_intent();
+    }
+}
+
+impl<'a, T> fmt::Debug for WriteIntent<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WriteIntent").field("readers", &self.readers()).finish()
+    }
+}
+
+unsafe impl<'a, T: Send + Sync> Send for WriteIntent<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for WriteIntent<'a, T> {}
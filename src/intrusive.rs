@@ -0,0 +1,463 @@
+use crate::*;
+use core::future::Future;
+use core::marker::PhantomPinned;
+use core::pin::Pin;
+use core::ptr::null_mut;
+use core::task::*;
+
+/// A cell whose borrows are acquired asynchronously without any heap allocation. Each
+/// pending acquisition future embeds its own waiter node, which it links into the cell's
+/// waiter list while pinned and unlinks when it completes or is dropped.
+///
+/// The waiter list is protected by a spinlock, so guards for this cell should not be dropped
+/// from interrupt handlers that may preempt a thread polling or dropping an acquisition future.
+///
+/// The cell does not dereference to a [`RwCell`], since guards obtained from it would release
+/// their borrows without waking the waiting futures.
+#[derive(Debug, Default)]
+pub struct IntrusiveRwCell<T> {
+    /// The underlying cell.
+    cell: RwCell<T>,
+    /// The futures waiting for borrows to be released.
+    waiters: WaiterQueue,
+}
+
+impl<T> IntrusiveRwCell<T> {
+    /// Creates a new cell that wraps the provided value.
+    #[inline(always)]
+    pub const fn new(value: T) -> Self {
+        Self {
+            cell: RwCell::new(value),
+            waiters: WaiterQueue::new(),
+        }
+    }
+
+    /// Consumes this cell, returning the wrapped value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.cell.into_inner()
+    }
+
+    /// Gets a mutable reference to the value. This requires no borrow accounting,
+    /// since the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.cell.get_mut()
+    }
+
+    /// Gets a raw pointer to the wrapped value.
+    #[inline(always)]
+    pub fn as_ptr(&self) -> *mut T {
+        self.cell.as_ptr()
+    }
+
+    /// Determines whether this cell is free to be borrowed.
+    #[inline(always)]
+    pub fn free(&self) -> bool {
+        self.cell.free()
+    }
+
+    /// Takes a snapshot of this cell's borrow state. The result is racy in the same way as
+    /// [`RwCell::borrow_state`].
+    #[inline(always)]
+    pub fn borrow_state(&self) -> BorrowState {
+        self.cell.borrow_state()
+    }
+
+    /// Gets the number of immutable borrows of this cell. This is zero if the cell is
+    /// mutably borrowed.
+    #[inline(always)]
+    pub fn reader_count(&self) -> usize {
+        self.cell.reader_count()
+    }
+
+    /// Determines whether this cell is mutably borrowed.
+    #[inline(always)]
+    pub fn is_locked_exclusive(&self) -> bool {
+        self.cell.is_locked_exclusive()
+    }
+
+    /// Immutably borrows the value of this cell, waiting until any mutable borrow is released.
+    #[inline(always)]
+    pub fn read(&self) -> IntrusiveBorrow<'_, Const, T> {
+        IntrusiveBorrow::new(self)
+    }
+
+    /// Mutably borrows the value of this cell, waiting until all other borrows are released.
+    /// The future aborts when polled if the cell is frozen, since it can never be mutably borrowed.
+    #[inline(always)]
+    pub fn write(&self) -> IntrusiveBorrow<'_, Mut, T> {
+        IntrusiveBorrow::new(self)
+    }
+
+    /// Attempts to immutably borrow the value of this cell without waiting, returning an error
+    /// if the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn try_read(&self) -> Result<IntrusiveRwCellGuard<'_, Const, T>, BorrowError> {
+        let guard = self.cell.try_borrow()?;
+        Ok(IntrusiveRwCellGuard::new(guard, &self.waiters))
+    }
+
+    /// Attempts to mutably borrow the value of this cell without waiting, returning an error
+    /// if any other borrows exist.
+    #[inline(always)]
+    pub fn try_write(&self) -> Result<IntrusiveRwCellGuard<'_, Mut, T>, BorrowMutError> {
+        let guard = self.cell.try_borrow_mut()?;
+        Ok(IntrusiveRwCellGuard::new(guard, &self.waiters))
+    }
+}
+
+impl<T> From<T> for IntrusiveRwCell<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+/// A future that borrows an [`IntrusiveRwCell`] with the given mutability. The future contains
+/// the node that links it into the cell's waiter list, so it must be pinned to be polled.
+#[must_use = "futures do nothing unless polled"]
+pub struct IntrusiveBorrow<'a, M: Mutability, T> {
+    /// The cell to borrow.
+    cell: &'a IntrusiveRwCell<T>,
+    /// The node of this future, which is only accessed while the waiter list is locked.
+    node: UnsafeCell<WaiterNode>,
+    /// Whether the node has ever been linked into the waiter list.
+    registered: Cell<bool>,
+    /// Marks the mutability of the borrow.
+    marker: PhantomData<fn() -> M>,
+    /// Prevents the node from being moved once it is linked.
+    _pinned: PhantomPinned,
+}
+
+impl<'a, M: Mutability, T> IntrusiveBorrow<'a, M, T> {
+    /// Creates a new future for the given cell.
+    #[inline(always)]
+    fn new(cell: &'a IntrusiveRwCell<T>) -> Self {
+        Self {
+            cell,
+            node: UnsafeCell::new(WaiterNode::new()),
+            registered: Cell::new(false),
+            marker: PhantomData,
+            _pinned: PhantomPinned,
+        }
+    }
+
+    /// Attempts to acquire a borrow of the cell, unlinking the node if it succeeds. Aborts if
+    /// a mutable borrow is requested and the cell is frozen.
+    #[inline(always)]
+    fn try_acquire(&self) -> Option<IntrusiveRwCellGuard<'a, M, T>> {
+        let guard = self.cell.cell.try_borrow_waiting()?;
+        self.unlink();
+        Some(IntrusiveRwCellGuard::new(guard, &self.cell.waiters))
+    }
+
+    /// Removes the node from the waiter list, if it is linked.
+    #[inline(always)]
+    fn unlink(&self) {
+        if self.registered.get() {
+            unsafe { self.cell.waiters.unlink(self.node.get()) };
+        }
+    }
+}
+
+impl<'a, M: Mutability, T> Future for IntrusiveBorrow<'a, M, T> {
+    type Output = IntrusiveRwCellGuard<'a, M, T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.as_ref().get_ref();
+        if let Some(guard) = this.try_acquire() {
+            return Poll::Ready(guard);
+        }
+
+        unsafe { this.cell.waiters.link(this.node.get(), cx.waker()) };
+        this.registered.set(true);
+        fence(Ordering::SeqCst);
+        match this.try_acquire() {
+            Some(guard) => Poll::Ready(guard),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<'a, M: Mutability, T> Drop for IntrusiveBorrow<'a, M, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.unlink();
+    }
+}
+
+impl<'a, M: Mutability, T> fmt::Debug for IntrusiveBorrow<'a, M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IntrusiveBorrow").finish_non_exhaustive()
+    }
+}
+
+unsafe impl<'a, M: Mutability, T: Send + Sync> Send for IntrusiveBorrow<'a, M, T> {}
+
+unsafe impl<'a, M: Mutability, T: Send + Sync> Sync for IntrusiveBorrow<'a, M, T> {}
+
+/// A guard for a borrow of an [`IntrusiveRwCell`], which wakes waiting futures when dropped.
+pub struct IntrusiveRwCellGuard<'a, M: Mutability, T: 'a + ?Sized> {
+    /// The underlying borrow.
+    guard: ManuallyDrop<RwCellGuard<'a, M, T>>,
+    /// The futures to wake when the borrow is released.
+    waiters: &'a WaiterQueue,
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized> IntrusiveRwCellGuard<'a, M, T> {
+    /// Wraps a borrow of a cell with the list of the cell's waiting futures.
+    #[inline(always)]
+    fn new(guard: RwCellGuard<'a, M, T>, waiters: &'a WaiterQueue) -> Self {
+        Self {
+            guard: ManuallyDrop::new(guard),
+            waiters,
+        }
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized> Deref for IntrusiveRwCellGuard<'a, M, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<'a, T: 'a + ?Sized> DerefMut for IntrusiveRwCellGuard<'a, Mut, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized> Drop for IntrusiveRwCellGuard<'a, M, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        unsafe { ManuallyDrop::drop(&mut self.guard) };
+        self.waiters.wake_all();
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized + fmt::Debug> fmt::Debug for IntrusiveRwCellGuard<'a, M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+/// A node in the waiter list of an [`IntrusiveRwCell`], which is embedded in a future.
+#[derive(Debug)]
+struct WaiterNode {
+    /// The previous node in the list.
+    prev: *mut WaiterNode,
+    /// The next node in the list.
+    next: *mut WaiterNode,
+    /// The waker of the future.
+    waker: Option<Waker>,
+    /// The number of times that the list had been woken when this node was linked.
+    generation: usize,
+    /// Whether this node is currently in the list.
+    linked: bool,
+}
+
+impl WaiterNode {
+    /// Creates a new, unlinked node.
+    #[inline(always)]
+    const fn new() -> Self {
+        Self {
+            prev: null_mut(),
+            next: null_mut(),
+            waker: None,
+            generation: 0,
+            linked: false,
+        }
+    }
+}
+
+/// A list of pinned futures that are waiting for a cell to be released.
+#[derive(Debug)]
+struct WaiterQueue {
+    /// Whether the list is locked.
+    locked: AtomicBool,
+    /// The number of linked nodes.
+    waiters: AtomicUsize,
+    /// The linked nodes, which are only accessed while the list is locked.
+    list: UnsafeCell<WaiterList>,
+}
+
+impl WaiterQueue {
+    /// Creates a new, empty queue.
+    #[inline(always)]
+    const fn new() -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            waiters: AtomicUsize::new(0),
+            list: UnsafeCell::new(WaiterList {
+                head: null_mut(),
+                tail: null_mut(),
+                generation: 0,
+            }),
+        }
+    }
+
+    /// Links a node at the end of the list and stores its waker, or updates the waker of a node
+    /// that is already linked. The waiter count is updated before returning, so the caller
+    /// must check the cell again afterward to avoid missing a release.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, the node must be pinned, and must be unlinked before
+    /// it is invalidated.
+    unsafe fn link(&self, node: *mut WaiterNode, waker: &Waker) {
+        let list = self.lock();
+        let list_ref = &mut *list.0;
+
+        if !(*node).waker.as_ref().is_some_and(|existing| existing.will_wake(waker)) {
+            (*node).waker = Some(waker.clone());
+        }
+
+        if !(*node).linked {
+            (*node).prev = list_ref.tail;
+            (*node).next = null_mut();
+            (*node).generation = list_ref.generation;
+            (*node).linked = true;
+
+            if list_ref.tail.is_null() {
+                list_ref.head = node;
+            }
+            else {
+                (*list_ref.tail).next = node;
+            }
+
+            list_ref.tail = node;
+            self.waiters.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Removes a node from the list if it is linked, and drops its waker.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, the node must have been linked into this list at
+    /// most once, and must not have been invalidated since.
+    unsafe fn unlink(&self, node: *mut WaiterNode) {
+        let list = self.lock();
+        if (*node).linked {
+            self.remove(&mut *list.0, node);
+        }
+
+        let waker = (*node).waker.take();
+        drop(list);
+        drop(waker);
+    }
+
+    /// Wakes and unlinks every node that was linked before this call. Nodes are removed
+    /// one at a time, and their wakers are invoked while the list is unlocked, so that
+    /// futures polled during waking may link themselves again without being woken twice.
+    /// Nodes from earlier generations are also woken, in case a concurrent call has not yet
+    /// reached them.
+    #[inline(always)]
+    fn wake_all(&self) {
+        fence(Ordering::SeqCst);
+        if self.waiters.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+
+        let generation = unsafe {
+            let list = self.lock();
+            let list_ref = &mut *list.0;
+            let generation = list_ref.generation;
+            list_ref.generation = generation.wrapping_add(1);
+            generation
+        };
+
+        loop {
+            let waker = unsafe {
+                let list = self.lock();
+                let list_ref = &mut *list.0;
+                let head = list_ref.head;
+                if head.is_null() || (generation.wrapping_sub((*head).generation) as isize) < 0 {
+                    break;
+                }
+
+                self.remove(list_ref, head);
+                (*head).waker.take()
+            };
+
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Removes a linked node from the list.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, the list must be locked and the node must be linked.
+    #[inline(always)]
+    unsafe fn remove(&self, list: &mut WaiterList, node: *mut WaiterNode) {
+        let (prev, next) = ((*node).prev, (*node).next);
+        if prev.is_null() {
+            list.head = next;
+        }
+        else {
+            (*prev).next = next;
+        }
+
+        if next.is_null() {
+            list.tail = prev;
+        }
+        else {
+            (*next).prev = prev;
+        }
+
+        (*node).prev = null_mut();
+        (*node).next = null_mut();
+        (*node).linked = false;
+        self.waiters.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Locks the list, spinning while another thread holds the lock.
+    #[inline(always)]
+    fn lock(&self) -> WaiterListLock<'_> {
+        while self.locked.compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            core::hint::spin_loop();
+        }
+
+        WaiterListLock(self.list.get(), &self.locked)
+    }
+}
+
+impl Default for WaiterQueue {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl Send for WaiterQueue {}
+
+unsafe impl Sync for WaiterQueue {}
+
+/// The linked nodes of a [`WaiterQueue`].
+#[derive(Debug)]
+struct WaiterList {
+    /// The first node in the list.
+    head: *mut WaiterNode,
+    /// The last node in the list.
+    tail: *mut WaiterNode,
+    /// The number of times that the list has been woken, wrapping on overflow.
+    generation: usize,
+}
+
+/// Unlocks a [`WaiterQueue`] when dropped.
+struct WaiterListLock<'a>(*mut WaiterList, &'a AtomicBool);
+
+impl Drop for WaiterListLock<'_> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.1.store(false, Ordering::Release);
+    }
+}
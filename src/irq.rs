@@ -0,0 +1,110 @@
+use crate::*;
+
+/// Runs code with interrupts masked, or inside some other critical section that prevents
+/// interrupt handlers from preempting it. With the `critical-section` feature, this is
+/// implemented by `GlobalCriticalSection`, which forwards to the `critical-section` crate.
+pub trait CriticalSection {
+    /// Runs `f` inside a critical section.
+    fn with<R>(f: impl FnOnce() -> R) -> R;
+}
+
+/// The critical section provided by the `critical-section` crate, which uses whichever
+/// implementation the final binary links in.
+#[cfg(feature = "critical-section")]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GlobalCriticalSection;
+
+#[cfg(feature = "critical-section")]
+impl CriticalSection for GlobalCriticalSection {
+    #[inline(always)]
+    fn with<R>(f: impl FnOnce() -> R) -> R {
+        critical_section::with(|_| f())
+    }
+}
+
+/// A cell that is shared between interrupt handlers and thread mode. Its main accessors run a
+/// closure inside the critical section `C`, so an interrupt handler can never observe a borrow
+/// made by them, and they report conflicts as errors rather than aborting, since aborting
+/// inside an interrupt handler is rarely recoverable. Access is closure-based so that
+/// interrupts are never left masked by a guard that outlives its use.
+///
+/// The `try_` methods borrow the cell without entering a critical section, for thread-mode code
+/// that is willing to handle conflicts with interrupt handlers itself.
+pub struct IrqRwCell<T, C> {
+    /// The underlying cell.
+    cell: RwCell<T>,
+    /// Marks the critical section that borrows run in.
+    marker: PhantomData<fn() -> C>,
+}
+
+impl<T, C: CriticalSection> IrqRwCell<T, C> {
+    /// Creates a new cell that wraps the provided value.
+    #[inline(always)]
+    pub const fn new(value: T) -> Self {
+        Self {
+            cell: RwCell::new(value),
+            marker: PhantomData,
+        }
+    }
+
+    /// Consumes this cell, returning the wrapped value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.cell.into_inner()
+    }
+
+    /// Gets a mutable reference to the value. This requires no borrow accounting,
+    /// since the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.cell.get_mut()
+    }
+
+    /// Immutably borrows the value inside a critical section and passes it to `f`. Returns an
+    /// error if the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> Result<R, BorrowError> {
+        C::with(|| self.cell.try_borrow().map(|guard| f(&guard)))
+    }
+
+    /// Mutably borrows the value inside a critical section and passes it to `f`. Returns an
+    /// error if any other borrows exist.
+    #[inline(always)]
+    pub fn with_mut<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, BorrowMutError> {
+        C::with(|| self.cell.try_borrow_mut().map(|mut guard| f(&mut guard)))
+    }
+
+    /// Attempts to immutably borrow the value without entering a critical section, returning
+    /// an error if the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn try_borrow(&self) -> Result<RwCellGuard<'_, Const, T>, BorrowError> {
+        self.cell.try_borrow()
+    }
+
+    /// Attempts to mutably borrow the value without entering a critical section, returning
+    /// an error if any other borrows exist.
+    #[inline(always)]
+    pub fn try_borrow_mut(&self) -> Result<RwCellGuard<'_, Mut, T>, BorrowMutError> {
+        self.cell.try_borrow_mut()
+    }
+}
+
+impl<T: Default, C: CriticalSection> Default for IrqRwCell<T, C> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T, C> fmt::Debug for IrqRwCell<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IrqRwCell").field("state", &self.cell.borrow_state()).finish_non_exhaustive()
+    }
+}
+
+impl<T, C: CriticalSection> From<T> for IrqRwCell<T, C> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
@@ -0,0 +1,111 @@
+use crate::*;
+
+/// A cell that is initialized with the result of a function the first time that it is
+/// accessed. Once initialized, it behaves like a [`RwCell`], which it dereferences to.
+///
+/// The initializer runs while the cell is mutably borrowed. If another thread accesses the
+/// cell for the first time while the initializer is running, or the initializer accesses the
+/// same cell, the borrows conflict and the program aborts.
+pub struct LazyRwCell<T, F = fn() -> T> {
+    /// The cell holding the value, which is initialized once `initialized` is set.
+    cell: RwCell<MaybeUninit<T>>,
+    /// Whether the value has been initialized.
+    initialized: AtomicBool,
+    /// The initializer, which is taken when it runs.
+    init: UnsafeCell<Option<F>>,
+}
+
+impl<T, F: FnOnce() -> T> LazyRwCell<T, F> {
+    /// Creates a new cell that will be initialized with the result of `f`.
+    #[inline(always)]
+    pub const fn new(f: F) -> Self {
+        Self {
+            cell: RwCell::new(MaybeUninit::uninit()),
+            initialized: AtomicBool::new(false),
+            init: UnsafeCell::new(Some(f)),
+        }
+    }
+
+    /// Initializes the cell if it has not been initialized yet, and returns the underlying
+    /// [`RwCell`]. Panics if a previous initializer unwound.
+    #[inline(always)]
+    pub fn force(this: &Self) -> &RwCell<T> {
+        if !this.initialized.load(Ordering::Acquire) {
+            this.initialize();
+        }
+
+        unsafe { this.cell.assume_init_ref() }
+    }
+
+    /// Consumes the cell, returning the value if it was initialized, or the initializer
+    /// otherwise. Panics if a previous initializer unwound.
+    #[inline(always)]
+    pub fn into_inner(this: Self) -> Result<T, F> {
+        let mut this = ManuallyDrop::new(this);
+        if *this.initialized.get_mut() {
+            unsafe { Ok(this.cell.get_mut().assume_init_read()) }
+        }
+        else {
+            Err(this.init.get_mut().take().expect("Initializer of lazy cell previously panicked."))
+        }
+    }
+
+    /// Runs the initializer, unless another thread finished doing so concurrently.
+    #[cold]
+    fn initialize(&self) {
+        match self.cell.try_borrow_mut() {
+            Ok(mut guard) => {
+                if !self.initialized.load(Ordering::Acquire) {
+                    let init = unsafe { (*self.init.get()).take() }.expect("Initializer of lazy cell previously panicked.");
+                    guard.write(init());
+                    self.initialized.store(true, Ordering::Release);
+                }
+            },
+            Err(_) => {
+                if !self.initialized.load(Ordering::Acquire) {
+                    AbortPanic::abort("Attempted to access lazy cell while it was being initialized.");
+                }
+            },
+        }
+    }
+}
+
+impl<T, F: FnOnce() -> T> Deref for LazyRwCell<T, F> {
+    type Target = RwCell<T>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        Self::force(self)
+    }
+}
+
+impl<T: Default> Default for LazyRwCell<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(T::default)
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for LazyRwCell<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_tuple("LazyRwCell");
+        if self.initialized.load(Ordering::Acquire) {
+            debug.field(unsafe { self.cell.assume_init_ref() });
+        }
+        else {
+            debug.field(&format_args!("<uninit>"));
+        }
+        debug.finish()
+    }
+}
+
+impl<T, F> Drop for LazyRwCell<T, F> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if *self.initialized.get_mut() {
+            unsafe { self.cell.get_mut().assume_init_drop() };
+        }
+    }
+}
+
+unsafe impl<T: Send + Sync, F: Send> Sync for LazyRwCell<T, F> {}
@@ -5,147 +5,2706 @@
 //! Defines a [`Send`] and [`Sync`] version of [`core::cell::RefCell`], which aborts the program
 //! if an attempted borrow fails.
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 use core::any::*;
 use core::cell::*;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
 use core::mem::*;
+use core::num::NonZeroUsize;
 use core::ops::*;
+use core::ptr::{addr_of, NonNull};
 use core::sync::atomic::*;
 pub use mutability_marker::*;
+#[cfg(feature = "alloc")]
+pub use crate::arc::*;
+pub use crate::array::*;
+#[cfg(feature = "alloc")]
+pub use crate::asynchronous::*;
+pub use crate::atomic::*;
+#[cfg(feature = "std")]
+pub use crate::blocking::*;
+pub use crate::bounded::*;
+pub use crate::double::*;
+pub use crate::fields::*;
+pub use crate::handle::*;
+pub use crate::intent::*;
+pub use crate::intrusive::*;
+pub use crate::irq::*;
+pub use crate::lazy::*;
+pub use crate::mailbox::*;
+pub use crate::many::*;
+pub use crate::once::*;
+pub use crate::padded::*;
+pub use crate::policy::*;
+#[cfg(feature = "std")]
+pub use crate::poison::*;
+pub use crate::reentrant::*;
+pub use crate::seq::*;
+pub use crate::shadow::*;
+#[cfg(feature = "std")]
+pub use crate::sharded::*;
+#[cfg(feature = "alloc")]
+pub use crate::snapshot::*;
+pub use crate::token::*;
+pub use crate::tracked::*;
+
+/// Defines reference-counted and pinned cells with owned guards.
+#[cfg(feature = "alloc")]
+mod arc;
+/// Defines an array with a separate borrow state for each element.
+mod array;
+/// Defines a cell whose borrows are acquired by futures.
+#[cfg(feature = "alloc")]
+mod asynchronous;
+/// Defines a cell for small values that never aborts.
+mod atomic;
+/// Defines a cell with blocking borrows and a selectable fairness policy.
+#[cfg(feature = "std")]
+mod blocking;
+/// Defines a cell with a configurable limit on concurrent immutable borrows.
+mod bounded;
+/// Defines a cell with separate buffers for readers and a writer.
+mod double;
+/// Defines cells with several fields that are borrowed independently.
+mod fields;
+/// Defines handles that restrict a cell to either immutable or mutable borrows.
+mod handle;
+/// Defines write intents that readers can observe before the write begins.
+mod intent;
+/// Defines a cell whose borrows are acquired by futures without allocating.
+mod intrusive;
+/// Defines a cell that is shared with interrupt handlers through critical sections.
+mod irq;
+/// Defines a cell that is initialized on first access.
+mod lazy;
+/// Defines a cell that hands a single value from one thread to another.
+mod mailbox;
+/// Defines borrows that acquire several cells at once.
+mod many;
+/// Defines a cell that can be written only once.
+mod once;
+/// Defines a cell that is padded to the size of a cache line.
+mod padded;
+/// Defines the policies that decide how cells handle conflicting borrows.
+mod policy;
+/// Defines a cell that is poisoned by panics during mutable borrows.
+#[cfg(feature = "std")]
+mod poison;
+/// Defines a cell that allows nested mutable borrows with an access token.
+mod reentrant;
+/// Defines a cell with optimistic reads based on a sequence counter.
+mod seq;
+/// Defines a cell whose borrow state is stored in a global side table.
+mod shadow;
+/// Defines a cell whose immutable borrows are distributed across reader slots.
+#[cfg(feature = "std")]
+mod sharded;
+/// Defines a cell whose readers load owned snapshots of its value.
+#[cfg(feature = "alloc")]
+mod snapshot;
+/// Defines a cell whose borrows are controlled by an owner token instead of a counter.
+mod token;
+/// Defines a cell with a name that is reported when its borrows conflict.
+mod tracked;
+
+/// A lightweight reference-counted cell. Aborts the program when borrows conflict.
+///
+/// The borrow counter is stored inline alongside the value, so a cell is always larger than
+/// the value it wraps, and a `&mut T` cannot be viewed as a `&RwCell<T>` like with
+/// `Cell::from_mut`. A [`ShadowRwCell`] stores its counter in a side table instead, so it
+/// provides [`ShadowRwCell::from_mut`] and [`ShadowRwCell::from_slice_mut`]. To borrow the
+/// elements of an array separately, in place of `Cell::as_array_of_cells`, use a
+/// [`RwCellArray`].
+///
+/// With the `std` feature, threads that wait for a borrow park in a global table keyed by the
+/// address of the counter, so the counter is the only state that a cell adds to its value.
+///
+/// What happens when [`RwCell::borrow`] or [`RwCell::borrow_mut`] conflicts with another
+/// borrow is decided by the [`ConflictPolicy`] `P`, which aborts by default. Guards do not
+/// depend on the policy, so cells with different policies return the same guard types.
+///
+/// A cell is [`Sync`] under the same conditions as `std::sync::RwLock`. Immutable guards on
+/// several threads hand out `&T` at once, so `T` must be [`Sync`], and a mutable guard lets
+/// another thread move a value into or out of the cell, so `T` must also be [`Send`]. A cell
+/// for a [`Cell`] cannot be shared:
+///
+/// ```compile_fail
+/// # use core::cell::Cell;
+/// # use sync_rw_cell::*;
+/// fn assert_sync<T: Sync>(_: &T) {}
+///
+/// assert_sync(&RwCell::new(Cell::new(0)));
+/// ```
+///
+/// Nor can a cell for a value that is [`Sync`] but not [`Send`], such as a `MutexGuard`:
+///
+/// ```compile_fail
+/// # use std::sync::{Mutex, MutexGuard};
+/// # use sync_rw_cell::*;
+/// fn assert_sync<T: Sync>(_: &T) {}
+///
+/// let mutex = Mutex::new(0);
+/// assert_sync(&RwCell::new(mutex.lock().unwrap()));
+/// ```
+#[repr(transparent)]
+pub struct RwCell<T, P = Abort> {
+    /// The inner cell data.
+    inner: ReadCell<RwCellInner<T>>,
+    /// Marks the conflict policy of the cell.
+    policy: PhantomData<fn() -> P>,
+}
+
+impl<T> RwCell<T> {
+    /// Creates a new cell that wraps the provided value.
+    #[inline(always)]
+    pub const fn new(value: T) -> Self {
+        Self::with_policy(value)
+    }
+}
+
+impl<T, P: ConflictPolicy> RwCell<T, P> {
+    /// Creates a new cell that wraps the provided value, and handles conflicting borrows
+    /// with the policy `P`.
+    #[inline(always)]
+    pub const fn with_policy(value: T) -> Self {
+        Self {
+            inner: ReadCell::new(RwCellInner {
+                state: RwCellState::new(),
+                value: UnsafeCell::new(value),
+            }),
+            policy: PhantomData,
+        }
+    }
+
+    /// Consumes this cell, returning the wrapped value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner().value.into_inner()
+    }
+
+    /// Consumes this cell, returning the wrapped value. Fails and returns the cell
+    /// if it is still borrowed, which can occur when a detached guard is alive.
+    #[inline(always)]
+    pub fn try_into_inner(self) -> Result<T, Self> {
+        if self.free() {
+            Ok(self.into_inner())
+        }
+        else {
+            Err(self)
+        }
+    }
+
+    /// Mutably borrows the value of this cell without any atomic operations. This is
+    /// statically guaranteed to be unique, since it requires exclusive access to the cell.
+    /// With the `versioning` feature, this increments the version of the cell, since the value
+    /// may be changed through the returned reference.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T {
+        let inner = &mut *self.inner;
+        let counter = *inner.state.counter.get_mut();
+        debug_assert!(counter & !PARKED == 0 || is_frozen(counter), "Attempted to exclusively access cell while a detached guard existed.");
+        #[cfg(feature = "versioning")]
+        {
+            let version = inner.state.version.get_mut();
+            *version = version.wrapping_add(1);
+        }
+        inner.value.get_mut()
+    }
+
+    /// Immutably borrows the value of this cell. If the cell is mutably borrowed, the
+    /// conflict is handled by the cell's policy. A cell may have at most 4095 immutable borrows
+    /// at once, and borrowing it again past that limit is also handled as a conflict.
+    #[inline(always)]
+    pub fn borrow(&self) -> RwCellGuard<'_, Const, T> {
+        self.inner.state.acquire_read_with::<P>();
+        unsafe { self.read_guard() }
+    }
+
+    /// Immutably borrows the value of a cell that lives for the rest of the program, such as
+    /// one in a `static`. The guard is not tied to any shorter lifetime.
+    #[inline(always)]
+    pub fn borrow_static(&'static self) -> RwCellGuard<'static, Const, T> {
+        self.borrow()
+    }
+
+    /// Attempts to immutably borrow the value of this cell, returning an error
+    /// instead of aborting if the cell is mutably borrowed, or already has the maximum of
+    /// 4095 immutable borrows.
+    #[inline(always)]
+    pub fn try_borrow(&self) -> Result<RwCellGuard<'_, Const, T>, BorrowError> {
+        if self.inner.state.try_acquire_read() {
+            unsafe { Ok(self.read_guard()) }
+        }
+        else {
+            Err(BorrowError)
+        }
+    }
+
+    /// Mutably borrows the value of this cell. If any other borrows exist, the conflict is
+    /// handled by the cell's policy.
+    #[inline(always)]
+    pub fn borrow_mut(&self) -> RwCellGuard<'_, Mut, T> {
+        self.inner.state.acquire_write_with::<P>();
+        unsafe { self.write_guard() }
+    }
+
+    /// Mutably borrows the value of a cell that lives for the rest of the program, such as
+    /// one in a `static`. The guard is not tied to any shorter lifetime.
+    #[inline(always)]
+    pub fn borrow_mut_static(&'static self) -> RwCellGuard<'static, Mut, T> {
+        self.borrow_mut()
+    }
+
+    /// Attempts to mutably borrow the value of this cell, returning an error
+    /// instead of aborting if any other borrows exist.
+    #[inline(always)]
+    pub fn try_borrow_mut(&self) -> Result<RwCellGuard<'_, Mut, T>, BorrowMutError> {
+        self.inner.state.try_acquire_write()?;
+        unsafe { Ok(self.write_guard()) }
+    }
+
+    /// Borrows the value of this cell immutably, with the option of later upgrading the borrow to
+    /// a mutable one. Only one upgradable borrow may exist at a time, but it coexists with plain
+    /// immutable borrows, and no mutable or upgradable borrow can be acquired until it is released.
+    /// Aborts if the cell is mutably borrowed, frozen, or already has an upgradable borrow or a
+    /// reserved write.
+    #[inline(always)]
+    pub fn borrow_upgradable(&self) -> UpgradableGuard<'_, T> {
+        match self.try_borrow_upgradable() {
+            Ok(guard) => guard,
+            Err(BorrowMutError::Frozen) => AbortPanic::abort("Attempted to upgradably borrow cell after it was frozen."),
+            Err(BorrowMutError::Upgradable) => AbortPanic::abort("Attempted to upgradably borrow cell while it had an upgradable borrow."),
+            Err(BorrowMutError::Reserved) => AbortPanic::abort("Attempted to upgradably borrow cell while a write to it was reserved."),
+            Err(_) => AbortPanic::abort("Attempted to upgradably borrow cell while it was mutably borrowed."),
+        }
+    }
+
+    /// Attempts to borrow the value of this cell upgradably, returning an error instead of
+    /// aborting if the cell is mutably borrowed, frozen, or already has an upgradable borrow or
+    /// a reserved write.
+    #[inline(always)]
+    pub fn try_borrow_upgradable(&self) -> Result<UpgradableGuard<'_, T>, BorrowMutError> {
+        self.inner.state.try_acquire_upgradable()?;
+        Ok(UpgradableGuard {
+            value: unsafe { NonNull::new_unchecked(self.inner.value.get()) },
+            state: &self.inner.state,
+        })
+    }
+
+    /// Mutably borrows the value of this cell, and returns a guard for a copy of the value. The
+    /// copy is only written back to the cell when [`SpeculativeGuard::commit`] is called, so
+    /// dropping the guard leaves the original value intact.
+    #[inline(always)]
+    pub fn borrow_mut_speculative(&self) -> SpeculativeGuard<'_, T>
+    where
+        T: Clone,
+    {
+        let guard = self.borrow_mut_tracked();
+        SpeculativeGuard {
+            value: (*guard).clone(),
+            guard,
+        }
+    }
+
+    /// Mutably borrows the value of this cell, and returns a guard that only records a change to
+    /// the value if it is mutably dereferenced.
+    #[inline(always)]
+    pub fn borrow_mut_tracked(&self) -> TrackedMutGuard<'_, T> {
+        let (value, state) = self.borrow_mut().into_parts();
+        TrackedMutGuard {
+            value,
+            state: state.get(),
+            changed: false,
+        }
+    }
+
+    /// Immutably borrows the value of this cell without any borrow accounting. In debug
+    /// builds, this asserts that the cell is not mutably borrowed.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, the value must not be mutably borrowed by any means
+    /// while the returned reference exists.
+    #[inline(always)]
+    pub unsafe fn borrow_unchecked(&self) -> &T {
+        debug_assert!(!is_writing(self.inner.state.counter.load(Ordering::Relaxed)), "Attempted to immutably borrow cell while it was mutably borrowed.");
+        &*self.inner.value.get()
+    }
+
+    /// Mutably borrows the value of this cell without any borrow accounting. In debug
+    /// builds, this asserts that the cell is not borrowed by any guards.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, the value must not be borrowed by any other means
+    /// while the returned reference exists.
+    #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn borrow_mut_unchecked(&self) -> &mut T {
+        debug_assert!(self.inner.state.counter.load(Ordering::Relaxed) & !PARKED == 0, "Attempted to mutably borrow cell while other borrows already existed.");
+        &mut *self.inner.value.get()
+    }
+
+    /// Moves the value out of this cell, leaving the cell mutably borrowed until the returned
+    /// reservation is checked in with a new value. Dropping the reservation without checking
+    /// in a value aborts the program. Aborts under the same conditions as [`RwCell::borrow_mut`].
+    #[inline(always)]
+    pub fn checkout(&self) -> (T, WriteReservation<'_, T>) {
+        self.checkout_inner(None)
+    }
+
+    /// Moves the value out of this cell, leaving the cell mutably borrowed until the returned
+    /// reservation is checked in with a new value. Dropping the reservation without checking
+    /// in a value stores [`Default::default`] in the cell. Aborts under the same conditions as
+    /// [`RwCell::borrow_mut`]. If [`Default::default`] unwinds while the reservation is dropped,
+    /// the program aborts, since the cell would otherwise be left without a value.
+    #[inline(always)]
+    pub fn checkout_or_default(&self) -> (T, WriteReservation<'_, T>)
+    where
+        T: Default,
+    {
+        self.checkout_inner(Some(T::default))
+    }
+
+    /// Permanently makes this cell read-only. Afterward, immutable borrows do not modify the
+    /// borrow counter, and mutable borrows always conflict. Because the borrows of a frozen
+    /// cell are not tracked, [`RwCell::try_into_inner`] always fails for it. Fails under the
+    /// same conditions as [`RwCell::try_borrow_mut`], except that freezing a cell again has
+    /// no effect.
+    #[inline(always)]
+    pub fn freeze(&self) -> Result<(), BorrowMutError> {
+        match transition(&self.inner.state.counter, 0, FROZEN, Ordering::AcqRel) {
+            Ok(previous) => {
+                self.inner.state.unpark(previous);
+                Ok(())
+            },
+            Err(state) if is_frozen(state) => Ok(()),
+            Err(state) if is_writing(state) => Err(BorrowMutError::Writing),
+            Err(state) if state & UPGRADABLE != 0 => Err(BorrowMutError::Upgradable),
+            Err(state) if state & INTENT != 0 => Err(BorrowMutError::Reserved),
+            Err(_) => Err(BorrowMutError::Reading),
+        }
+    }
+
+    /// Immutably borrows the value of this cell for the duration of `f`. The borrow is
+    /// released when `f` returns or unwinds.
+    #[inline(always)]
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.borrow())
+    }
+
+    /// Mutably borrows the value of this cell for the duration of `f`. The borrow is
+    /// released when `f` returns or unwinds.
+    #[inline(always)]
+    pub fn with_mut<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.borrow_mut())
+    }
+
+    /// Applies `f` to the value of this cell. This is equivalent to [`RwCell::with_mut`],
+    /// which may be used instead when `f` needs to return a value.
+    #[inline(always)]
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        self.with_mut(f)
+    }
+
+    /// Replaces the value of this cell, returning the previous value. Aborts under the same
+    /// conditions as [`RwCell::borrow_mut`].
+    #[inline(always)]
+    pub fn replace(&self, value: T) -> T {
+        replace(&mut *self.borrow_mut(), value)
+    }
+
+    /// Replaces the value of this cell with one computed from the previous value, returning
+    /// the previous value. The cell remains mutably borrowed while `f` runs.
+    #[inline(always)]
+    pub fn replace_with(&self, f: impl FnOnce(&mut T) -> T) -> T {
+        let mut guard = self.borrow_mut();
+        let value = f(&mut guard);
+        replace(&mut *guard, value)
+    }
+
+    /// Gets a copy of the value of this cell. Aborts under the same conditions as
+    /// [`RwCell::borrow`].
+    #[inline(always)]
+    pub fn get(&self) -> T
+    where
+        T: Copy,
+    {
+        *self.borrow()
+    }
+
+    /// Clones the value of this cell. The borrow is released before returning, even if
+    /// cloning unwinds. Aborts under the same conditions as [`RwCell::borrow`].
+    #[inline(always)]
+    pub fn clone_inner(&self) -> T
+    where
+        T: Clone,
+    {
+        T::clone(&self.borrow())
+    }
+
+    /// Clones the value of this cell, returning [`None`] instead of aborting if the cell is
+    /// mutably borrowed.
+    #[inline(always)]
+    pub fn try_clone_inner(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.try_borrow().ok().map(|value| T::clone(&value))
+    }
+
+    /// Sets the value of this cell. The previous value is dropped while the cell is still
+    /// mutably borrowed, so a destructor that accesses the cell will conflict.
+    #[inline(always)]
+    pub fn set(&self, value: T) {
+        *self.borrow_mut() = value;
+    }
+
+    /// Stores `new` in this cell if its value equals `current`. Returns the previous value,
+    /// wrapped in [`Ok`] if the exchange occurred and [`Err`] otherwise. Aborts under the same
+    /// conditions as [`RwCell::borrow_mut`].
+    #[inline(always)]
+    pub fn compare_exchange(&self, current: T, new: T) -> Result<T, T>
+    where
+        T: Copy + PartialEq,
+    {
+        let mut guard = self.borrow_mut();
+        let previous = *guard;
+        if previous == current {
+            *guard = new;
+            Ok(previous)
+        }
+        else {
+            Err(previous)
+        }
+    }
+
+    /// Applies `f` to the value of this cell and stores the result if it is [`Some`]. Returns
+    /// the previous value, wrapped in [`Ok`] if a new value was stored and [`Err`] otherwise.
+    /// Aborts under the same conditions as [`RwCell::borrow_mut`].
+    #[inline(always)]
+    pub fn fetch_update(&self, mut f: impl FnMut(T) -> Option<T>) -> Result<T, T>
+    where
+        T: Copy,
+    {
+        let mut guard = self.borrow_mut();
+        let previous = *guard;
+        match f(previous) {
+            Some(value) => {
+                *guard = value;
+                Ok(previous)
+            }
+            None => Err(previous),
+        }
+    }
+
+    /// Takes the value of this cell, leaving [`Default::default`] in its place. Aborts under
+    /// the same conditions as [`RwCell::borrow_mut`].
+    #[inline(always)]
+    pub fn take(&self) -> T
+    where
+        T: Default,
+    {
+        take(&mut *self.borrow_mut())
+    }
+
+    /// Swaps the values of two cells. Aborts if either cell is already borrowed. Swapping a
+    /// cell with itself has no effect beyond the usual conflict checks.
+    #[inline(always)]
+    pub fn swap(&self, other: &Self) {
+        if core::ptr::eq(self, other) {
+            drop(self.borrow_mut());
+        }
+        else {
+            swap(&mut *self.borrow_mut(), &mut *other.borrow_mut());
+        }
+    }
+
+    /// Gets a raw pointer to the value of this cell. The pointer may be used to access the
+    /// value, but no borrow accounting is performed.
+    #[inline(always)]
+    pub fn as_ptr(&self) -> *mut T {
+        self.inner.value.get()
+    }
+
+    /// Gets a raw pointer to the value of a cell, given only a raw pointer to the cell itself.
+    /// No borrow accounting is performed.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, `this` must point to a valid, initialized cell.
+    #[inline(always)]
+    pub unsafe fn raw_get(this: *const Self) -> *mut T {
+        let inner = ReadCell::raw_get(addr_of!((*this).inner));
+        UnsafeCell::raw_get(addr_of!((*inner).value))
+    }
+
+    /// Attempts to immutably borrow the value of this cell, retrying up to `max_spins` times
+    /// while the cell is mutably borrowed. Returns [`None`] instead of aborting if the
+    /// borrow could not be acquired.
+    #[inline(always)]
+    pub fn borrow_spin(&self, max_spins: usize) -> Option<RwCellGuard<'_, Const, T>> {
+        Self::spin(max_spins, || self.try_borrow().ok())
+    }
+
+    /// Attempts to mutably borrow the value of this cell, retrying up to `max_spins` times
+    /// while other borrows exist. Returns [`None`] instead of aborting if the borrow could
+    /// not be acquired.
+    #[inline(always)]
+    pub fn borrow_mut_spin(&self, max_spins: usize) -> Option<RwCellGuard<'_, Mut, T>> {
+        Self::spin(max_spins, || self.try_borrow_mut().ok())
+    }
+
+    /// Immutably borrows the value of this cell, spinning until any mutable borrow is released
+    /// instead of aborting.
+    #[inline(always)]
+    pub fn borrow_blocking(&self) -> RwCellGuard<'_, Const, T> {
+        self.inner.state.spin_acquire_read();
+        unsafe { self.read_guard() }
+    }
+
+    /// Mutably borrows the value of this cell, spinning until all other borrows are released
+    /// instead of aborting. Aborts if the cell is frozen, since it can never be mutably borrowed.
+    #[inline(always)]
+    pub fn borrow_mut_blocking(&self) -> RwCellGuard<'_, Mut, T> {
+        self.inner.state.spin_acquire_write();
+        unsafe { self.write_guard() }
+    }
+
+    /// Blocks the current thread until `pred` holds for the value of this cell, then returns
+    /// an immutable borrow of the value. The predicate is checked immediately and again
+    /// each time a mutable borrow of the cell is released, and may also be checked spuriously.
+    #[cfg(feature = "std")]
+    pub fn wait_until(&self, mut pred: impl FnMut(&T) -> bool) -> RwCellGuard<'_, Const, T> {
+        loop {
+            let guard = self.borrow_wait();
+            if pred(&guard) {
+                return guard;
+            }
+
+            forget(guard);
+            self.inner.state.release_read_and_park();
+        }
+    }
+
+    /// Immutably borrows the value of this cell, parking the current thread until any mutable
+    /// borrow is released instead of aborting.
+    #[cfg(feature = "std")]
+    pub fn borrow_wait(&self) -> RwCellGuard<'_, Const, T> {
+        ParkingBucket::park_until(&self.inner.state.counter, || self.try_borrow().ok())
+    }
+
+    /// Mutably borrows the value of this cell, parking the current thread until all other
+    /// borrows are released instead of aborting. Aborts if the cell is frozen, since it can
+    /// never be mutably borrowed.
+    #[cfg(feature = "std")]
+    pub fn borrow_mut_wait(&self) -> RwCellGuard<'_, Mut, T> {
+        ParkingBucket::park_until(&self.inner.state.counter, || match self.try_borrow_mut() {
+            Ok(guard) => Some(guard),
+            Err(BorrowMutError::Frozen) => AbortPanic::abort("Attempted to mutably borrow cell after it was frozen."),
+            Err(_) => None,
+        })
+    }
+
+    /// Attempts to immutably borrow the value of this cell, parking the current thread for up
+    /// to `timeout` while the cell is mutably borrowed. Returns [`None`] if the borrow could not
+    /// be acquired in time.
+    #[cfg(feature = "std")]
+    pub fn try_borrow_for(&self, timeout: core::time::Duration) -> Option<RwCellGuard<'_, Const, T>> {
+        self.try_borrow_deadline(std::time::Instant::now().checked_add(timeout))
+    }
+
+    /// Attempts to immutably borrow the value of this cell, parking the current thread until
+    /// `deadline` while the cell is mutably borrowed. Returns [`None`] if the borrow could not
+    /// be acquired in time.
+    #[cfg(feature = "std")]
+    pub fn try_borrow_until(&self, deadline: std::time::Instant) -> Option<RwCellGuard<'_, Const, T>> {
+        self.try_borrow_deadline(Some(deadline))
+    }
+
+    /// Attempts to mutably borrow the value of this cell, parking the current thread for up
+    /// to `timeout` while other borrows exist. Returns [`None`] if the borrow could not be
+    /// acquired in time, or if the cell is frozen.
+    #[cfg(feature = "std")]
+    pub fn try_borrow_mut_for(&self, timeout: core::time::Duration) -> Option<RwCellGuard<'_, Mut, T>> {
+        self.try_borrow_mut_deadline(std::time::Instant::now().checked_add(timeout))
+    }
+
+    /// Attempts to mutably borrow the value of this cell, parking the current thread until
+    /// `deadline` while other borrows exist. Returns [`None`] if the borrow could not be
+    /// acquired in time, or if the cell is frozen.
+    #[cfg(feature = "std")]
+    pub fn try_borrow_mut_until(&self, deadline: std::time::Instant) -> Option<RwCellGuard<'_, Mut, T>> {
+        self.try_borrow_mut_deadline(Some(deadline))
+    }
+
+    /// Attempts to immutably borrow the value of this cell, parking the current thread until
+    /// the deadline passes. A missing deadline waits forever.
+    #[cfg(feature = "std")]
+    fn try_borrow_deadline(&self, deadline: Option<std::time::Instant>) -> Option<RwCellGuard<'_, Const, T>> {
+        ParkingBucket::park_until_deadline(&self.inner.state.counter, deadline, || self.try_borrow().ok())
+    }
+
+    /// Attempts to mutably borrow the value of this cell, parking the current thread until
+    /// the deadline passes. A missing deadline waits forever. Fails immediately if the cell
+    /// is frozen.
+    #[cfg(feature = "std")]
+    fn try_borrow_mut_deadline(&self, deadline: Option<std::time::Instant>) -> Option<RwCellGuard<'_, Mut, T>> {
+        ParkingBucket::park_until_deadline(&self.inner.state.counter, deadline, || match self.try_borrow_mut() {
+            Ok(guard) => Some(Some(guard)),
+            Err(BorrowMutError::Frozen) => Some(None),
+            Err(_) => None,
+        })
+        .flatten()
+    }
+
+    /// Determines whether this cell is free to be borrowed.
+    #[inline(always)]
+    pub fn free(&self) -> bool {
+        self.inner.state.counter.load(Ordering::Acquire) & !PARKED == 0
+    }
+
+    /// Releases one immutable borrow of this cell without a guard, such as one that was leaked
+    /// with [`core::mem::forget`].
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, the cell must be immutably borrowed, and the released
+    /// borrow must not be used again. This has no effect if the cell is frozen.
+    #[inline(always)]
+    pub unsafe fn force_unlock_read(&self) {
+        let counter = &self.inner.state.counter;
+        if !is_frozen(counter.load(Ordering::Relaxed)) {
+            let previous = self.inner.state.release_read();
+            debug_assert!(previous & !PARKED != 0 && !is_writing(previous), "Attempted to force unlock read on cell that was not immutably borrowed.");
+        }
+    }
+
+    /// Releases one mutable borrow of this cell without a guard, such as one that was leaked
+    /// with [`core::mem::forget`]. If the borrow was split, the cell remains mutably borrowed
+    /// until every part is released.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, the cell must be mutably borrowed, and the released
+    /// borrow must not be used again.
+    #[inline(always)]
+    pub unsafe fn force_unlock_write(&self) {
+        debug_assert!(is_writing(self.inner.state.counter.load(Ordering::Relaxed)), "Attempted to force unlock write on cell that was not mutably borrowed.");
+        self.inner.state.release_write();
+    }
+
+    /// Takes a snapshot of this cell's borrow state. Other threads may borrow or release
+    /// the cell concurrently, so the result may be out of date as soon as it is returned.
+    #[inline(always)]
+    pub fn borrow_state(&self) -> BorrowState {
+        self.inner.state.borrow_state()
+    }
+
+    /// Gets the number of immutable borrows of this cell. This is zero if the cell is
+    /// mutably borrowed. The result is racy in the same way as [`RwCell::borrow_state`].
+    #[inline(always)]
+    pub fn reader_count(&self) -> usize {
+        match self.borrow_state() {
+            BorrowState::Reading(readers) => readers,
+            _ => 0,
+        }
+    }
+
+    /// Determines whether this cell is mutably borrowed. The result is racy in the same way
+    /// as [`RwCell::borrow_state`].
+    #[inline(always)]
+    pub fn is_locked_exclusive(&self) -> bool {
+        self.borrow_state() == BorrowState::Writing
+    }
+
+    /// Invokes `f` until it succeeds, spinning between attempts, and gives up after `max_spins`
+    /// retries.
+    #[inline(always)]
+    fn spin<R>(max_spins: usize, mut f: impl FnMut() -> Option<R>) -> Option<R> {
+        for _ in 0..max_spins {
+            if let Some(result) = f() {
+                return Some(result);
+            }
+
+            core::hint::spin_loop();
+        }
+
+        f()
+    }
+
+    /// Gets the number of times that this cell has been mutably borrowed, wrapping on overflow.
+    /// The version is incremented when a mutable guard is dropped, so a thread that observes a
+    /// new version will also observe the data written under that guard. [`RwCell::get_mut`]
+    /// also counts as a mutable borrow, while guards that record no change, such as an
+    /// uncommitted [`SpeculativeGuard`], leave the version unchanged.
+    #[cfg(feature = "versioning")]
+    #[inline(always)]
+    pub fn version(&self) -> u64 {
+        self.inner.state.version.load(Ordering::Acquire)
+    }
+
+    /// Determines whether this cell has been mutably borrowed since [`RwCell::version`]
+    /// returned `last`.
+    #[cfg(feature = "versioning")]
+    #[inline(always)]
+    pub fn changed_since(&self, last: u64) -> bool {
+        self.version() != last
+    }
+
+    /// Attempts to mutably borrow the value of this cell, registering `waker` to be woken once
+    /// every borrow has been released if other borrows exist. The cell has a single slot for
+    /// wakers, so only the most recently registered waker is woken. Aborts if the cell is frozen.
+    #[cfg(feature = "waker")]
+    #[inline(always)]
+    pub fn poll_borrow_mut(&self, waker: &core::task::Waker) -> core::task::Poll<RwCellGuard<'_, Mut, T>> {
+        match self.try_borrow_mut() {
+            Ok(guard) => return core::task::Poll::Ready(guard),
+            Err(BorrowMutError::Frozen) => AbortPanic::abort("Attempted to mutably borrow cell after it was frozen."),
+            Err(_) => self.inner.state.waker.register(waker),
+        }
+
+        match self.try_borrow_mut() {
+            Ok(guard) => core::task::Poll::Ready(guard),
+            Err(_) => core::task::Poll::Pending,
+        }
+    }
+
+    /// Moves the value out of this cell, returning it alongside a reservation that uses
+    /// `restore` to refill the cell if it is dropped.
+    #[inline(always)]
+    fn checkout_inner(&self, restore: Option<fn() -> T>) -> (T, WriteReservation<'_, T>) {
+        let guard = self.borrow_mut();
+        forget(guard);
+        unsafe {
+            (self.inner.value.get().read(), WriteReservation { inner: &self.inner, restore })
+        }
+    }
+
+    /// Creates an immutable guard for the value of this cell.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, the caller must have acquired an immutable borrow.
+    #[inline(always)]
+    unsafe fn read_guard(&self) -> RwCellGuard<'_, Const, T> {
+        RwCellGuard::from_parts(NonNull::new_unchecked(self.inner.value.get()), StateRef::new(&self.inner.state))
+    }
+
+    /// Attempts to borrow the value of this cell with the given mutability, on behalf of a
+    /// waiter that will retry later. Aborts if a mutable borrow of a frozen cell is requested,
+    /// since it could never succeed.
+    #[inline(always)]
+    fn try_borrow_waiting<M: Mutability>(&self) -> Option<RwCellGuard<'_, M, T>> {
+        let state = &self.inner.state;
+        let acquired = if TypeId::of::<M>() == TypeId::of::<Mut>() {
+            match state.try_acquire_write() {
+                Ok(()) => true,
+                Err(BorrowMutError::Frozen) => AbortPanic::abort("Attempted to mutably borrow cell after it was frozen."),
+                Err(_) => false,
+            }
+        }
+        else {
+            state.try_acquire_read()
+        };
+
+        acquired.then(|| unsafe { RwCellGuard::from_parts(NonNull::new_unchecked(self.inner.value.get()), StateRef::new(state)) })
+    }
+
+    /// Creates a mutable guard for the value of this cell.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, the caller must have acquired a mutable borrow.
+    #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn write_guard(&self) -> RwCellGuard<'_, Mut, T> {
+        RwCellGuard::from_parts(NonNull::new_unchecked(self.inner.value.get()), StateRef::new(&self.inner.state))
+    }
+}
+
+impl<T: Copy, P: ConflictPolicy> RwCell<T, P> {
+    /// Adds `rhs` to the value of this cell, returning the new value. Aborts under the same
+    /// conditions as [`RwCell::borrow_mut`].
+    #[inline(always)]
+    pub fn add_assign(&self, rhs: T) -> T
+    where
+        T: AddAssign,
+    {
+        self.with_mut(|value| {
+            *value += rhs;
+            *value
+        })
+    }
+
+    /// Subtracts `rhs` from the value of this cell, returning the new value. Aborts under the
+    /// same conditions as [`RwCell::borrow_mut`].
+    #[inline(always)]
+    pub fn sub_assign(&self, rhs: T) -> T
+    where
+        T: SubAssign,
+    {
+        self.with_mut(|value| {
+            *value -= rhs;
+            *value
+        })
+    }
+
+    /// Applies a bitwise or of `rhs` to the value of this cell, returning the new value. Aborts
+    /// under the same conditions as [`RwCell::borrow_mut`].
+    #[inline(always)]
+    pub fn bitor_assign(&self, rhs: T) -> T
+    where
+        T: BitOrAssign,
+    {
+        self.with_mut(|value| {
+            *value |= rhs;
+            *value
+        })
+    }
+
+    /// Sets the value of this cell to the minimum of its value and `rhs`, returning the new
+    /// value. Aborts under the same conditions as [`RwCell::borrow_mut`].
+    #[inline(always)]
+    pub fn min_assign(&self, rhs: T) -> T
+    where
+        T: Ord,
+    {
+        self.with_mut(|value| {
+            *value = (*value).min(rhs);
+            *value
+        })
+    }
+
+    /// Sets the value of this cell to the maximum of its value and `rhs`, returning the new
+    /// value. Aborts under the same conditions as [`RwCell::borrow_mut`].
+    #[inline(always)]
+    pub fn max_assign(&self, rhs: T) -> T
+    where
+        T: Ord,
+    {
+        self.with_mut(|value| {
+            *value = (*value).max(rhs);
+            *value
+        })
+    }
+}
+
+impl<T, P: ConflictPolicy> RwCell<Option<T>, P> {
+    /// Mutably borrows the value of this cell, first inserting the result of `init` if the
+    /// cell is empty. The returned guard is mapped to the contained value.
+    #[inline(always)]
+    pub fn borrow_or_insert_with(&self, init: impl FnOnce() -> T) -> RwCellGuard<'_, Mut, T> {
+        let mut guard = self.borrow_mut();
+        if guard.is_none() {
+            *guard = Some(init());
+        }
+
+        RwCellGuard::<Mut, _>::map(guard, |value| unsafe { value.as_mut().unwrap_unchecked() })
+    }
+
+    /// Immutably borrows the value of this cell if it is not empty. The returned guard is
+    /// mapped to the contained value.
+    #[inline(always)]
+    pub fn borrow_some(&self) -> Option<RwCellGuard<'_, Const, T>> {
+        let guard = self.borrow();
+        if guard.is_some() {
+            Some(RwCellGuard::<Const, _>::map(guard, |value| unsafe { value.as_ref().unwrap_unchecked() }))
+        }
+        else {
+            None
+        }
+    }
+}
+
+impl<T, P: ConflictPolicy> RwCell<MaybeUninit<T>, P> {
+    /// Initializes the value of this cell, returning a mutable guard to the now-initialized
+    /// value. Any value that was previously stored is overwritten without being dropped.
+    /// Aborts under the same conditions as [`RwCell::borrow_mut`].
+    #[inline(always)]
+    pub fn write_init(&self, value: T) -> RwCellGuard<'_, Mut, T> {
+        let mut guard = self.borrow_mut();
+        guard.write(value);
+        RwCellGuard::<Mut, _>::map(guard, |value| unsafe { value.assume_init_mut() })
+    }
+
+    /// Converts this cell into a cell of the initialized value, preserving its borrow state.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, the value of this cell must be initialized.
+    #[inline(always)]
+    pub unsafe fn assume_init(self) -> RwCell<T, P> {
+        let this = ManuallyDrop::new(self);
+        (&*this as *const Self as *const RwCell<T, P>).read()
+    }
+
+    /// Views this cell as a cell of the initialized value.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, the value of this cell must be initialized, and must
+    /// remain initialized for as long as the returned reference exists.
+    #[inline(always)]
+    pub unsafe fn assume_init_ref(&self) -> &RwCell<T, P> {
+        &*(self as *const Self as *const RwCell<T, P>)
+    }
+}
+
+impl<T: fmt::Debug, P> fmt::Debug for RwCell<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RwCell").field("inner", &self.inner).finish()
+    }
+}
+
+impl<T: Default, P: ConflictPolicy> Default for RwCell<T, P> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::with_policy(T::default())
+    }
+}
+
+unsafe impl<T: Send + Sync, P> Sync for RwCell<T, P> {}
+
+/// A snapshot of the borrow state of a cell.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BorrowState {
+    /// The cell is not borrowed.
+    Free,
+    /// The cell is immutably borrowed by the given number of guards, including any
+    /// upgradable guard.
+    Reading(usize),
+    /// The cell is mutably borrowed.
+    Writing,
+    /// The cell is frozen, and its immutable borrows are not tracked.
+    Frozen,
+}
+
+/// An error returned by [`RwCell::try_borrow`] when the cell is mutably borrowed.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BorrowError;
+
+impl fmt::Display for BorrowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Attempted to immutably borrow cell while it was mutably borrowed.")
+    }
+}
+
+impl core::error::Error for BorrowError {}
+
+/// An error returned by [`RwCell::try_borrow_mut`] when other borrows of the cell exist.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BorrowMutError {
+    /// The cell was immutably borrowed.
+    Reading,
+    /// The cell was mutably borrowed.
+    Writing,
+    /// The cell was frozen.
+    Frozen,
+    /// The cell had an upgradable borrow.
+    Upgradable,
+    /// The cell had a reserved write.
+    Reserved,
+}
+
+impl fmt::Display for BorrowMutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reading => f.write_str("Attempted to mutably borrow cell while it was immutably borrowed."),
+            Self::Writing => f.write_str("Attempted to mutably borrow cell while it was mutably borrowed."),
+            Self::Frozen => f.write_str("Attempted to mutably borrow cell after it was frozen."),
+            Self::Upgradable => f.write_str("Attempted to mutably borrow cell while it had an upgradable borrow."),
+            Self::Reserved => f.write_str("Attempted to mutably borrow cell while a write to it was reserved."),
+        }
+    }
+}
+
+impl core::error::Error for BorrowMutError {}
+
+/// Stores the inner data for a read-write cell. This uses a C-compatible layout so that
+/// cells containing layout-compatible values, such as `T` and `MaybeUninit<T>`, are also
+/// layout-compatible.
+#[derive(Debug, Default)]
+#[repr(C)]
+struct RwCellInner<T> {
+    /// The value contained in the cell.
+    value: UnsafeCell<T>,
+    /// The borrow state.
+    state: RwCellState,
+}
+
+/// The largest number of immutable borrows that a cell may have at once, which is documented
+/// on [`RwCell::borrow`]. This also masks the reader count within a counter value.
+const MAX_READERS: u16 = 0x0FFF;
+/// The counter bit that is set while a cell has a reserved write, created by
+/// [`RwCell::reserve_write`]. Like [`UPGRADABLE`], immutable borrows may coexist with it, but
+/// the two bits are never set at once.
+const INTENT: u16 = 0x1000;
+/// The counter bit that is set while a cell has an upgradable borrow. Immutable borrows may
+/// coexist with it, and are counted in the lower bits.
+const UPGRADABLE: u16 = 0x2000;
+/// The counter value of a frozen cell.
+const FROZEN: u16 = 0x4000;
+/// The counter value of a cell with a single mutable borrow. Each time that a mutable borrow
+/// is split, the counter is decremented, down to a minimum of [`MIN_WRITER`].
+const WRITER: u16 = 0x7FFF;
+/// The smallest counter value of a mutably-borrowed cell.
+const MIN_WRITER: u16 = 0x6000;
+/// The counter bit that is set while threads may be parked waiting on the cell. It is kept
+/// alongside every other state, so releasing a borrow only visits the parking table when
+/// this bit was set in the value that the release replaced.
+const PARKED: u16 = 0x8000;
+
+/// Determines whether a counter value belongs to a frozen cell. Readers that race with
+/// [`RwCell::freeze`] may briefly increment the counter of a frozen cell, so a range of
+/// values is reserved for this state.
+#[inline(always)]
+const fn is_frozen(counter: u16) -> bool {
+    counter & 0x6000 == FROZEN
+}
+
+/// Determines whether a counter value belongs to a mutably-borrowed cell.
+#[inline(always)]
+const fn is_writing(counter: u16) -> bool {
+    counter & !PARKED >= MIN_WRITER
+}
+
+/// Atomically replaces the counter value `current` with `new`, leaving the parked bit of the
+/// counter unchanged. Returns the previous counter value, or the current one on failure.
+#[inline(always)]
+fn transition(counter: &AtomicU16, current: u16, new: u16, success: Ordering) -> Result<u16, u16> {
+    let mut expected = current;
+    loop {
+        match counter.compare_exchange(expected, new | expected & PARKED, success, Ordering::Relaxed) {
+            Ok(previous) => return Ok(previous),
+            Err(state) if state & !PARKED == current => expected = state,
+            Err(state) => return Err(state),
+        }
+    }
+}
+
+/// Tracks the borrows of a read-write cell.
+#[derive(Debug, Default)]
+struct RwCellState {
+    /// The borrow counter.
+    counter: AtomicU16,
+    /// The number of mutable borrows that have been released.
+    #[cfg(feature = "versioning")]
+    version: AtomicU64,
+    /// The task waiting for every borrow to be released.
+    #[cfg(feature = "waker")]
+    waker: WakerSlot,
+}
+
+impl RwCellState {
+    /// Creates a new state for an unborrowed cell.
+    #[inline(always)]
+    const fn new() -> Self {
+        Self {
+            counter: AtomicU16::new(0),
+            #[cfg(feature = "versioning")]
+            version: AtomicU64::new(0),
+            #[cfg(feature = "waker")]
+            waker: WakerSlot::new(),
+        }
+    }
+
+    /// Gets the current borrow state. An upgradable borrow or a reserved write is counted as a reader.
+    #[inline(always)]
+    fn borrow_state(&self) -> BorrowState {
+        match self.counter.load(Ordering::Acquire) & !PARKED {
+            0 => BorrowState::Free,
+            state if is_writing(state) => BorrowState::Writing,
+            state if is_frozen(state) => BorrowState::Frozen,
+            state => BorrowState::Reading((state & MAX_READERS) as usize + (state & (UPGRADABLE | INTENT) != 0) as usize),
+        }
+    }
+
+    /// Attempts to acquire an immutable borrow of the cell, failing if the cell is
+    /// mutably borrowed.
+    #[inline(always)]
+    fn try_acquire_read(&self) -> bool {
+        self.try_acquire_read_bounded(MAX_READERS)
+    }
+
+    /// Attempts to acquire an immutable borrow of the cell, failing if the cell is mutably
+    /// borrowed or already has `max_readers` immutable borrows. The limit is checked by the
+    /// same atomic update that records the borrow.
+    #[inline(always)]
+    fn try_acquire_read_bounded(&self, max_readers: u16) -> bool {
+        let mut state = self.counter.load(Ordering::Acquire);
+        loop {
+            if is_frozen(state) {
+                return true;
+            }
+            else if is_writing(state) || state & MAX_READERS >= max_readers {
+                return false;
+            }
+
+            match self.counter.compare_exchange_weak(state, state + 1, Ordering::Acquire, Ordering::Acquire) {
+                Ok(_) => return true,
+                Err(current) => state = current,
+            }
+        }
+    }
+
+    /// Attempts to acquire a mutable borrow of the cell, failing if any other borrows exist.
+    #[inline(always)]
+    fn try_acquire_write(&self) -> Result<(), BorrowMutError> {
+        match transition(&self.counter, 0, WRITER, Ordering::Acquire) {
+            Ok(_) => Ok(()),
+            Err(state) if is_writing(state) => Err(BorrowMutError::Writing),
+            Err(state) if is_frozen(state) => Err(BorrowMutError::Frozen),
+            Err(state) if state & UPGRADABLE != 0 => Err(BorrowMutError::Upgradable),
+            Err(state) if state & INTENT != 0 => Err(BorrowMutError::Reserved),
+            Err(_) => Err(BorrowMutError::Reading),
+        }
+    }
+
+    /// Attempts to acquire an upgradable borrow of the cell, failing if the cell is mutably
+    /// borrowed, frozen, or already has an upgradable borrow or a reserved write.
+    #[inline(always)]
+    fn try_acquire_upgradable(&self) -> Result<(), BorrowMutError> {
+        let mut state = self.counter.load(Ordering::Acquire);
+        loop {
+            if is_writing(state) {
+                return Err(BorrowMutError::Writing);
+            }
+            else if is_frozen(state) {
+                return Err(BorrowMutError::Frozen);
+            }
+            else if state & UPGRADABLE != 0 {
+                return Err(BorrowMutError::Upgradable);
+            }
+            else if state & INTENT != 0 {
+                return Err(BorrowMutError::Reserved);
+            }
+
+            match self.counter.compare_exchange_weak(state, state | UPGRADABLE, Ordering::Acquire, Ordering::Acquire) {
+                Ok(_) => return Ok(()),
+                Err(current) => state = current,
+            }
+        }
+    }
+
+    /// Attempts to reserve a write to the cell, failing under the same conditions as
+    /// [`RwCellState::try_acquire_upgradable`].
+    #[inline(always)]
+    fn try_acquire_intent(&self) -> Result<(), BorrowMutError> {
+        let mut state = self.counter.load(Ordering::Acquire);
+        loop {
+            if is_writing(state) {
+                return Err(BorrowMutError::Writing);
+            }
+            else if is_frozen(state) {
+                return Err(BorrowMutError::Frozen);
+            }
+            else if state & UPGRADABLE != 0 {
+                return Err(BorrowMutError::Upgradable);
+            }
+            else if state & INTENT != 0 {
+                return Err(BorrowMutError::Reserved);
+            }
+
+            match self.counter.compare_exchange_weak(state, state | INTENT, Ordering::Acquire, Ordering::Acquire) {
+                Ok(_) => return Ok(()),
+                Err(current) => state = current,
+            }
+        }
+    }
+
+    /// Acquires an immutable borrow of the cell. Aborts if the cell is mutably borrowed.
+    #[inline(always)]
+    fn acquire_read(&self) {
+        if !is_frozen(self.counter.load(Ordering::Acquire)) {
+            let previous = self.counter.fetch_add(1, Ordering::AcqRel);
+            if is_frozen(previous) {
+                self.counter.fetch_sub(1, Ordering::Relaxed);
+            }
+            else if is_writing(previous) {
+                AbortPanic::abort("Attempted to immutably borrow cell while it was mutably borrowed.");
+            }
+            else if previous & MAX_READERS == MAX_READERS {
+                AbortPanic::abort("Attempted to immutably borrow cell too many times.");
+            }
+        }
+    }
+
+    /// Acquires an immutable borrow of the cell, consulting the policy `P` each time the
+    /// borrow conflicts. The default policy uses the same single update as
+    /// [`RwCellState::acquire_read`].
+    #[inline(always)]
+    fn acquire_read_with<P: ConflictPolicy>(&self) {
+        if TypeId::of::<P>() == TypeId::of::<Abort>() {
+            self.acquire_read();
+        }
+        else {
+            let mut attempts = 0;
+            while !self.try_acquire_read() {
+                let reason = if is_writing(self.counter.load(Ordering::Relaxed)) {
+                    "Attempted to immutably borrow cell while it was mutably borrowed."
+                }
+                else {
+                    "Attempted to immutably borrow cell too many times."
+                };
+                Self::resolve_conflict(P::on_read_conflict(attempts), reason);
+                attempts += 1;
+            }
+        }
+    }
+
+    /// Acquires a mutable borrow of the cell, consulting the policy `P` each time the borrow
+    /// conflicts. The default policy uses the same single update as
+    /// [`RwCellState::acquire_write`].
+    #[inline(always)]
+    fn acquire_write_with<P: ConflictPolicy>(&self) {
+        if TypeId::of::<P>() == TypeId::of::<Abort>() {
+            self.acquire_write();
+        }
+        else {
+            let mut attempts = 0;
+            loop {
+                match self.try_acquire_write() {
+                    Ok(()) => return,
+                    Err(BorrowMutError::Frozen) => Self::resolve_conflict(P::on_write_conflict(attempts), "Attempted to mutably borrow cell after it was frozen."),
+                    Err(_) => Self::resolve_conflict(P::on_write_conflict(attempts), "Attempted to mutably borrow cell while other borrows already existed."),
+                }
+
+                attempts += 1;
+            }
+        }
+    }
+
+    /// Carries out the action that a policy chose for a conflicting borrow. Returns only if the
+    /// borrow should be retried.
+    #[inline(always)]
+    fn resolve_conflict(action: ConflictAction, reason: &str) {
+        match action {
+            ConflictAction::Abort => AbortPanic::abort(reason),
+            ConflictAction::Panic => panic!("{}", reason),
+            ConflictAction::Retry => core::hint::spin_loop(),
+        }
+    }
+
+    /// Acquires a mutable borrow of the cell. Aborts if any other borrows exist. The parked bit
+    /// is restored if the swap cleared it.
+    #[inline(always)]
+    fn acquire_write(&self) {
+        let previous = self.counter.swap(WRITER, Ordering::AcqRel);
+        Self::abort_if(previous & !PARKED != 0, "Attempted to mutably borrow cell while other borrows already existed.");
+        if previous & PARKED != 0 {
+            self.counter.fetch_or(PARKED, Ordering::Relaxed);
+        }
+    }
+
+    /// Acquires an immutable borrow of the cell, spinning while the cell is mutably borrowed.
+    #[inline(always)]
+    fn spin_acquire_read(&self) {
+        while !self.try_acquire_read() {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Acquires a mutable borrow of the cell, spinning while any other borrows exist. The
+    /// counter is only changed once the cell is free, so that waiting does not disturb the
+    /// borrows being waited on. Aborts if the cell is frozen.
+    #[inline(always)]
+    fn spin_acquire_write(&self) {
+        loop {
+            match self.try_acquire_write() {
+                Ok(()) => return,
+                Err(BorrowMutError::Frozen) => AbortPanic::abort("Attempted to mutably borrow cell after it was frozen."),
+                Err(_) => loop {
+                    core::hint::spin_loop();
+                    let state = self.counter.load(Ordering::Relaxed);
+                    if state & !PARKED == 0 || is_frozen(state) {
+                        break;
+                    }
+                },
+            }
+        }
+    }
+
+    /// Aborts the program if the given condition is true.
+    #[inline(always)]
+    fn abort_if(condition: bool, reason: &str) {
+        if condition {
+            AbortPanic::abort(reason);
+        }
+    }
+
+    /// Releases a borrow of the cell with the given mutability.
+    #[inline(always)]
+    fn release<M: Mutability>(&self) {
+        if TypeId::of::<M>() == TypeId::of::<Mut>() {
+            self.release_write();
+        }
+        else if !is_frozen(self.counter.load(Ordering::Relaxed)) {
+            self.release_read();
+        }
+    }
+
+    /// Releases an immutable borrow of a cell that is not frozen, returning the previous
+    /// counter value.
+    #[inline(always)]
+    fn release_read(&self) -> u16 {
+        let previous = self.counter.fetch_sub(1, Ordering::AcqRel);
+        let readers = previous & MAX_READERS;
+        if readers == 1 || readers == MAX_READERS {
+            self.unpark(previous);
+        }
+        #[cfg(feature = "waker")]
+        if previous & !PARKED == 1 {
+            self.waker.wake();
+        }
+        previous
+    }
+
+    /// Releases a mutable borrow of the cell. The cell becomes free once every part of a split
+    /// borrow has been released.
+    #[inline(always)]
+    fn release_write(&self) {
+        #[cfg(feature = "versioning")]
+        self.version.fetch_add(1, Ordering::Release);
+        self.release_write_unchanged();
+    }
+
+    /// Releases a mutable borrow of the cell without recording a change to its value. The last
+    /// part of a split borrow frees the cell, keeping only the parked bit.
+    #[inline(always)]
+    fn release_write_unchanged(&self) {
+        let mut previous = WRITER;
+        while let Err(current) = self.counter.compare_exchange_weak(
+            previous,
+            if previous & !PARKED == WRITER { previous & PARKED } else { previous + 1 },
+            Ordering::Release,
+            Ordering::Relaxed,
+        ) {
+            previous = current;
+        }
+
+        if previous & !PARKED == WRITER {
+            self.unpark(previous);
+            #[cfg(feature = "waker")]
+            self.waker.wake();
+        }
+    }
+
+    /// Atomically converts a mutable borrow of the cell into a single immutable borrow. Aborts
+    /// if the mutable borrow has been split.
+    #[inline(always)]
+    fn downgrade_write(&self) {
+        #[cfg(feature = "versioning")]
+        self.version.fetch_add(1, Ordering::Release);
+        match transition(&self.counter, WRITER, 1, Ordering::Release) {
+            Ok(previous) => self.unpark(previous),
+            Err(_) => AbortPanic::abort("Attempted to downgrade split mutable borrow of cell."),
+        }
+    }
+
+    /// Adds another immutable borrow to a cell that is already immutably borrowed. Aborts if
+    /// the cell has too many immutable borrows.
+    #[inline(always)]
+    fn share_read(&self) {
+        if !is_frozen(self.counter.load(Ordering::Relaxed)) && self.counter.fetch_add(1, Ordering::Relaxed) & MAX_READERS == MAX_READERS {
+            AbortPanic::abort("Attempted to immutably borrow cell too many times.");
+        }
+    }
+
+    /// Releases an upgradable borrow of the cell.
+    #[inline(always)]
+    fn release_upgradable(&self) {
+        let previous = self.counter.fetch_sub(UPGRADABLE, Ordering::Release);
+        self.unpark(previous);
+        #[cfg(feature = "waker")]
+        if previous & !PARKED == UPGRADABLE {
+            self.waker.wake();
+        }
+    }
+
+    /// Releases a reserved write to the cell.
+    #[inline(always)]
+    fn release_intent(&self) {
+        let previous = self.counter.fetch_sub(INTENT, Ordering::Release);
+        self.unpark(previous);
+        #[cfg(feature = "waker")]
+        if previous & !PARKED == INTENT {
+            self.waker.wake();
+        }
+    }
+
+    /// Attempts to atomically convert a reserved write to the cell into a mutable borrow,
+    /// failing if any immutable borrows exist.
+    #[inline(always)]
+    fn try_acquire_reserved(&self) -> bool {
+        transition(&self.counter, INTENT, WRITER, Ordering::Acquire).is_ok()
+    }
+
+    /// Attempts to atomically convert an upgradable borrow of the cell into a mutable borrow,
+    /// failing if any immutable borrows exist.
+    #[inline(always)]
+    fn try_upgrade(&self) -> bool {
+        transition(&self.counter, UPGRADABLE, WRITER, Ordering::Acquire).is_ok()
+    }
+
+    /// Atomically converts an upgradable borrow of the cell into an immutable borrow. Aborts if
+    /// the cell has too many immutable borrows.
+    #[inline(always)]
+    fn downgrade_upgradable(&self) {
+        let previous = self.counter.fetch_sub(UPGRADABLE - 1, Ordering::Relaxed);
+        if previous & MAX_READERS == MAX_READERS {
+            AbortPanic::abort("Attempted to immutably borrow cell too many times.");
+        }
+        self.unpark(previous);
+    }
+
+    /// Splits a mutable borrow of the cell in two, so that it must be released an additional
+    /// time. Aborts if the borrow has been split too many times.
+    #[inline(always)]
+    fn split_write(&self) {
+        if self.counter.fetch_sub(1, Ordering::Relaxed) & !PARKED == MIN_WRITER {
+            AbortPanic::abort("Attempted to split mutable borrow of cell too many times.");
+        }
+    }
+
+    /// Wakes the threads parked on the cell if the parked bit was set in `previous`, the counter
+    /// value that a release replaced. Without the `std` feature, threads never park.
+    #[inline(always)]
+    fn unpark(&self, previous: u16) {
+        #[cfg(feature = "std")]
+        if previous & PARKED != 0 {
+            ParkingBucket::unpark(&self.counter);
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = previous;
+    }
+
+    /// Releases an immutable borrow of the cell, then parks the current thread until a mutable
+    /// borrow of the cell is released. The parked bit is set before the borrow is released, so
+    /// a writer that acquires the cell afterward always wakes the thread. Threads that were
+    /// waiting for this borrow are woken directly, and the bit is left set for this thread. The
+    /// thread may also be woken spuriously.
+    #[cfg(feature = "std")]
+    fn release_read_and_park(&self) {
+        let bucket = ParkingBucket::of(&self.counter);
+        let lock = bucket.lock();
+        if !is_frozen(self.counter.fetch_or(PARKED, Ordering::SeqCst)) {
+            let previous = self.counter.fetch_sub(1, Ordering::AcqRel);
+            let readers = previous & MAX_READERS;
+            if readers == 1 || readers == MAX_READERS {
+                bucket.condvar.notify_all();
+            }
+            #[cfg(feature = "waker")]
+            if previous & !PARKED == 1 {
+                self.waker.wake();
+            }
+        }
+
+        drop(bucket.condvar.wait(lock));
+    }
+}
+
+/// A set of threads that are parked until a value is available, for types whose state is not
+/// a borrow counter.
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+struct WaitQueue {
+    /// The number of parked threads.
+    waiters: AtomicUsize,
+    /// The lock that parked threads hold while checking the cell.
+    lock: std::sync::Mutex<()>,
+    /// Wakes parked threads.
+    condvar: std::sync::Condvar,
+}
+
+#[cfg(feature = "std")]
+impl WaitQueue {
+    /// Creates a new, empty queue.
+    #[inline(always)]
+    const fn new() -> Self {
+        Self {
+            waiters: AtomicUsize::new(0),
+            lock: std::sync::Mutex::new(()),
+            condvar: std::sync::Condvar::new(),
+        }
+    }
+
+    /// Invokes `f` until it succeeds, parking the current thread between attempts until the
+    /// queue is notified. The thread is registered and `f` is invoked again while holding the
+    /// lock, so a notification cannot be missed between the attempt and parking.
+    fn park_until<R>(&self, f: impl FnMut() -> Option<R>) -> R {
+        match self.park_until_deadline(None, f) {
+            Some(result) => result,
+            None => unreachable!(),
+        }
+    }
+
+    /// Invokes `f` until it succeeds or the deadline passes, parking the current thread between
+    /// attempts. `f` is invoked one final time once the deadline has passed. A thread that times
+    /// out is unregistered before returning, and every parked thread is woken by a notification,
+    /// so timing out never causes a notification to be missed by another thread.
+    fn park_until_deadline<R>(&self, deadline: Option<std::time::Instant>, mut f: impl FnMut() -> Option<R>) -> Option<R> {
+        if let Some(result) = f() {
+            return Some(result);
+        }
+
+        let mut lock = self.lock.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let _registration = WaiterRegistration::new(&self.waiters);
+        loop {
+            fence(Ordering::SeqCst);
+            if let Some(result) = f() {
+                return Some(result);
+            }
+
+            lock = match deadline {
+                Some(deadline) => {
+                    let now = std::time::Instant::now();
+                    if now >= deadline {
+                        return None;
+                    }
+
+                    match self.condvar.wait_timeout(lock, deadline - now) {
+                        Ok((lock, _)) => lock,
+                        Err(error) => error.into_inner().0,
+                    }
+                },
+                None => self.condvar.wait(lock).unwrap_or_else(std::sync::PoisonError::into_inner),
+            };
+        }
+    }
+
+    /// Wakes all threads parked on this queue.
+    #[inline(always)]
+    fn notify(&self) {
+        fence(Ordering::SeqCst);
+        if self.waiters.load(Ordering::SeqCst) != 0 {
+            let _lock = self.lock.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+            self.condvar.notify_all();
+        }
+    }
+}
+
+/// The number of buckets in the [`PARKING_TABLE`].
+#[cfg(feature = "std")]
+const PARKING_BUCKETS: usize = 64;
+
+/// The buckets in which threads park while waiting on a borrow counter. Each counter is assigned
+/// a bucket by hashing its address, so that cells need no storage of their own for waiting
+/// threads. Threads that wait on different counters may share a bucket, in which case they are
+/// woken together and park again.
+#[cfg(feature = "std")]
+static PARKING_TABLE: [ParkingBucket; PARKING_BUCKETS] = [const { ParkingBucket::new() }; PARKING_BUCKETS];
+
+/// A set of threads in the [`PARKING_TABLE`] that are waiting on counters with the same hash.
+/// Waiting threads set the [`PARKED`] bit of their counter, and a release that replaces a
+/// counter value with the bit set wakes the bucket.
+#[cfg(feature = "std")]
+struct ParkingBucket {
+    /// The lock that parked threads hold while checking their counter.
+    lock: std::sync::Mutex<()>,
+    /// Wakes parked threads.
+    condvar: std::sync::Condvar,
+}
+
+#[cfg(feature = "std")]
+impl ParkingBucket {
+    /// Creates a new, empty bucket.
+    #[inline(always)]
+    const fn new() -> Self {
+        Self {
+            lock: std::sync::Mutex::new(()),
+            condvar: std::sync::Condvar::new(),
+        }
+    }
+
+    /// Gets the bucket that is assigned to a counter.
+    #[inline(always)]
+    fn of(counter: &AtomicU16) -> &'static Self {
+        let hash = (counter as *const AtomicU16 as usize as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        &PARKING_TABLE[(hash >> (u64::BITS - PARKING_BUCKETS.trailing_zeros())) as usize]
+    }
+
+    /// Locks this bucket, ignoring poisoning, since the lock protects no data.
+    #[inline(always)]
+    fn lock(&self) -> std::sync::MutexGuard<'_, ()> {
+        self.lock.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Invokes `f` until it succeeds, parking the current thread between attempts until the
+    /// counter is released.
+    fn park_until<R>(counter: &AtomicU16, f: impl FnMut() -> Option<R>) -> R {
+        match Self::park_until_deadline(counter, None, f) {
+            Some(result) => result,
+            None => unreachable!(),
+        }
+    }
+
+    /// Invokes `f` until it succeeds or the deadline passes, parking the current thread between
+    /// attempts. `f` is invoked one final time once the deadline has passed. Before each attempt,
+    /// the thread sets the parked bit of the counter while holding the bucket's lock, so a release
+    /// either happens before the attempt or sees the bit and wakes the bucket once the thread has
+    /// parked. `f` must not release any borrows when it fails, since it runs under the lock. A
+    /// thread that times out may leave the bit set, which only causes a needless wakeup later.
+    fn park_until_deadline<R>(counter: &AtomicU16, deadline: Option<std::time::Instant>, mut f: impl FnMut() -> Option<R>) -> Option<R> {
+        if let Some(result) = f() {
+            return Some(result);
+        }
+
+        let bucket = Self::of(counter);
+        let mut lock = bucket.lock();
+        loop {
+            counter.fetch_or(PARKED, Ordering::SeqCst);
+            if let Some(result) = f() {
+                return Some(result);
+            }
+
+            lock = match deadline {
+                Some(deadline) => {
+                    let now = std::time::Instant::now();
+                    if now >= deadline {
+                        return None;
+                    }
+
+                    match bucket.condvar.wait_timeout(lock, deadline - now) {
+                        Ok((lock, _)) => lock,
+                        Err(error) => error.into_inner().0,
+                    }
+                },
+                None => bucket.condvar.wait(lock).unwrap_or_else(std::sync::PoisonError::into_inner),
+            };
+        }
+    }
+
+    /// Clears the parked bit of a counter, and wakes every thread in its bucket if the bit was
+    /// still set. If another release cleared the bit first, that release wakes the bucket instead.
+    #[cold]
+    fn unpark(counter: &AtomicU16) {
+        if counter.fetch_and(!PARKED, Ordering::SeqCst) & PARKED != 0 {
+            let bucket = Self::of(counter);
+            let _lock = bucket.lock();
+            bucket.condvar.notify_all();
+        }
+    }
+}
+
+/// Registers a thread as waiting on a cell for as long as it exists.
+#[cfg(feature = "std")]
+struct WaiterRegistration<'a>(&'a AtomicUsize);
+
+#[cfg(feature = "std")]
+impl<'a> WaiterRegistration<'a> {
+    /// Registers a new waiter with the given counter.
+    #[inline(always)]
+    fn new(waiters: &'a AtomicUsize) -> Self {
+        waiters.fetch_add(1, Ordering::SeqCst);
+        Self(waiters)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for WaiterRegistration<'_> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// The state of a waker slot that is neither being registered nor woken.
+#[cfg(feature = "waker")]
+const WAKER_IDLE: u8 = 0;
+/// The state bit that is set while a waker slot is being registered.
+#[cfg(feature = "waker")]
+const WAKER_REGISTERING: u8 = 1;
+/// The state bit that is set while a waker slot is being woken.
+#[cfg(feature = "waker")]
+const WAKER_WAKING: u8 = 2;
+
+/// A slot that holds the waker of a single task waiting on a cell. Registering a waker replaces
+/// the previous one, so only the most recently registered task is woken.
+#[cfg(feature = "waker")]
+#[derive(Default)]
+struct WakerSlot {
+    /// Whether the waker is being registered or woken.
+    state: AtomicU8,
+    /// The registered waker, which is accessed only by the thread that set a state bit.
+    waker: UnsafeCell<Option<core::task::Waker>>,
+}
+
+#[cfg(feature = "waker")]
+impl WakerSlot {
+    /// Creates a new, empty slot.
+    #[inline(always)]
+    const fn new() -> Self {
+        Self {
+            state: AtomicU8::new(WAKER_IDLE),
+            waker: UnsafeCell::new(None),
+        }
+    }
+
+    /// Stores the given waker in the slot. If the slot is woken while the waker is being
+    /// stored, the waker is woken immediately instead.
+    #[inline(always)]
+    fn register(&self, waker: &core::task::Waker) {
+        match self.state.compare_exchange(WAKER_IDLE, WAKER_REGISTERING, Ordering::Acquire, Ordering::Acquire) {
+            Ok(_) => {
+                unsafe {
+                    match &mut *self.waker.get() {
+                        Some(old) if old.will_wake(waker) => {},
+                        slot => *slot = Some(waker.clone()),
+                    }
+                }
+
+                if self.state.compare_exchange(WAKER_REGISTERING, WAKER_IDLE, Ordering::AcqRel, Ordering::Acquire).is_err() {
+                    let waker = unsafe { (*self.waker.get()).take() };
+                    self.state.store(WAKER_IDLE, Ordering::Release);
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                }
+            },
+            Err(WAKER_WAKING) => waker.wake_by_ref(),
+            Err(_) => {},
+        }
+    }
+
+    /// Takes the waker out of the slot and wakes it. If a waker is being registered at the
+    /// same time, the registering thread wakes it instead.
+    #[inline(always)]
+    fn wake(&self) {
+        if self.state.fetch_or(WAKER_WAKING, Ordering::AcqRel) == WAKER_IDLE {
+            let waker = unsafe { (*self.waker.get()).take() };
+            self.state.fetch_and(!WAKER_WAKING, Ordering::Release);
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+}
+
+#[cfg(feature = "waker")]
+unsafe impl Sync for WakerSlot {}
+
+#[cfg(feature = "waker")]
+impl fmt::Debug for WakerSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WakerSlot").finish_non_exhaustive()
+    }
+}
+
+/// A resource guard that dynamically controls the lifetime of a mutable read-write cell borrow.
+///
+/// Guards are [`Send`] and [`Sync`] under the same conditions as the references they hold. An
+/// immutable guard requires `T: Sync`, and a mutable guard requires `T: Send` to be sent or
+/// `T: Sync` to be shared. The cell's state is only changed through atomic operations when a
+/// guard is dropped, so releasing a borrow on a different thread than the one that acquired it
+/// is sound.
+///
+/// An immutable guard for a [`Cell`] cannot be sent, because `&Cell<_>` is not [`Send`]:
+///
+/// ```compile_fail
+/// # use core::cell::Cell;
+/// # use sync_rw_cell::*;
+/// fn assert_send<T: Send>(_: T) {}
+///
+/// let cell = RwCell::new(Cell::new(0));
+/// assert_send(cell.borrow());
+/// ```
+///
+/// A mutable guard for a [`Cell`] can be sent, but not shared:
+///
+/// ```compile_fail
+/// # use core::cell::Cell;
+/// # use sync_rw_cell::*;
+/// fn assert_sync<T: Sync>(_: T) {}
+///
+/// let cell = RwCell::new(Cell::new(0));
+/// assert_sync(cell.borrow_mut());
+/// ```
+///
+/// No guard for an `Rc` can be sent or shared:
+///
+/// ```compile_fail
+/// # use std::rc::Rc;
+/// # use sync_rw_cell::*;
+/// fn assert_sync<T: Sync>(_: T) {}
+///
+/// let cell = RwCell::new(Rc::new(0));
+/// assert_sync(cell.borrow());
+/// ```
+///
+/// ```compile_fail
+/// # use std::rc::Rc;
+/// # use sync_rw_cell::*;
+/// fn assert_send<T: Send>(_: T) {}
+///
+/// let cell = RwCell::new(Rc::new(0));
+/// assert_send(cell.borrow_mut());
+/// ```
+///
+/// The same guards compile when both bounds are met:
+///
+/// ```
+/// # use core::cell::Cell;
+/// # use sync_rw_cell::*;
+/// fn assert_send<T: Send>(_: T) {}
+/// fn assert_sync<T: Sync>(_: T) {}
+///
+/// let cell = RwCell::new(Cell::new(0));
+/// assert_send(cell.borrow_mut());
+///
+/// let cell = RwCell::new(0);
+/// assert_send(cell.borrow());
+/// assert_sync(cell.borrow());
+/// assert_sync(cell.borrow_mut());
+/// ```
+pub struct RwCellGuard<'a, M: Mutability, T: 'a + ?Sized> {
+    /// The value currently being borrowed.
+    value: NonNull<T>,
+    /// The borrow state of the cell.
+    state: StateRef<'a>,
+    /// Marks the lifetime and mutability of the borrow.
+    marker: PhantomData<M::Ref<'a, T>>,
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized> RwCellGuard<'a, M, T> {
+    /// Removes the lifetime from a guard, allowing it to be freely used in
+    /// other parts of the program.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, the underlying cell must not be moved or
+    /// destroyed while this guard exists. Cells that live for the rest of the program can
+    /// be borrowed safely with [`RwCell::borrow_static`] and [`RwCell::borrow_mut_static`]
+    /// instead, and shared cells with `ArcRwCell` if the `alloc` feature is enabled.
+    #[inline(always)]
+    pub unsafe fn detach(self) -> RwCellGuard<'static, M, T> {
+        transmute(self)
+    }
+
+    /// Decomposes a guard into a pointer to its value and the state of its borrow, without
+    /// releasing the borrow. The guard can be reassembled with [`RwCellGuard::from_raw`].
+    #[inline(always)]
+    pub fn into_raw(orig: Self) -> (NonNull<T>, RawGuardState) {
+        let (value, state) = orig.into_parts();
+        (value, RawGuardState(state.pointer))
+    }
+
+    /// Reassembles a guard from the parts returned by [`RwCellGuard::into_raw`].
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, `value` and `state` must have been returned by a single
+    /// call to [`RwCellGuard::into_raw`] on a guard with the same mutability and type, they must
+    /// be reassembled only once, and the cell must not be moved or destroyed while the
+    /// returned guard exists.
+    #[inline(always)]
+    pub unsafe fn from_raw(value: NonNull<T>, state: RawGuardState) -> RwCellGuard<'static, M, T> {
+        RwCellGuard::from_parts(value, StateRef {
+            pointer: state.0,
+            marker: PhantomData,
+        })
+    }
+
+    /// Creates a guard from its raw parts.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, the caller must own a borrow of the cell with the
+    /// given state, and `value` must be valid for that borrow.
+    #[inline(always)]
+    unsafe fn from_parts(value: NonNull<T>, state: StateRef<'a>) -> Self {
+        Self {
+            value,
+            state,
+            marker: PhantomData,
+        }
+    }
+
+    /// Decomposes a guard into its raw parts without releasing the borrow.
+    #[inline(always)]
+    fn into_parts(self) -> (NonNull<T>, StateRef<'a>) {
+        let this = ManuallyDrop::new(self);
+        (this.value, this.state)
+    }
+}
+
+impl<'a, T: 'a + ?Sized> RwCellGuard<'a, Const, T> {
+    /// Creates a reference to a specific portion of a value.
+    #[inline(always)]
+    pub fn map<U, F>(orig: Self, f: F) -> RwCellGuard<'a, Const, U>
+    where
+        F: FnOnce(&T) -> &U,
+        U: ?Sized,
+    {
+        unsafe {
+            let result = RwCellGuard::from_parts(NonNull::from(f(orig.value.as_ref())), orig.state.projected());
+            forget(orig);
+            result
+        }
+    }
+
+    /// Creates a reference to a specific portion of a value, along with an additional value
+    /// computed by `f`.
+    #[inline(always)]
+    pub fn map_with<U, R, F>(orig: Self, f: F) -> (RwCellGuard<'a, Const, U>, R)
+    where
+        F: FnOnce(&T) -> (&U, R),
+        U: ?Sized,
+    {
+        unsafe {
+            let (value, extra) = f(orig.value.as_ref());
+            let value = NonNull::from(value);
+            let (_, state) = orig.into_parts();
+            (RwCellGuard::from_parts(value, state.projected()), extra)
+        }
+    }
+
+    /// Creates another guard for the same value. This adds an immutable borrow to the cell, so
+    /// it aborts under the same conditions as [`RwCell::borrow`] if the cell has too many
+    /// immutable borrows. With the `stable_deref` feature, guards also implement [`Clone`] in
+    /// terms of this function, so that they can be used as `CloneStableDeref` owners. In that
+    /// case, `guard.clone()` clones the guard, and `T::clone(&guard)` clones the value.
+    #[allow(clippy::should_implement_trait)]
+    #[inline(always)]
+    pub fn clone(orig: &Self) -> Self {
+        orig.state.get().share_read();
+        unsafe { Self::from_parts(orig.value, orig.state) }
+    }
+
+    /// Converts a guard into a reference for the remaining lifetime of the cell, leaving the
+    /// cell immutably borrowed forever. Calling [`RwCell::force_unlock_read`] to release the
+    /// borrow is only sound once the returned reference is no longer used.
+    #[inline(always)]
+    pub fn leak(orig: Self) -> &'a T {
+        let (value, _) = orig.into_parts();
+        unsafe { value.as_ref() }
+    }
+
+    /// Splits a guard into two guards for portions of a value. This adds an immutable borrow
+    /// to the cell, so it aborts under the same conditions as [`RwCell::borrow`] if the cell
+    /// has too many immutable borrows.
+    #[inline(always)]
+    pub fn map_split<U, V, F>(orig: Self, f: F) -> (RwCellGuard<'a, Const, U>, RwCellGuard<'a, Const, V>)
+    where
+        F: FnOnce(&T) -> (&U, &V),
+        U: ?Sized,
+        V: ?Sized,
+    {
+        unsafe {
+            let (first, second) = f(orig.value.as_ref());
+            let (first, second) = (NonNull::from(first), NonNull::from(second));
+            let (_, state) = orig.into_parts();
+            state.get().share_read();
+            (RwCellGuard::from_parts(first, state.projected()), RwCellGuard::from_parts(second, state.projected()))
+        }
+    }
+
+    /// Creates a reference to an optional portion of a value. If the portion is absent,
+    /// the original guard is returned.
+    #[inline(always)]
+    pub fn filter_map<U, F>(orig: Self, f: F) -> Result<RwCellGuard<'a, Const, U>, Self>
+    where
+        F: FnOnce(&T) -> Option<&U>,
+        U: ?Sized,
+    {
+        unsafe {
+            match f(orig.value.as_ref()) {
+                Some(value) => {
+                    let value = NonNull::from(value);
+                    let (_, state) = orig.into_parts();
+                    Ok(RwCellGuard::from_parts(value, state.projected()))
+                }
+                None => Err(orig),
+            }
+        }
+    }
+
+    /// Converts a guard into an iterator that owns the borrow. Each call to `next` passes the
+    /// value and the current index to `f`, and yields a guard for the returned portion until
+    /// `f` returns `None`. Every yielded guard adds an immutable borrow to the cell, so this
+    /// aborts under the same conditions as [`RwCell::borrow`] if too many of them exist.
+    #[inline(always)]
+    pub fn into_iter_mapped<U, F>(orig: Self, f: F) -> RwCellGuardIter<'a, T, U, F>
+    where
+        F: FnMut(&T, usize) -> Option<&U>,
+        U: ?Sized,
+    {
+        RwCellGuardIter {
+            guard: orig,
+            index: 0,
+            project: f,
+            marker: PhantomData,
+        }
+    }
+
+    /// Temporarily releases the borrow while `f` runs, then spins until the cell can be
+    /// immutably borrowed again. Aborts if the guard was created by mapping another guard,
+    /// since the mapped value could be invalidated while the cell is unlocked.
+    #[inline(always)]
+    pub fn unlocked<R>(orig: &mut Self, f: impl FnOnce() -> R) -> R {
+        if orig.state.is_projected() {
+            AbortPanic::abort("Attempted to unlock mapped borrow of cell.");
+        }
+
+        let state = orig.state.get();
+        state.release::<Const>();
+        let _reacquire = BorrowReacquire::<Const>::new(state);
+        f()
+    }
+
+    /// Releases the borrow and borrows the cell again, giving other threads that are waiting
+    /// to borrow the cell a chance to do so. Spins until the cell can be immutably borrowed again.
+    /// Aborts if the guard was created by mapping another guard.
+    #[inline(always)]
+    pub fn bump(orig: &mut Self) {
+        Self::unlocked(orig, || ());
+    }
+
+    /// Attempts to convert an immutable guard into a mutable guard without releasing the borrow.
+    /// This fails and returns the original guard if other immutable borrows of the cell exist,
+    /// or if the guard was created by mapping another guard.
+    #[inline(always)]
+    pub fn try_upgrade(orig: Self) -> Result<RwCellGuard<'a, Mut, T>, Self> {
+        if !orig.state.is_projected() && transition(&orig.state.get().counter, 1, WRITER, Ordering::Acquire).is_ok() {
+            let (value, state) = orig.into_parts();
+            unsafe { Ok(RwCellGuard::from_parts(value, state)) }
+        }
+        else {
+            Err(orig)
+        }
+    }
+
+    /// Immutably borrows a cell contained in the value, and returns a guard that holds both
+    /// borrows. The inner borrow is released before the outer one.
+    #[inline(always)]
+    pub fn and_borrow<U, F>(orig: Self, f: F) -> NestedGuard<'a, Const, U>
+    where
+        F: FnOnce(&T) -> &RwCell<U>,
+    {
+        let (value, state) = orig.into_parts();
+        let outer = BorrowRelease::<Const>::new(state.get());
+        let inner = f(unsafe { value.as_ref() }).borrow();
+        NestedGuard { inner, _outer: outer }
+    }
+
+    /// Mutably borrows a cell contained in the value, and returns a guard that holds both
+    /// borrows. The inner borrow is released before the outer one.
+    #[inline(always)]
+    pub fn and_borrow_mut<U, F>(orig: Self, f: F) -> NestedGuard<'a, Mut, U>
+    where
+        F: FnOnce(&T) -> &RwCell<U>,
+    {
+        let (value, state) = orig.into_parts();
+        let outer = BorrowRelease::<Const>::new(state.get());
+        let inner = f(unsafe { value.as_ref() }).borrow_mut();
+        NestedGuard { inner, _outer: outer }
+    }
+
+    /// Attempts to immutably borrow a cell contained in the value, and returns a guard that
+    /// holds both borrows. If the inner borrow fails, the outer borrow is released as well.
+    #[inline(always)]
+    pub fn try_and_borrow<U, F>(orig: Self, f: F) -> Result<NestedGuard<'a, Const, U>, BorrowError>
+    where
+        F: FnOnce(&T) -> &RwCell<U>,
+    {
+        let (value, state) = orig.into_parts();
+        let outer = BorrowRelease::<Const>::new(state.get());
+        let inner = f(unsafe { value.as_ref() }).try_borrow()?;
+        Ok(NestedGuard { inner, _outer: outer })
+    }
+
+    /// Attempts to mutably borrow a cell contained in the value, and returns a guard that
+    /// holds both borrows. If the inner borrow fails, the outer borrow is released as well.
+    #[inline(always)]
+    pub fn try_and_borrow_mut<U, F>(orig: Self, f: F) -> Result<NestedGuard<'a, Mut, U>, BorrowMutError>
+    where
+        F: FnOnce(&T) -> &RwCell<U>,
+    {
+        let (value, state) = orig.into_parts();
+        let outer = BorrowRelease::<Const>::new(state.get());
+        let inner = f(unsafe { value.as_ref() }).try_borrow_mut()?;
+        Ok(NestedGuard { inner, _outer: outer })
+    }
+
+    /// Clones the borrowed value and releases the borrow.
+    #[inline(always)]
+    pub fn to_owned_mut(orig: Self) -> T
+    where
+        T: Clone,
+    {
+        (*orig).clone()
+    }
+
+    /// Attempts to convert an immutable guard into a mutable guard, under the same conditions
+    /// as [`RwCellGuard::try_upgrade`]. On failure, the original guard is returned along with a
+    /// clone of its value.
+    #[inline(always)]
+    pub fn try_into_mut(orig: Self) -> Result<RwCellGuard<'a, Mut, T>, (Self, T)>
+    where
+        T: Clone,
+    {
+        Self::try_upgrade(orig).map_err(|orig| {
+            let value = (*orig).clone();
+            (orig, value)
+        })
+    }
+}
+
+impl<'a, T: 'a + ?Sized> RwCellGuard<'a, Mut, T> {
+    /// Creates a reference to a specific portion of a value. The borrow is released if `f` unwinds.
+    #[inline(always)]
+    pub fn map<U, F>(orig: Self, f: F) -> RwCellGuard<'a, Mut, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+        U: ?Sized,
+    {
+        let (mut value, state) = orig.into_parts();
+        let release = BorrowRelease::<Mut>::new(state.get());
+        let result = NonNull::from(f(unsafe { value.as_mut() }));
+        forget(release);
+        unsafe { RwCellGuard::from_parts(result, state.projected()) }
+    }
+
+    /// Creates a reference to a specific portion of a value, along with an additional value
+    /// computed by `f`. The borrow is released if `f` unwinds.
+    #[inline(always)]
+    pub fn map_with<U, R, F>(orig: Self, f: F) -> (RwCellGuard<'a, Mut, U>, R)
+    where
+        F: FnOnce(&mut T) -> (&mut U, R),
+        U: ?Sized,
+    {
+        let (mut value, state) = orig.into_parts();
+        let release = BorrowRelease::<Mut>::new(state.get());
+        let (result, extra) = f(unsafe { value.as_mut() });
+        let result = NonNull::from(result);
+        forget(release);
+        unsafe { (RwCellGuard::from_parts(result, state.projected()), extra) }
+    }
+
+    /// Converts a guard into a reference for the remaining lifetime of the cell, leaving the
+    /// cell mutably borrowed forever. Calling [`RwCell::force_unlock_write`] to release the
+    /// borrow is only sound once the returned reference is no longer used.
+    #[inline(always)]
+    pub fn leak(orig: Self) -> &'a mut T {
+        let (mut value, _) = orig.into_parts();
+        unsafe { value.as_mut() }
+    }
+
+    /// Converts a guard into an iterator that owns the borrow, and yields a separate guard for
+    /// each element of the slice returned by `f`. The cell remains mutably borrowed until the
+    /// iterator and all yielded guards are dropped. Aborts if too many yielded guards exist
+    /// at once.
+    #[inline(always)]
+    pub fn into_iter_mapped<U, F>(orig: Self, f: F) -> RwCellGuardIterMut<'a, U>
+    where
+        F: FnOnce(&mut T) -> &mut [U],
+    {
+        RwCellGuardIterMut {
+            guard: RwCellGuard::<Mut, _>::map(orig, f),
+            index: 0,
+        }
+    }
+
+    /// Temporarily releases the borrow while `f` runs, then spins until the cell can be
+    /// mutably borrowed again. Aborts if the guard was created by mapping or splitting another
+    /// guard, since the mapped value could be invalidated while the cell is unlocked.
+    #[inline(always)]
+    pub fn unlocked<R>(orig: &mut Self, f: impl FnOnce() -> R) -> R {
+        if orig.state.is_projected() {
+            AbortPanic::abort("Attempted to unlock mapped borrow of cell.");
+        }
+
+        let state = orig.state.get();
+        state.release::<Mut>();
+        let _reacquire = BorrowReacquire::<Mut>::new(state);
+        f()
+    }
+
+    /// Releases the borrow and borrows the cell again, giving other threads that are waiting
+    /// to borrow the cell a chance to do so. Spins until the cell can be mutably borrowed again.
+    /// Aborts if the guard was created by mapping another guard.
+    #[inline(always)]
+    pub fn bump(orig: &mut Self) {
+        Self::unlocked(orig, || ());
+    }
+
+    /// Converts a mutable guard into an immutable guard without releasing the borrow, so no
+    /// other mutable borrow can occur in between. Aborts if the guard was produced by splitting
+    /// another guard, and the other parts still exist.
+    #[inline(always)]
+    pub fn downgrade(orig: Self) -> RwCellGuard<'a, Const, T> {
+        let (value, state) = orig.into_parts();
+        state.get().downgrade_write();
+        unsafe { RwCellGuard::from_parts(value, state) }
+    }
+
+    /// Splits a guard into two guards for different portions of a value. The cell remains
+    /// mutably borrowed until both guards are dropped. The borrow is released if `f` unwinds.
+    #[inline(always)]
+    pub fn map_split<U, V, F>(orig: Self, f: F) -> (RwCellGuard<'a, Mut, U>, RwCellGuard<'a, Mut, V>)
+    where
+        F: FnOnce(&mut T) -> (&mut U, &mut V),
+        U: ?Sized,
+        V: ?Sized,
+    {
+        let (mut value, state) = orig.into_parts();
+        let release = BorrowRelease::<Mut>::new(state.get());
+        let (first, second) = f(unsafe { value.as_mut() });
+        let (first, second) = (NonNull::from(first), NonNull::from(second));
+        forget(release);
+        state.get().split_write();
+        unsafe { (RwCellGuard::from_parts(first, state.projected()), RwCellGuard::from_parts(second, state.projected())) }
+    }
+
+    /// Creates a reference to an optional portion of a value. If the portion is absent,
+    /// the original guard is returned. The borrow is released if `f` unwinds.
+    #[inline(always)]
+    pub fn filter_map<U, F>(orig: Self, f: F) -> Result<RwCellGuard<'a, Mut, U>, Self>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+        U: ?Sized,
+    {
+        let (mut value, state) = orig.into_parts();
+        let release = BorrowRelease::<Mut>::new(state.get());
+        let result = f(unsafe { value.as_mut() }).map(NonNull::from);
+        forget(release);
+
+        unsafe {
+            match result {
+                Some(result) => Ok(RwCellGuard::from_parts(result, state.projected())),
+                None => Err(RwCellGuard::from_parts(value, state)),
+            }
+        }
+    }
+}
+
+impl<'a, T: 'a> RwCellGuard<'a, Mut, [T]> {
+    /// Divides a guard for a slice into two guards at an index. The cell remains mutably
+    /// borrowed until both guards are dropped. Panics if `mid > len`, releasing the borrow.
+    #[inline(always)]
+    pub fn split_at_mut(orig: Self, mid: usize) -> (Self, Self) {
+        Self::map_split(orig, |value| value.split_at_mut(mid))
+    }
+
+    /// Converts a guard for a slice into an iterator over guards for consecutive chunks of
+    /// `chunk_size` elements. The last chunk is shorter if `chunk_size` does not divide the
+    /// length. The cell remains mutably borrowed until the iterator and all yielded guards are
+    /// dropped. Panics if `chunk_size` is zero, releasing the borrow.
+    #[inline(always)]
+    pub fn chunks_mut(orig: Self, chunk_size: usize) -> RwCellGuardChunksMut<'a, T> {
+        assert!(chunk_size != 0, "Chunk size must be non-zero.");
+        RwCellGuardChunksMut {
+            guard: orig,
+            chunk_size,
+            offset: 0,
+        }
+    }
+
+    /// Splits a guard for a slice into guards for the elements at several indices. If any index
+    /// is out of bounds or repeated, the original guard is returned. The cell remains mutably
+    /// borrowed until all of the guards are dropped. Aborts if too many guards would exist at once.
+    #[inline(always)]
+    pub fn get_disjoint_mut<const N: usize>(orig: Self, indices: [usize; N]) -> Result<[RwCellGuard<'a, Mut, T>; N], Self> {
+        let len = orig.value.len();
+        for (i, &index) in indices.iter().enumerate() {
+            if index >= len || indices[..i].contains(&index) {
+                return Err(orig);
+            }
+        }
+
+        let (value, state) = orig.into_parts();
+        if N == 0 {
+            state.get().release_write();
+        }
+
+        for _ in 1..N {
+            state.get().split_write();
+        }
+
+        let base = value.as_ptr().cast::<T>();
+        Ok(core::array::from_fn(|i| unsafe { RwCellGuard::from_parts(NonNull::new_unchecked(base.add(indices[i])), state.projected()) }))
+    }
+}
+
+impl<'a> RwCellGuard<'a, Const, dyn Any> {
+    /// Attempts to downcast a type-erased guard to a concrete type. If the value is not of
+    /// type `U`, the original guard is returned.
+    #[inline(always)]
+    pub fn downcast_ref<U: Any>(orig: Self) -> Result<RwCellGuard<'a, Const, U>, Self> {
+        if orig.is::<U>() {
+            let (value, state) = orig.into_parts();
+            unsafe { Ok(RwCellGuard::from_parts(value.cast(), state)) }
+        }
+        else {
+            Err(orig)
+        }
+    }
+}
+
+impl<'a> RwCellGuard<'a, Mut, dyn Any> {
+    /// Attempts to downcast a type-erased guard to a concrete type. If the value is not of
+    /// type `U`, the original guard is returned.
+    #[inline(always)]
+    pub fn downcast_mut<U: Any>(orig: Self) -> Result<RwCellGuard<'a, Mut, U>, Self> {
+        if orig.is::<U>() {
+            let (value, state) = orig.into_parts();
+            unsafe { Ok(RwCellGuard::from_parts(value.cast(), state)) }
+        }
+        else {
+            Err(orig)
+        }
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized> Deref for RwCellGuard<'a, M, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<'a, T: 'a + ?Sized> DerefMut for RwCellGuard<'a, Mut, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized> Drop for RwCellGuard<'a, M, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.state.get().release::<M>();
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized + fmt::Debug> fmt::Debug for RwCellGuard<'a, M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized + fmt::Display> fmt::Display for RwCellGuard<'a, M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized + fmt::LowerHex> fmt::LowerHex for RwCellGuard<'a, M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized + fmt::UpperHex> fmt::UpperHex for RwCellGuard<'a, M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized + fmt::Binary> fmt::Binary for RwCellGuard<'a, M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized + fmt::Octal> fmt::Octal for RwCellGuard<'a, M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<'a, 'b, M: Mutability, N: Mutability, T: 'a + 'b + ?Sized + PartialEq> PartialEq<RwCellGuard<'b, N, T>> for RwCellGuard<'a, M, T> {
+    #[inline(always)]
+    fn eq(&self, other: &RwCellGuard<'b, N, T>) -> bool {
+        **self == **other
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized + PartialEq> PartialEq<T> for RwCellGuard<'a, M, T> {
+    #[inline(always)]
+    fn eq(&self, other: &T) -> bool {
+        **self == *other
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized + Eq> Eq for RwCellGuard<'a, M, T> {}
+
+impl<'a, 'b, M: Mutability, N: Mutability, T: 'a + 'b + ?Sized + PartialOrd> PartialOrd<RwCellGuard<'b, N, T>> for RwCellGuard<'a, M, T> {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &RwCellGuard<'b, N, T>) -> Option<core::cmp::Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized + PartialOrd> PartialOrd<T> for RwCellGuard<'a, M, T> {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &T) -> Option<core::cmp::Ordering> {
+        (**self).partial_cmp(other)
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized + Ord> Ord for RwCellGuard<'a, M, T> {
+    #[inline(always)]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized + Hash> Hash for RwCellGuard<'a, M, T> {
+    #[inline(always)]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized> AsRef<T> for RwCellGuard<'a, M, T> {
+    #[inline(always)]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T: 'a + ?Sized> AsMut<T> for RwCellGuard<'a, Mut, T> {
+    #[inline(always)]
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'a, M: Mutability, T: 'a + ?Sized> core::borrow::Borrow<T> for RwCellGuard<'a, M, T> {
+    #[inline(always)]
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T: 'a + ?Sized> core::borrow::BorrowMut<T> for RwCellGuard<'a, Mut, T> {
+    #[inline(always)]
+    fn borrow_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T: 'a + ?Sized + std::io::Read> std::io::Read for RwCellGuard<'a, Mut, T> {
+    #[inline(always)]
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        (**self).read(buf)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T: 'a + ?Sized> std::io::Read for RwCellGuard<'a, Const, T>
+where
+    for<'b> &'b T: std::io::Read,
+{
+    #[inline(always)]
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        (&**self).read(buf)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T: 'a + ?Sized + std::io::Write> std::io::Write for RwCellGuard<'a, Mut, T> {
+    #[inline(always)]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        (**self).write(buf)
+    }
+
+    #[inline(always)]
+    fn flush(&mut self) -> std::io::Result<()> {
+        (**self).flush()
+    }
+}
 
-/// A lightweight reference-counted cell. Aborts the program when borrows conflict.
-#[derive(Debug, Default)]
-pub struct RwCell<T> {
-    /// The inner cell data.
-    inner: ReadCell<RwCellInner<T>>,
+#[cfg(feature = "std")]
+impl<'a, T: 'a + ?Sized + std::io::Seek> std::io::Seek for RwCellGuard<'a, Mut, T> {
+    #[inline(always)]
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        (**self).seek(pos)
+    }
 }
 
-impl<T> RwCell<T> {
-    /// Creates a new cell that wraps the provided value.
+#[cfg(feature = "std")]
+impl<'a, T: 'a + ?Sized + std::io::BufRead> std::io::BufRead for RwCellGuard<'a, Mut, T> {
     #[inline(always)]
-    pub const fn new(value: T) -> Self {
-        Self {
-            inner: ReadCell::new(RwCellInner {
-                borrow_state: AtomicU16::new(0),
-                value: UnsafeCell::new(value),
-            }),
-        }
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        (**self).fill_buf()
     }
 
-    /// Immutably borrows the value of this cell.
     #[inline(always)]
-    pub fn borrow(&self) -> RwCellGuard<Const, T> {
-        unsafe {
-            Self::abort_if(
-                self.inner.borrow_state.fetch_add(1, Ordering::AcqRel) >= u16::MAX - 1,
-                "Attempted to immutably borrow cell while it was mutably borrowed.",
-            );
-            RwCellGuard {
-                value: &*(self.inner.value.get() as *const T),
-                borrow_state: &self.inner.borrow_state,
-            }
-        }
+    fn consume(&mut self, amt: usize) {
+        (**self).consume(amt)
     }
+}
 
-    /// Mutably borrows the value of this cell.
+#[cfg(feature = "serde")]
+impl<'a, M: Mutability, T: 'a + ?Sized + serde::Serialize> serde::Serialize for RwCellGuard<'a, M, T> {
     #[inline(always)]
-    pub fn borrow_mut(&self) -> RwCellGuard<Mut, T> {
-        unsafe {
-            Self::abort_if(
-                self.inner.borrow_state.swap(u16::MAX, Ordering::AcqRel) != 0,
-                "Attempted to mutably borrow cell while other borrows already existed.",
-            );
-            RwCellGuard {
-                value: &mut *self.inner.value.get(),
-                borrow_state: &self.inner.borrow_state,
-            }
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "stable_deref")]
+unsafe impl<'a, M: Mutability, T: 'a + ?Sized> stable_deref_trait::StableDeref for RwCellGuard<'a, M, T> {}
+
+#[cfg(feature = "stable_deref")]
+impl<'a, T: 'a + ?Sized> Clone for RwCellGuard<'a, Const, T> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        RwCellGuard::<Const, T>::clone(self)
+    }
+}
+
+#[cfg(feature = "stable_deref")]
+unsafe impl<'a, T: 'a + ?Sized> stable_deref_trait::CloneStableDeref for RwCellGuard<'a, Const, T> {}
+
+unsafe impl<'a, T: 'a + ?Sized + Sync> Send for RwCellGuard<'a, Const, T> {}
+unsafe impl<'a, T: 'a + ?Sized + Sync> Sync for RwCellGuard<'a, Const, T> {}
+unsafe impl<'a, T: 'a + ?Sized + Send> Send for RwCellGuard<'a, Mut, T> {}
+unsafe impl<'a, T: 'a + ?Sized + Sync> Sync for RwCellGuard<'a, Mut, T> {}
+
+/// A reference to the borrow state of a cell. The lowest bit of the pointer records whether
+/// the guard holding this reference was created by mapping another guard, in which case
+/// the value may have come from a shared reference, and may not outlive changes to the rest
+/// of the cell.
+#[derive(Copy, Clone)]
+struct StateRef<'a> {
+    /// The tagged pointer to the state.
+    pointer: NonNull<RwCellState>,
+    /// Marks the lifetime of the state.
+    marker: PhantomData<&'a RwCellState>,
+}
+
+impl<'a> StateRef<'a> {
+    /// Creates a new, untagged reference to the given state.
+    #[inline(always)]
+    fn new(state: &'a RwCellState) -> Self {
+        Self {
+            pointer: NonNull::from(state),
+            marker: PhantomData,
         }
     }
 
-    /// Determines whether this cell is free to be borrowed.
+    /// Gets the referenced state.
     #[inline(always)]
-    pub fn free(&self) -> bool {
-        self.inner.borrow_state.load(Ordering::Acquire) == 0
+    fn get(self) -> &'a RwCellState {
+        unsafe { self.pointer.map_addr(|address| NonZeroUsize::new_unchecked(address.get() & !1)).as_ref() }
     }
 
-    /// Aborts the program if the given condition is true.
+    /// Tags this reference to indicate that the associated value was created by mapping.
     #[inline(always)]
-    fn abort_if(condition: bool, reason: &str) {
-        if condition {
-            AbortPanic::abort(reason);
+    fn projected(self) -> Self {
+        Self {
+            pointer: self.pointer.map_addr(|address| address | 1),
+            marker: PhantomData,
         }
     }
-}
 
-/// Stores the inner data for a read-write cell.
-#[derive(Debug, Default)]
-struct RwCellInner<T> {
-    /// The value contained in the cell.
-    value: UnsafeCell<T>,
-    /// The borrow counter.
-    borrow_state: AtomicU16,
+    /// Determines whether the associated value was created by mapping.
+    #[inline(always)]
+    fn is_projected(self) -> bool {
+        self.pointer.addr().get() & 1 != 0
+    }
 }
 
-/// A resource guard that dynamically controls the lifetime of a mutable read-write cell borrow.
+unsafe impl Send for StateRef<'_> {}
+unsafe impl Sync for StateRef<'_> {}
+
+/// The borrow state of a guard that has been decomposed by [`RwCellGuard::into_raw`].
 #[derive(Debug)]
-pub struct RwCellGuard<'a, M: Mutability, T: 'a + ?Sized> {
-    /// The value currently being borrowed.
-    value: M::Ref<'a, T>,
-    /// The borrow counter.
-    borrow_state: &'a AtomicU16,
-}
+pub struct RawGuardState(NonNull<RwCellState>);
 
-impl<'a, M: Mutability, T: 'a + ?Sized> RwCellGuard<'a, M, T> {
-    /// Removes the lifetime from a guard, allowing it to be freely used in
-    /// other parts of the program.
+impl RawGuardState {
+    /// Converts the state into an opaque pointer.
+    #[inline(always)]
+    pub fn into_ptr(self) -> *mut () {
+        self.0.as_ptr().cast()
+    }
+
+    /// Recreates a state from an opaque pointer.
     ///
     /// # Safety
     ///
-    /// For this function to be sound, the underlying cell must not be moved or
-    /// destroyed while this guard exists.
+    /// For this function to be sound, `ptr` must have been returned by [`RawGuardState::into_ptr`].
     #[inline(always)]
-    pub unsafe fn detach(self) -> RwCellGuard<'static, M, T> {
-        transmute(self)
+    pub unsafe fn from_ptr(ptr: *mut ()) -> Self {
+        Self(NonNull::new_unchecked(ptr.cast()))
     }
 }
 
-impl<'a, T: 'a + ?Sized> RwCellGuard<'a, Const, T> {
-    /// Creates a reference to a specific portion of a value.
+/// Holds a cell mutably borrowed while its value has been moved out by [`RwCell::checkout`].
+#[derive(Debug)]
+pub struct WriteReservation<'a, T> {
+    /// The inner data of the cell whose value was moved out.
+    inner: &'a RwCellInner<T>,
+    /// Creates a value to store in the cell if the reservation is dropped.
+    restore: Option<fn() -> T>,
+}
+
+impl<'a, T> WriteReservation<'a, T> {
+    /// Stores a new value in the cell and releases its mutable borrow.
     #[inline(always)]
-    pub fn map<U, F>(orig: Self, f: F) -> RwCellGuard<'a, Const, U>
-    where
-        F: FnOnce(&T) -> &U,
-        U: ?Sized,
-    {
-        let result = RwCellGuard {
-            value: f(orig.value),
-            borrow_state: orig.borrow_state,
-        };
-        forget(orig);
-        result
+    pub fn checkin(self, value: T) {
+        let this = ManuallyDrop::new(self);
+        this.finish(value);
     }
-}
 
-impl<'a, T: 'a + ?Sized> RwCellGuard<'a, Mut, T> {
-    /// Creates a reference to a specific portion of a value.
+    /// Stores a value in the cell and releases its mutable borrow.
     #[inline(always)]
-    pub fn map<U, F>(orig: Self, f: F) -> RwCellGuard<'a, Mut, U>
-    where
-        F: FnOnce(&mut T) -> &mut U,
-        U: ?Sized,
-    {
-        let RwCellGuardDestructure {
-            value,
-            borrow_state,
-        } = orig.into();
+    fn finish(&self, value: T) {
+        unsafe {
+            self.inner.value.get().write(value);
+        }
+        self.inner.state.release_write();
+    }
+}
 
-        RwCellGuard {
-            value: f(value),
-            borrow_state,
+impl<'a, T> Drop for WriteReservation<'a, T> {
+    fn drop(&mut self) {
+        match self.restore {
+            Some(restore) => {
+                let guard = AbortPanic("Failed to restore the value of a cell after its write reservation was dropped.");
+                let value = restore();
+                forget(guard);
+                self.finish(value);
+            },
+            None => AbortPanic::abort("Dropped a write reservation without checking in a value."),
         }
     }
 }
 
-impl<'a, M: Mutability, T: 'a + ?Sized> Deref for RwCellGuard<'a, M, T> {
+/// Holds a cell mutably borrowed while a copy of its value is modified. The copy replaces the
+/// value of the cell only if the guard is committed, and the cell's version is unchanged if the
+/// guard is dropped instead.
+#[derive(Debug)]
+pub struct SpeculativeGuard<'a, T> {
+    /// The mutable borrow of the cell, which records a change only once the copy is committed.
+    guard: TrackedMutGuard<'a, T>,
+    /// The working copy of the value.
+    value: T,
+}
+
+impl<'a, T> SpeculativeGuard<'a, T> {
+    /// Writes the working copy back to the cell and releases its mutable borrow.
+    #[inline(always)]
+    pub fn commit(orig: Self) {
+        let Self { mut guard, value } = orig;
+        *guard = value;
+    }
+}
+
+impl<'a, T> Deref for SpeculativeGuard<'a, T> {
     type Target = T;
 
     #[inline(always)]
@@ -154,40 +2713,331 @@ impl<'a, M: Mutability, T: 'a + ?Sized> Deref for RwCellGuard<'a, M, T> {
     }
 }
 
-impl<'a, T: 'a + ?Sized> DerefMut for RwCellGuard<'a, Mut, T> {
+impl<'a, T> DerefMut for SpeculativeGuard<'a, T> {
     #[inline(always)]
     fn deref_mut(&mut self) -> &mut Self::Target {
-        self.value
+        &mut self.value
     }
 }
 
-impl<'a, M: Mutability, T: 'a + ?Sized> Drop for RwCellGuard<'a, M, T> {
+/// A mutable guard for a cell that only records a change to the cell's value if the guard has
+/// been mutably dereferenced.
+pub struct TrackedMutGuard<'a, T> {
+    /// The borrowed value.
+    value: NonNull<T>,
+    /// The borrow state of the cell.
+    state: &'a RwCellState,
+    /// Whether the value has been mutably dereferenced.
+    changed: bool,
+}
+
+impl<'a, T> TrackedMutGuard<'a, T> {
+    /// Determines whether the value has been mutably dereferenced through this guard.
+    #[inline(always)]
+    pub fn is_changed(orig: &Self) -> bool {
+        orig.changed
+    }
+}
+
+impl<'a, T> Deref for TrackedMutGuard<'a, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<'a, T> DerefMut for TrackedMutGuard<'a, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.changed = true;
+        unsafe { self.value.as_mut() }
+    }
+}
+
+impl<'a, T> Drop for TrackedMutGuard<'a, T> {
     #[inline(always)]
     fn drop(&mut self) {
-        if TypeId::of::<M>() == TypeId::of::<Mut>() {
-            self.borrow_state.store(0, Ordering::Release);
+        if self.changed {
+            self.state.release_write();
         }
         else {
-            self.borrow_state.fetch_sub(1, Ordering::AcqRel);
+            self.state.release_write_unchanged();
         }
     }
 }
 
-/// Allows for destructure a mutable guard without running its destructor.
-struct RwCellGuardDestructure<'a, M: Mutability, T: ?Sized + 'a> {
-    /// The value currently being borrowed.
-    value: M::Ref<'a, T>,
-    /// The borrow counter.
-    borrow_state: &'a AtomicU16,
+impl<'a, T: fmt::Debug> fmt::Debug for TrackedMutGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+unsafe impl<'a, T: Send> Send for TrackedMutGuard<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for TrackedMutGuard<'a, T> {}
+
+/// A guard for an upgradable borrow of a cell. The value may be read like with an immutable
+/// borrow, and the guard can later be converted into a mutable borrow without releasing it.
+pub struct UpgradableGuard<'a, T> {
+    /// The borrowed value.
+    value: NonNull<T>,
+    /// The borrow state of the cell.
+    state: &'a RwCellState,
 }
 
-impl<'a, M: Mutability, T: ?Sized> From<RwCellGuard<'a, M, T>> for RwCellGuardDestructure<'a, M, T> {
+impl<'a, T> UpgradableGuard<'a, T> {
+    /// Converts an upgradable guard into a mutable guard without releasing the borrow. Aborts
+    /// if any immutable borrows of the cell still exist.
     #[inline(always)]
-    fn from(value: RwCellGuard<'a, M, T>) -> Self {
-        unsafe {
-            let mut result = MaybeUninit::uninit();
-            (result.as_mut_ptr() as *mut RwCellGuard<M, T>).write(value);
-            result.assume_init()
+    pub fn upgrade(orig: Self) -> RwCellGuard<'a, Mut, T> {
+        match Self::try_upgrade(orig) {
+            Ok(guard) => guard,
+            Err(_) => AbortPanic::abort("Attempted to upgrade borrow of cell while it was immutably borrowed."),
+        }
+    }
+
+    /// Attempts to convert an upgradable guard into a mutable guard without releasing the borrow.
+    /// This fails and returns the original guard if any immutable borrows of the cell exist.
+    #[inline(always)]
+    pub fn try_upgrade(orig: Self) -> Result<RwCellGuard<'a, Mut, T>, Self> {
+        if orig.state.try_upgrade() {
+            let orig = ManuallyDrop::new(orig);
+            unsafe { Ok(RwCellGuard::from_parts(orig.value, StateRef::new(orig.state))) }
+        }
+        else {
+            Err(orig)
+        }
+    }
+
+    /// Converts an upgradable guard into a mutable guard without releasing the borrow, spinning
+    /// until every immutable borrow of the cell has been released. New immutable borrows may
+    /// still be acquired while spinning, so a continuous stream of readers can delay the upgrade.
+    #[inline(always)]
+    pub fn upgrade_blocking(mut orig: Self) -> RwCellGuard<'a, Mut, T> {
+        loop {
+            match Self::try_upgrade(orig) {
+                Ok(guard) => return guard,
+                Err(guard) => orig = guard,
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Converts an upgradable guard into an immutable guard without releasing the borrow, so
+    /// that another upgradable borrow may be acquired. Aborts if the cell has too many
+    /// immutable borrows.
+    #[inline(always)]
+    pub fn downgrade(orig: Self) -> RwCellGuard<'a, Const, T> {
+        let orig = ManuallyDrop::new(orig);
+        orig.state.downgrade_upgradable();
+        unsafe { RwCellGuard::from_parts(orig.value, StateRef::new(orig.state)) }
+    }
+}
+
+impl<'a, T> Deref for UpgradableGuard<'a, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<'a, T> Drop for UpgradableGuard<'a, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.state.release_upgradable();
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for UpgradableGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+unsafe impl<'a, T: Send + Sync> Send for UpgradableGuard<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for UpgradableGuard<'a, T> {}
+
+/// A guard for a cell that is contained within another immutably borrowed cell, which holds
+/// the borrows of both cells.
+pub struct NestedGuard<'a, M: Mutability, U: 'a> {
+    /// The borrow of the inner cell.
+    inner: RwCellGuard<'a, M, U>,
+    /// Releases the borrow of the outer cell after the inner borrow.
+    _outer: BorrowRelease<'a, Const>,
+}
+
+impl<'a, M: Mutability, U: 'a> Deref for NestedGuard<'a, M, U> {
+    type Target = U;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<'a, U: 'a> DerefMut for NestedGuard<'a, Mut, U> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+/// An iterator that owns an immutable borrow of a cell, and yields guards for portions of its value.
+pub struct RwCellGuardIter<'a, T: 'a + ?Sized, U: 'a + ?Sized, F> {
+    /// The borrow of the whole value.
+    guard: RwCellGuard<'a, Const, T>,
+    /// The index to pass to the projection next.
+    index: usize,
+    /// Selects the portion of the value at each index.
+    project: F,
+    /// Marks the type of the yielded portions.
+    marker: PhantomData<fn() -> &'a U>,
+}
+
+impl<'a, T: 'a + ?Sized, U: 'a + ?Sized, F: FnMut(&T, usize) -> Option<&U>> Iterator for RwCellGuardIter<'a, T, U, F> {
+    type Item = RwCellGuard<'a, Const, U>;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = NonNull::from((self.project)(&self.guard, self.index)?);
+        self.index += 1;
+        let state = self.guard.state;
+        state.get().share_read();
+        unsafe { Some(RwCellGuard::from_parts(value, state.projected())) }
+    }
+}
+
+/// An iterator that owns a mutable borrow of a cell, and yields guards for the elements of a slice.
+pub struct RwCellGuardIterMut<'a, U: 'a> {
+    /// The borrow of the whole slice.
+    guard: RwCellGuard<'a, Mut, [U]>,
+    /// The index of the next element to yield.
+    index: usize,
+}
+
+impl<'a, U: 'a> Iterator for RwCellGuardIterMut<'a, U> {
+    type Item = RwCellGuard<'a, Mut, U>;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.guard.value.len() {
+            let value = unsafe { NonNull::new_unchecked(self.guard.value.as_ptr().cast::<U>().add(self.index)) };
+            self.index += 1;
+            let state = self.guard.state;
+            state.get().split_write();
+            unsafe { Some(RwCellGuard::from_parts(value, state)) }
+        }
+        else {
+            None
+        }
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.guard.value.len() - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, U: 'a> ExactSizeIterator for RwCellGuardIterMut<'a, U> {}
+
+/// An iterator that owns a mutable borrow of a cell, and yields guards for consecutive chunks of a slice.
+pub struct RwCellGuardChunksMut<'a, T: 'a> {
+    /// The borrow of the whole slice.
+    guard: RwCellGuard<'a, Mut, [T]>,
+    /// The maximum length of each chunk.
+    chunk_size: usize,
+    /// The index at which the next chunk begins.
+    offset: usize,
+}
+
+impl<'a, T: 'a> Iterator for RwCellGuardChunksMut<'a, T> {
+    type Item = RwCellGuard<'a, Mut, [T]>;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.guard.value.len() - self.offset;
+        if remaining > 0 {
+            let len = remaining.min(self.chunk_size);
+            let start = unsafe { self.guard.value.as_ptr().cast::<T>().add(self.offset) };
+            let value = unsafe { NonNull::new_unchecked(core::ptr::slice_from_raw_parts_mut(start, len)) };
+            self.offset += len;
+            let state = self.guard.state;
+            state.get().split_write();
+            unsafe { Some(RwCellGuard::from_parts(value, state)) }
+        }
+        else {
+            None
+        }
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let chunks = (self.guard.value.len() - self.offset).div_ceil(self.chunk_size);
+        (chunks, Some(chunks))
+    }
+}
+
+impl<'a, T: 'a> ExactSizeIterator for RwCellGuardChunksMut<'a, T> {}
+
+/// Releases a borrow of a cell when dropped, unless it is forgotten. This restores the
+/// borrow state if a mapping function unwinds after a guard has been destructured.
+struct BorrowRelease<'a, M: Mutability> {
+    /// The borrow state of the cell.
+    state: &'a RwCellState,
+    /// Marks the mutability of the borrow.
+    marker: PhantomData<M>,
+}
+
+impl<'a, M: Mutability> BorrowRelease<'a, M> {
+    /// Creates a new release for a borrow of the given cell.
+    #[inline(always)]
+    fn new(state: &'a RwCellState) -> Self {
+        Self {
+            state,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, M: Mutability> Drop for BorrowRelease<'a, M> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.state.release::<M>();
+    }
+}
+
+/// Reacquires a borrow of a cell when dropped, spinning until the borrow succeeds. This
+/// restores a guard's borrow after it has been temporarily released, even if the caller unwinds.
+struct BorrowReacquire<'a, M: Mutability> {
+    /// The borrow state of the cell.
+    state: &'a RwCellState,
+    /// Marks the mutability of the borrow.
+    marker: PhantomData<M>,
+}
+
+impl<'a, M: Mutability> BorrowReacquire<'a, M> {
+    /// Creates a new reacquisition for a borrow of the given cell.
+    #[inline(always)]
+    fn new(state: &'a RwCellState) -> Self {
+        Self {
+            state,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, M: Mutability> Drop for BorrowReacquire<'a, M> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if TypeId::of::<M>() == TypeId::of::<Mut>() {
+            self.state.spin_acquire_write();
+        }
+        else {
+            self.state.spin_acquire_read();
         }
     }
 }
@@ -203,6 +3053,17 @@ impl AbortPanic {
         let guard = Self(message);
         panic!("{:?}", message);
     }
+
+    /// Immediately aborts the program with the given message, naming the cell that caused it.
+    #[allow(unused_variables)]
+    #[inline(always)]
+    fn abort_named(message: &str, name: Option<&str>) -> ! {
+        let guard = Self(message);
+        match name {
+            Some(name) => panic!("{:?} (cell {:?})", message, name),
+            None => panic!("{:?}", message),
+        }
+    }
 }
 
 impl Drop for AbortPanic {
@@ -216,6 +3077,7 @@ impl Drop for AbortPanic {
 /// A read-only cell that allows immutable references for the inner data to
 /// be held simultaneously as mutable references to the outer data.
 #[derive(Debug, Default)]
+#[repr(transparent)]
 struct ReadCell<T> {
     /// The underlying value.
     inner: UnsafeCell<T>,
@@ -229,6 +3091,18 @@ impl<T> ReadCell<T> {
             inner: UnsafeCell::new(value),
         }
     }
+
+    /// Gets a raw pointer to the underlying value, given a raw pointer to the cell.
+    #[inline(always)]
+    pub const fn raw_get(this: *const Self) -> *mut T {
+        unsafe { UnsafeCell::raw_get(addr_of!((*this).inner)) }
+    }
+
+    /// Consumes this cell, returning the underlying value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
 }
 
 impl<T> Deref for ReadCell<T> {
@@ -238,4 +3112,27 @@ impl<T> Deref for ReadCell<T> {
     fn deref(&self) -> &Self::Target {
         unsafe { &*self.inner.get() }
     }
-}
\ No newline at end of file
+}
+
+impl<T> DerefMut for ReadCell<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.inner.get_mut()
+    }
+}
+#[cfg(all(test, feature = "versioning"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_wraps_on_overflow() {
+        let cell = RwCell::new(0);
+        cell.inner.state.version.store(u64::MAX, Ordering::Relaxed);
+        let last = cell.version();
+
+        *cell.borrow_mut() = 1;
+        assert_eq!(cell.version(), 0);
+        assert!(cell.changed_since(last));
+        assert!(!cell.changed_since(cell.version()));
+    }
+}
@@ -0,0 +1,146 @@
+use crate::*;
+
+/// The state of an empty mailbox.
+const EMPTY: u8 = 0;
+/// The state of a mailbox whose value is being put or taken.
+const BUSY: u8 = 1;
+/// The state of a mailbox that holds a value.
+const FULL: u8 = 2;
+
+/// A cell that holds at most one value, for handing values from one thread to another without
+/// a queue. A producer puts a value into the empty mailbox, and a consumer takes it out. The
+/// value is accessed only by the thread that claimed it, so neither side can observe a value
+/// that is partially put or taken. Each put and take changes the state with a single atomic
+/// operation on either side of moving the value.
+pub struct MailboxCell<T> {
+    /// Whether the mailbox is empty, full, or being accessed.
+    state: AtomicU8,
+    /// The value, which is initialized while the mailbox is full.
+    value: UnsafeCell<MaybeUninit<T>>,
+    /// The threads waiting for a value to be put.
+    #[cfg(feature = "std")]
+    queue: WaitQueue,
+}
+
+impl<T> MailboxCell<T> {
+    /// Creates a new, empty mailbox.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            state: AtomicU8::new(EMPTY),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+            #[cfg(feature = "std")]
+            queue: WaitQueue::new(),
+        }
+    }
+
+    /// Creates a new mailbox that holds the provided value.
+    #[inline(always)]
+    pub const fn with_value(value: T) -> Self {
+        Self {
+            state: AtomicU8::new(FULL),
+            value: UnsafeCell::new(MaybeUninit::new(value)),
+            #[cfg(feature = "std")]
+            queue: WaitQueue::new(),
+        }
+    }
+
+    /// Consumes this mailbox, returning its value if it holds one.
+    #[inline(always)]
+    pub fn into_inner(mut self) -> Option<T> {
+        self.take_mut()
+    }
+
+    /// Determines whether this mailbox currently holds a value. The result may be outdated
+    /// as soon as it is returned.
+    #[inline(always)]
+    pub fn is_full(&self) -> bool {
+        self.state.load(Ordering::Relaxed) == FULL
+    }
+
+    /// Puts a value into this mailbox. Fails and returns the value if the mailbox already
+    /// holds one, or if another thread is putting or taking a value at the same time.
+    #[inline(always)]
+    pub fn put(&self, value: T) -> Result<(), T> {
+        if self.state.compare_exchange(EMPTY, BUSY, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            return Err(value);
+        }
+
+        unsafe { (*self.value.get()).write(value) };
+        self.state.store(FULL, Ordering::Release);
+        #[cfg(feature = "std")]
+        self.queue.notify();
+        Ok(())
+    }
+
+    /// Takes the value out of this mailbox, leaving it empty. Returns `None` if the mailbox
+    /// is empty, or if another thread is putting or taking a value at the same time.
+    #[inline(always)]
+    pub fn take(&self) -> Option<T> {
+        if self.state.compare_exchange(FULL, BUSY, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            return None;
+        }
+
+        let value = unsafe { (*self.value.get()).assume_init_read() };
+        self.state.store(EMPTY, Ordering::Release);
+        Some(value)
+    }
+
+    /// Takes the value out of this mailbox, parking the current thread until one is put.
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub fn recv(&self) -> T {
+        self.queue.park_until(|| self.take())
+    }
+
+    /// Takes the value out of this mailbox, parking the current thread until one is put or the
+    /// timeout elapses. Returns `None` if the timeout elapses first.
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub fn recv_timeout(&self, timeout: std::time::Duration) -> Option<T> {
+        self.queue.park_until_deadline(std::time::Instant::now().checked_add(timeout), || self.take())
+    }
+
+    /// Takes the value out of this mailbox without any atomic operations, since the mailbox
+    /// is mutably borrowed.
+    #[inline(always)]
+    fn take_mut(&mut self) -> Option<T> {
+        let state = self.state.get_mut();
+        if *state == FULL {
+            *state = EMPTY;
+            unsafe { Some(self.value.get_mut().assume_init_read()) }
+        }
+        else {
+            None
+        }
+    }
+}
+
+impl<T> Default for MailboxCell<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for MailboxCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MailboxCell").field("full", &self.is_full()).finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for MailboxCell<T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        drop(self.take_mut());
+    }
+}
+
+impl<T> From<T> for MailboxCell<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self::with_value(value)
+    }
+}
+
+unsafe impl<T: Send> Sync for MailboxCell<T> {}
@@ -0,0 +1,79 @@
+use crate::*;
+
+impl<T> RwCell<T> {
+    /// Attempts to mutably borrow every cell in `cells` at once. Either all of the cells are
+    /// borrowed, or none are: if any cell is already borrowed, the borrows acquired so far are
+    /// released and `None` is returned. Also returns `None` if the same cell appears more than
+    /// once, since it could never be mutably borrowed twice.
+    #[inline(always)]
+    pub fn borrow_many_mut<'a, const N: usize>(cells: [&'a Self; N]) -> Option<[RwCellGuard<'a, Mut, T>; N]> {
+        acquire_all_mut(&cells.map(|cell| &cell.inner.state)).then(|| unsafe { cells.map(|cell| cell.write_guard()) })
+    }
+}
+
+/// A collection of cells that may be mutably borrowed together, either all at once or not at
+/// all. This is implemented for arrays of cells, and for tuples of up to eight cells with
+/// different types.
+pub trait BorrowManyMut<'a> {
+    /// The guards that are returned when every cell is borrowed.
+    type Guards;
+
+    /// Attempts to mutably borrow every cell at once. If any cell is already borrowed, or the
+    /// same cell appears more than once, nothing is borrowed and `None` is returned.
+    fn borrow_many_mut(self) -> Option<Self::Guards>;
+}
+
+impl<'a, T, const N: usize> BorrowManyMut<'a> for [&'a RwCell<T>; N] {
+    type Guards = [RwCellGuard<'a, Mut, T>; N];
+
+    #[inline(always)]
+    fn borrow_many_mut(self) -> Option<Self::Guards> {
+        RwCell::borrow_many_mut(self)
+    }
+}
+
+/// Implements [`BorrowManyMut`] for a tuple of cells.
+macro_rules! borrow_many_mut_tuple {
+    ($($index:tt => $ty:ident),*) => {
+        impl<'a, $($ty),*> BorrowManyMut<'a> for ($(&'a RwCell<$ty>,)*) {
+            type Guards = ($(RwCellGuard<'a, Mut, $ty>,)*);
+
+            #[inline(always)]
+            fn borrow_many_mut(self) -> Option<Self::Guards> {
+                acquire_all_mut(&[$(&self.$index.inner.state),*]).then(|| unsafe { ($(self.$index.write_guard(),)*) })
+            }
+        }
+    };
+}
+
+borrow_many_mut_tuple!(0 => A);
+borrow_many_mut_tuple!(0 => A, 1 => B);
+borrow_many_mut_tuple!(0 => A, 1 => B, 2 => C);
+borrow_many_mut_tuple!(0 => A, 1 => B, 2 => C, 3 => D);
+borrow_many_mut_tuple!(0 => A, 1 => B, 2 => C, 3 => D, 4 => E);
+borrow_many_mut_tuple!(0 => A, 1 => B, 2 => C, 3 => D, 4 => E, 5 => F);
+borrow_many_mut_tuple!(0 => A, 1 => B, 2 => C, 3 => D, 4 => E, 5 => F, 6 => G);
+borrow_many_mut_tuple!(0 => A, 1 => B, 2 => C, 3 => D, 4 => E, 5 => F, 6 => G, 7 => H);
+
+/// Acquires a mutable borrow of every state, or of none of them. Returns whether the borrows
+/// were acquired, which never happens if the same state appears twice.
+#[inline(always)]
+fn acquire_all_mut(states: &[&RwCellState]) -> bool {
+    for (i, state) in states.iter().enumerate() {
+        if states[..i].iter().any(|other| core::ptr::eq(*other, *state)) {
+            return false;
+        }
+    }
+
+    for (i, state) in states.iter().enumerate() {
+        if state.try_acquire_write().is_err() {
+            for acquired in &states[..i] {
+                acquired.release_write_unchanged();
+            }
+
+            return false;
+        }
+    }
+
+    true
+}
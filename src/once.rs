@@ -0,0 +1,162 @@
+use crate::*;
+
+/// A cell that can be written only once. After initialization, the value is read through
+/// plain references, without any borrow accounting.
+///
+/// When several threads attempt to initialize the cell at once, one of them runs its
+/// initializer while the others spin until the value is ready. An initializer that tries to
+/// initialize the same cell again will spin forever.
+pub struct OnceRwCell<T> {
+    /// The initialization state of the cell.
+    counter: AtomicU16,
+    /// The value, which is initialized once the counter is frozen.
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+impl<T> OnceRwCell<T> {
+    /// Creates a new, uninitialized cell.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            counter: AtomicU16::new(0),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// Gets a reference to the value, or `None` if the cell is not yet initialized.
+    #[inline(always)]
+    pub fn get(&self) -> Option<&T> {
+        if is_frozen(self.counter.load(Ordering::Acquire)) {
+            unsafe { Some((*self.value.get()).assume_init_ref()) }
+        }
+        else {
+            None
+        }
+    }
+
+    /// Gets a mutable reference to the value, or `None` if the cell is not yet initialized.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        if is_frozen(*self.counter.get_mut()) {
+            unsafe { Some(self.value.get_mut().assume_init_mut()) }
+        }
+        else {
+            None
+        }
+    }
+
+    /// Initializes the cell with a value. If the cell is already initialized, or is being
+    /// initialized by another thread, the value is returned.
+    #[inline(always)]
+    pub fn set(&self, value: T) -> Result<(), T> {
+        if self.counter.compare_exchange(0, WRITER, Ordering::Acquire, Ordering::Relaxed).is_ok() {
+            unsafe { self.finish(value) };
+            Ok(())
+        }
+        else {
+            Err(value)
+        }
+    }
+
+    /// Gets a reference to the value, initializing it with `f` if the cell is empty. If `f`
+    /// unwinds, the cell remains uninitialized.
+    #[inline(always)]
+    pub fn get_or_init(&self, f: impl FnOnce() -> T) -> &T {
+        let mut f = Some(f);
+        loop {
+            if let Some(value) = self.get() {
+                return value;
+            }
+            else if self.counter.compare_exchange(0, WRITER, Ordering::Acquire, Ordering::Relaxed).is_ok() {
+                let reset = OnceReset(&self.counter);
+                let value = (f.take().expect("Initializer was already taken."))();
+                forget(reset);
+                return unsafe { self.finish(value) };
+            }
+
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Consumes the cell, returning the value if it was initialized.
+    #[inline(always)]
+    pub fn into_inner(self) -> Option<T> {
+        let mut this = ManuallyDrop::new(self);
+        if is_frozen(*this.counter.get_mut()) {
+            unsafe { Some(this.value.get_mut().assume_init_read()) }
+        }
+        else {
+            None
+        }
+    }
+
+    /// Converts this cell into a [`RwCell`] holding the value. If the cell is not initialized,
+    /// it is returned instead.
+    #[inline(always)]
+    pub fn into_rw_cell(mut self) -> Result<RwCell<T>, Self> {
+        if self.get_mut().is_some() {
+            Ok(RwCell::new(self.into_inner().expect("Cell was not initialized.")))
+        }
+        else {
+            Err(self)
+        }
+    }
+
+    /// Writes the value of a cell that this thread is initializing, and marks it as ready.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, the current thread must have moved the counter
+    /// from empty to initializing.
+    #[inline(always)]
+    unsafe fn finish(&self, value: T) -> &T {
+        let result = (*self.value.get()).write(value);
+        self.counter.store(FROZEN, Ordering::Release);
+        result
+    }
+}
+
+impl<T> Default for OnceRwCell<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<T> for OnceRwCell<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self {
+            counter: AtomicU16::new(FROZEN),
+            value: UnsafeCell::new(MaybeUninit::new(value)),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for OnceRwCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OnceRwCell").field(&self.get()).finish()
+    }
+}
+
+impl<T> Drop for OnceRwCell<T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if let Some(value) = self.get_mut() {
+            unsafe { core::ptr::drop_in_place(value) };
+        }
+    }
+}
+
+unsafe impl<T: Send + Sync> Sync for OnceRwCell<T> {}
+
+/// Returns a cell to the uninitialized state when dropped, unless it is forgotten. This allows
+/// other threads to initialize the cell if an initializer unwinds.
+struct OnceReset<'a>(&'a AtomicU16);
+
+impl Drop for OnceReset<'_> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.0.store(0, Ordering::Release);
+    }
+}
@@ -0,0 +1,192 @@
+use crate::*;
+use std::sync::PoisonError;
+
+/// A cell which becomes poisoned if a thread panics while holding a mutable borrow of it,
+/// since the value may have been left partially updated. Once poisoned, further borrows fail
+/// until [`PoisonRwCell::clear_poison`] is called, mirroring [`std::sync::RwLock`].
+#[derive(Default)]
+pub struct PoisonRwCell<T> {
+    /// The underlying cell.
+    cell: RwCell<T>,
+    /// Whether a thread panicked while mutably borrowing the cell.
+    poisoned: AtomicBool,
+}
+
+impl<T> PoisonRwCell<T> {
+    /// Creates a new cell that wraps the provided value.
+    #[inline(always)]
+    pub const fn new(value: T) -> Self {
+        Self {
+            cell: RwCell::new(value),
+            poisoned: AtomicBool::new(false),
+        }
+    }
+
+    /// Consumes this cell, returning the wrapped value. Returns an error containing the value
+    /// if the cell is poisoned.
+    #[inline(always)]
+    pub fn into_inner(self) -> Result<T, PoisonError<T>> {
+        let poisoned = self.poisoned.into_inner();
+        let value = self.cell.into_inner();
+        if poisoned {
+            Err(PoisonError::new(value))
+        }
+        else {
+            Ok(value)
+        }
+    }
+
+    /// Gets a mutable reference to the value. This requires no borrow accounting,
+    /// since the cell is mutably borrowed. Returns an error containing the reference
+    /// if the cell is poisoned.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> Result<&mut T, PoisonError<&mut T>> {
+        let poisoned = *self.poisoned.get_mut();
+        let value = self.cell.get_mut();
+        if poisoned {
+            Err(PoisonError::new(value))
+        }
+        else {
+            Ok(value)
+        }
+    }
+
+    /// Determines whether this cell is poisoned.
+    #[inline(always)]
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::Acquire)
+    }
+
+    /// Clears the poisoned state of this cell, so that it may be borrowed again.
+    #[inline(always)]
+    pub fn clear_poison(&self) {
+        self.poisoned.store(false, Ordering::Release);
+    }
+
+    /// Immutably borrows the value of this cell. Aborts if the cell is mutably borrowed
+    /// or poisoned.
+    #[inline(always)]
+    pub fn borrow(&self) -> RwCellGuard<'_, Const, T> {
+        match self.try_borrow() {
+            Ok(guard) => guard,
+            Err(PoisonBorrowError::Poisoned) => AbortPanic::abort("Attempted to borrow cell after it was poisoned."),
+            Err(PoisonBorrowError::Borrow(_)) => AbortPanic::abort("Attempted to immutably borrow cell while it was mutably borrowed."),
+        }
+    }
+
+    /// Attempts to immutably borrow the value of this cell, returning an error instead of
+    /// aborting if the cell is mutably borrowed or poisoned.
+    #[inline(always)]
+    pub fn try_borrow(&self) -> Result<RwCellGuard<'_, Const, T>, PoisonBorrowError<BorrowError>> {
+        let guard = self.cell.try_borrow().map_err(PoisonBorrowError::Borrow)?;
+        if self.poisoned.load(Ordering::Acquire) {
+            Err(PoisonBorrowError::Poisoned)
+        }
+        else {
+            Ok(guard)
+        }
+    }
+
+    /// Mutably borrows the value of this cell. Aborts if any other borrows exist, or if
+    /// the cell is poisoned.
+    #[inline(always)]
+    pub fn borrow_mut(&self) -> PoisonGuard<'_, T> {
+        match self.try_borrow_mut() {
+            Ok(guard) => guard,
+            Err(PoisonBorrowError::Poisoned) => AbortPanic::abort("Attempted to borrow cell after it was poisoned."),
+            Err(PoisonBorrowError::Borrow(_)) => AbortPanic::abort("Attempted to mutably borrow cell while other borrows already existed."),
+        }
+    }
+
+    /// Attempts to mutably borrow the value of this cell, returning an error instead of
+    /// aborting if any other borrows exist or the cell is poisoned.
+    #[inline(always)]
+    pub fn try_borrow_mut(&self) -> Result<PoisonGuard<'_, T>, PoisonBorrowError<BorrowMutError>> {
+        let guard = self.cell.try_borrow_mut().map_err(PoisonBorrowError::Borrow)?;
+        if self.poisoned.load(Ordering::Acquire) {
+            Err(PoisonBorrowError::Poisoned)
+        }
+        else {
+            Ok(PoisonGuard {
+                guard,
+                poisoned: &self.poisoned,
+                panicking: std::thread::panicking(),
+            })
+        }
+    }
+}
+
+impl<T> fmt::Debug for PoisonRwCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PoisonRwCell").field("poisoned", &self.is_poisoned()).finish_non_exhaustive()
+    }
+}
+
+impl<T> From<T> for PoisonRwCell<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+/// An error returned when a [`PoisonRwCell`] could not be borrowed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PoisonBorrowError<E> {
+    /// A thread panicked while mutably borrowing the cell.
+    Poisoned,
+    /// The borrow conflicted with another borrow of the cell.
+    Borrow(E),
+}
+
+impl<E: fmt::Display> fmt::Display for PoisonBorrowError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Poisoned => f.write_str("Attempted to borrow cell after it was poisoned."),
+            Self::Borrow(error) => error.fmt(f),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> core::error::Error for PoisonBorrowError<E> {}
+
+/// A guard for a mutable borrow of a [`PoisonRwCell`], which poisons the cell if it is
+/// dropped while the thread is panicking.
+pub struct PoisonGuard<'a, T> {
+    /// The underlying borrow.
+    guard: RwCellGuard<'a, Mut, T>,
+    /// The poison flag of the borrowed cell.
+    poisoned: &'a AtomicBool,
+    /// Whether the thread was already panicking when the borrow was acquired.
+    panicking: bool,
+}
+
+impl<'a, T> Deref for PoisonGuard<'a, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<'a, T> DerefMut for PoisonGuard<'a, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+impl<'a, T> Drop for PoisonGuard<'a, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if !self.panicking && std::thread::panicking() {
+            self.poisoned.store(true, Ordering::Release);
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for PoisonGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
@@ -0,0 +1,81 @@
+use crate::*;
+
+/// Decides what happens when a borrow of a [`RwCell`] conflicts with an existing borrow. A
+/// policy is chosen per cell through its type, so different parts of a program can handle
+/// conflicts differently without any global configuration.
+pub trait ConflictPolicy: 'static {
+    /// Chooses how to handle an immutable borrow that conflicts with a mutable borrow.
+    /// `attempts` is the number of times that the borrow has already been retried.
+    fn on_read_conflict(attempts: usize) -> ConflictAction;
+
+    /// Chooses how to handle a mutable borrow that conflicts with another borrow.
+    /// `attempts` is the number of times that the borrow has already been retried.
+    fn on_write_conflict(attempts: usize) -> ConflictAction;
+}
+
+/// The ways in which a [`ConflictPolicy`] may handle a conflicting borrow.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ConflictAction {
+    /// Aborts the program.
+    Abort,
+    /// Panics with an unwinding panic, which may be caught.
+    Panic,
+    /// Attempts the borrow again after a short spin.
+    Retry,
+}
+
+/// A policy that aborts the program on any conflict. This is the default policy of a
+/// [`RwCell`], and acquires borrows without consulting the policy at all.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Abort;
+
+impl ConflictPolicy for Abort {
+    #[inline(always)]
+    fn on_read_conflict(_: usize) -> ConflictAction {
+        ConflictAction::Abort
+    }
+
+    #[inline(always)]
+    fn on_write_conflict(_: usize) -> ConflictAction {
+        ConflictAction::Abort
+    }
+}
+
+/// A policy that panics on any conflict, so that the conflict may be caught and recovered from.
+/// Guards that are dropped while unwinding release their borrows as usual.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PanicUnwind;
+
+impl ConflictPolicy for PanicUnwind {
+    #[inline(always)]
+    fn on_read_conflict(_: usize) -> ConflictAction {
+        ConflictAction::Panic
+    }
+
+    #[inline(always)]
+    fn on_write_conflict(_: usize) -> ConflictAction {
+        ConflictAction::Panic
+    }
+}
+
+/// A policy that spins and retries a conflicting borrow up to `N` times, and aborts if the
+/// borrow still conflicts after that.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SpinRetry<const N: usize>;
+
+impl<const N: usize> ConflictPolicy for SpinRetry<N> {
+    #[inline(always)]
+    fn on_read_conflict(attempts: usize) -> ConflictAction {
+        if attempts < N {
+            ConflictAction::Retry
+        }
+        else {
+            ConflictAction::Abort
+        }
+    }
+
+    #[inline(always)]
+    fn on_write_conflict(attempts: usize) -> ConflictAction {
+        Self::on_read_conflict(attempts)
+    }
+}
@@ -0,0 +1,155 @@
+use crate::*;
+
+/// The identifier that will be assigned to the next access token.
+static NEXT_TOKEN: AtomicUsize = AtomicUsize::new(1);
+
+/// Identifies a single chain of mutable accesses, such as a thread or a script invocation.
+/// Every token is unique, so holding a mutable reference to one proves that no other part
+/// of the program is using it.
+#[derive(Debug)]
+pub struct AccessToken {
+    /// The unique identifier of this token.
+    id: usize,
+}
+
+impl AccessToken {
+    /// Creates a new, unique token.
+    #[inline(always)]
+    pub fn new() -> Self {
+        match NEXT_TOKEN.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |id| id.checked_add(1)) {
+            Ok(id) => Self { id },
+            Err(_) => AbortPanic::abort("Created too many access tokens."),
+        }
+    }
+}
+
+impl Default for AccessToken {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A cell which may be mutably borrowed again while it is already mutably borrowed, as long
+/// as both borrows use the same [`AccessToken`]. The nested guard borrows the token through
+/// the outer guard, so only the innermost guard is usable at any time. Borrows using different
+/// tokens, or the plain [`RwCell`] methods, conflict as usual.
+#[derive(Debug, Default)]
+pub struct ReentrantRwCell<T> {
+    /// The underlying cell.
+    cell: RwCell<T>,
+    /// The identifier of the token that mutably borrows the cell, or zero.
+    owner: AtomicUsize,
+}
+
+impl<T> ReentrantRwCell<T> {
+    /// Creates a new cell that wraps the provided value.
+    #[inline(always)]
+    pub const fn new(value: T) -> Self {
+        Self {
+            cell: RwCell::new(value),
+            owner: AtomicUsize::new(0),
+        }
+    }
+
+    /// Consumes this cell, returning the wrapped value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.cell.into_inner()
+    }
+
+    /// Mutably borrows the value of this cell with a token. If the cell is already mutably
+    /// borrowed with the same token, the borrow is nested within the existing one. Otherwise,
+    /// aborts if any other borrows exist.
+    #[inline(always)]
+    pub fn borrow_mut_with<'a>(&'a self, token: &'a mut AccessToken) -> ReentrantGuard<'a, T> {
+        let nested = self.owner.load(Ordering::Relaxed) == token.id;
+        if !nested {
+            self.cell.inner.state.acquire_write();
+            self.owner.store(token.id, Ordering::Relaxed);
+        }
+
+        ReentrantGuard { cell: self, token, nested }
+    }
+
+    /// Attempts to mutably borrow the value of this cell with a token. If the cell is already
+    /// mutably borrowed with the same token, the borrow is nested within the existing one.
+    /// Otherwise, returns an error if any other borrows exist.
+    #[inline(always)]
+    pub fn try_borrow_mut_with<'a>(&'a self, token: &'a mut AccessToken) -> Result<ReentrantGuard<'a, T>, BorrowMutError> {
+        let nested = self.owner.load(Ordering::Relaxed) == token.id;
+        if !nested {
+            self.cell.inner.state.try_acquire_write()?;
+            self.owner.store(token.id, Ordering::Relaxed);
+        }
+
+        Ok(ReentrantGuard { cell: self, token, nested })
+    }
+}
+
+impl<T> Deref for ReentrantRwCell<T> {
+    type Target = RwCell<T>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.cell
+    }
+}
+
+impl<T> From<T> for ReentrantRwCell<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+/// A guard for a mutable borrow of a [`ReentrantRwCell`] made with an [`AccessToken`].
+pub struct ReentrantGuard<'a, T> {
+    /// The borrowed cell.
+    cell: &'a ReentrantRwCell<T>,
+    /// The token used to borrow the cell.
+    token: &'a mut AccessToken,
+    /// Whether this borrow is nested within another borrow of the same cell.
+    nested: bool,
+}
+
+impl<'a, T> ReentrantGuard<'a, T> {
+    /// Gets the token used to borrow the cell, so that it can be used for nested borrows. The
+    /// guard cannot be used while the token is borrowed.
+    #[inline(always)]
+    pub fn token(orig: &mut Self) -> &mut AccessToken {
+        orig.token
+    }
+}
+
+impl<'a, T> Deref for ReentrantGuard<'a, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.cell.cell.as_ptr() }
+    }
+}
+
+impl<'a, T> DerefMut for ReentrantGuard<'a, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.cell.cell.as_ptr() }
+    }
+}
+
+impl<'a, T> Drop for ReentrantGuard<'a, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if !self.nested {
+            self.cell.owner.store(0, Ordering::Relaxed);
+            self.cell.cell.inner.state.release_write();
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for ReentrantGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
@@ -0,0 +1,156 @@
+use crate::*;
+
+/// A cell for [`Copy`] values that uses a sequence counter, so that reads never block or
+/// abort. Readers copy the value and retry if a write occurred in the meantime, while
+/// concurrent writers spin until the current write completes.
+pub struct SeqCell<T: AtomicValue> {
+    /// The sequence counter, which is odd while a write is in progress.
+    sequence: AtomicUsize,
+    /// The value, which is only accessed through atomic operations on its bytes.
+    value: UnsafeCell<T>,
+}
+
+impl<T: AtomicValue> SeqCell<T> {
+    /// Creates a new cell that wraps the provided value.
+    #[inline(always)]
+    pub const fn new(value: T) -> Self {
+        Self {
+            sequence: AtomicUsize::new(0),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Consumes this cell, returning the wrapped value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    /// Gets a mutable reference to the value. This requires no synchronization,
+    /// since the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// Gets a copy of the value, retrying until no write occurs during the copy.
+    #[inline(always)]
+    pub fn read(&self) -> T {
+        loop {
+            let before = self.sequence.load(Ordering::Acquire);
+            if before & 1 == 0 {
+                let result = unsafe { self.load_bytes() };
+                fence(Ordering::Acquire);
+                if self.sequence.load(Ordering::Relaxed) == before {
+                    return unsafe { result.assume_init() };
+                }
+            }
+
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Sets the value, spinning while another thread is writing.
+    #[inline(always)]
+    pub fn write(&self, value: T) {
+        let sequence = self.begin_write();
+        fence(Ordering::Release);
+        unsafe { self.store_bytes(&value) };
+        self.sequence.store(sequence.wrapping_add(2), Ordering::Release);
+    }
+
+    /// Sets the value to the result of `f`, which is given the current value.
+    #[inline(always)]
+    pub fn update(&self, f: impl FnOnce(T) -> T) {
+        let sequence = self.begin_write();
+        let finish = SeqFinish(&self.sequence, sequence.wrapping_add(2));
+        unsafe {
+            let value = f(self.load_bytes().assume_init());
+            fence(Ordering::Release);
+            self.store_bytes(&value);
+        }
+        drop(finish);
+    }
+
+    /// Marks a write as in progress, spinning while another thread is writing. Returns the
+    /// sequence number from before the write.
+    #[inline(always)]
+    fn begin_write(&self) -> usize {
+        let mut sequence = self.sequence.load(Ordering::Relaxed);
+        loop {
+            if sequence & 1 == 0 {
+                match self.sequence.compare_exchange_weak(sequence, sequence.wrapping_add(1), Ordering::Acquire, Ordering::Relaxed) {
+                    Ok(_) => return sequence,
+                    Err(current) => sequence = current,
+                }
+            }
+            else {
+                core::hint::spin_loop();
+                sequence = self.sequence.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Copies the value out of the cell byte by byte using relaxed atomic operations, so that
+    /// copies which race with writes are not data races. The copy is only valid if no write
+    /// occurred in the meantime.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, all concurrent accesses to the value must be atomic.
+    #[inline(always)]
+    unsafe fn load_bytes(&self) -> MaybeUninit<T> {
+        let mut result = MaybeUninit::<T>::uninit();
+        let (src, dst) = (self.value.get().cast::<u8>(), result.as_mut_ptr().cast::<u8>());
+        for i in 0..size_of::<T>() {
+            dst.add(i).write(AtomicU8::from_ptr(src.add(i)).load(Ordering::Relaxed));
+        }
+        result
+    }
+
+    /// Copies a value into the cell byte by byte using relaxed atomic operations.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, the current thread must be writing to the cell.
+    #[inline(always)]
+    unsafe fn store_bytes(&self, value: &T) {
+        let (src, dst) = ((value as *const T).cast::<u8>(), self.value.get().cast::<u8>());
+        for i in 0..size_of::<T>() {
+            AtomicU8::from_ptr(dst.add(i)).store(src.add(i).read(), Ordering::Relaxed);
+        }
+    }
+}
+
+impl<T: AtomicValue + Default> Default for SeqCell<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: AtomicValue + fmt::Debug> fmt::Debug for SeqCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SeqCell").field(&self.read()).finish()
+    }
+}
+
+impl<T: AtomicValue> From<T> for SeqCell<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+unsafe impl<T: AtomicValue + Send> Sync for SeqCell<T> {}
+
+/// Completes a write to a [`SeqCell`] when dropped, so that the cell remains usable if the
+/// writer unwinds.
+struct SeqFinish<'a>(&'a AtomicUsize, usize);
+
+impl Drop for SeqFinish<'_> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.0.store(self.1, Ordering::Release);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::*;
+
+/// The base-two logarithm of the number of borrow states in the side table.
+const SHADOW_BITS: u32 = 10;
+/// The number of borrow states in the side table.
+const SHADOW_STATES: usize = 1 << SHADOW_BITS;
+
+/// The borrow states of every shadow cell, which are selected by hashing the cell's address.
+static STATES: [RwCellState; SHADOW_STATES] = [const { RwCellState::new() }; SHADOW_STATES];
+
+/// A cell whose borrow state is stored in a global side table rather than alongside its value,
+/// so that it has exactly the same layout as `T`. Slices of cells may then be reinterpreted as
+/// slices of values and back, using [`ShadowRwCell::cast_slice`],
+/// [`ShadowRwCell::cast_slice_mut`], and [`ShadowRwCell::from_slice_mut`]. Borrows return
+/// ordinary guards, which may be mapped or downgraded as usual.
+///
+/// The side table has a fixed number of entries, which are selected by hashing the address of
+/// each cell. Cells whose addresses collide share a borrow state, so borrowing one of them
+/// conflicts with borrows of the others, and a guard that is leaked keeps every cell sharing
+/// its entry borrowed. Looking up the entry also makes each borrow slightly slower than a
+/// borrow of a [`RwCell`].
+#[repr(transparent)]
+pub struct ShadowRwCell<T> {
+    /// The wrapped value.
+    value: UnsafeCell<T>,
+}
+
+impl<T> ShadowRwCell<T> {
+    /// Creates a new cell that wraps the provided value.
+    #[inline(always)]
+    pub const fn new(value: T) -> Self {
+        Self {
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Consumes this cell, returning the wrapped value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    /// Gets a mutable reference to the value. This requires no borrow accounting,
+    /// since the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// Gets a raw pointer to the wrapped value.
+    #[inline(always)]
+    pub const fn as_ptr(&self) -> *mut T {
+        self.value.get()
+    }
+
+    /// Views a mutable reference to a value as a mutable reference to a cell.
+    #[inline(always)]
+    pub fn from_mut(value: &mut T) -> &mut Self {
+        unsafe { &mut *(value as *mut T).cast() }
+    }
+
+    /// Views a mutable slice of values as a mutable slice of cells.
+    #[inline(always)]
+    pub fn from_slice_mut(values: &mut [T]) -> &mut [Self] {
+        unsafe { &mut *(values as *mut [T] as *mut [Self]) }
+    }
+
+    /// Views a mutable slice of cells as a mutable slice of their values. This requires no
+    /// borrow accounting, since the cells are mutably borrowed.
+    #[inline(always)]
+    pub fn cast_slice_mut(cells: &mut [Self]) -> &mut [T] {
+        unsafe { &mut *(cells as *mut [Self] as *mut [T]) }
+    }
+
+    /// Immutably borrows every cell in a slice, and views them as a slice of their values.
+    /// Returns an error if any of the cells are mutably borrowed, or if an entry of the side
+    /// table cannot record any more immutable borrows, in which case none of the cells remain
+    /// borrowed.
+    #[inline(always)]
+    pub fn cast_slice(cells: &[Self]) -> Result<ShadowSliceGuard<'_, T>, BorrowError> {
+        for (i, cell) in cells.iter().enumerate() {
+            if !cell.state().try_acquire_read() {
+                for acquired in &cells[..i] {
+                    acquired.state().release::<Const>();
+                }
+
+                return Err(BorrowError);
+            }
+        }
+
+        Ok(ShadowSliceGuard { cells })
+    }
+
+    /// Gets the current borrow state of this cell, which also reflects the borrows of any
+    /// cells that share its entry in the side table. The result is racy in the same way as
+    /// [`RwCell::borrow_state`].
+    #[inline(always)]
+    pub fn borrow_state(&self) -> BorrowState {
+        self.state().borrow_state()
+    }
+
+    /// Immutably borrows the value of this cell. Aborts if the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn borrow(&self) -> RwCellGuard<'_, Const, T> {
+        match self.try_borrow() {
+            Ok(guard) => guard,
+            Err(_) => AbortPanic::abort("Attempted to immutably borrow cell while it was mutably borrowed."),
+        }
+    }
+
+    /// Attempts to immutably borrow the value of this cell, returning an error
+    /// instead of aborting if the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn try_borrow(&self) -> Result<RwCellGuard<'_, Const, T>, BorrowError> {
+        let state = self.state();
+        if state.try_acquire_read() {
+            unsafe { Ok(RwCellGuard::from_parts(NonNull::new_unchecked(self.as_ptr()), StateRef::new(state))) }
+        }
+        else {
+            Err(BorrowError)
+        }
+    }
+
+    /// Mutably borrows the value of this cell. Aborts if any other borrows exist.
+    #[inline(always)]
+    pub fn borrow_mut(&self) -> RwCellGuard<'_, Mut, T> {
+        match self.try_borrow_mut() {
+            Ok(guard) => guard,
+            Err(_) => AbortPanic::abort("Attempted to mutably borrow cell while other borrows already existed."),
+        }
+    }
+
+    /// Attempts to mutably borrow the value of this cell, returning an error
+    /// instead of aborting if any other borrows exist.
+    #[inline(always)]
+    pub fn try_borrow_mut(&self) -> Result<RwCellGuard<'_, Mut, T>, BorrowMutError> {
+        let state = self.state();
+        state.try_acquire_write()?;
+        unsafe { Ok(RwCellGuard::from_parts(NonNull::new_unchecked(self.as_ptr()), StateRef::new(state))) }
+    }
+
+    /// Gets the entry of the side table that holds the borrow state of this cell.
+    #[inline(always)]
+    fn state(&self) -> &'static RwCellState {
+        let hash = self.as_ptr().addr().wrapping_mul(0x9E37_79B9_7F4A_7C15_u64 as usize);
+        &STATES[hash >> (usize::BITS - SHADOW_BITS)]
+    }
+}
+
+impl<T: Default> Default for ShadowRwCell<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> fmt::Debug for ShadowRwCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShadowRwCell").field("state", &self.borrow_state()).finish_non_exhaustive()
+    }
+}
+
+impl<T> From<T> for ShadowRwCell<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+unsafe impl<T: Send + Sync> Sync for ShadowRwCell<T> {}
+
+/// A guard for immutable borrows of every cell in a slice of [`ShadowRwCell`]s, which
+/// dereferences to a slice of their values.
+pub struct ShadowSliceGuard<'a, T> {
+    /// The borrowed cells.
+    cells: &'a [ShadowRwCell<T>],
+}
+
+impl<'a, T> Deref for ShadowSliceGuard<'a, T> {
+    type Target = [T];
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*(self.cells as *const [ShadowRwCell<T>] as *const [T]) }
+    }
+}
+
+impl<'a, T> Drop for ShadowSliceGuard<'a, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        for cell in self.cells {
+            cell.state().release::<Const>();
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for ShadowSliceGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+unsafe impl<'a, T: Sync> Send for ShadowSliceGuard<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for ShadowSliceGuard<'a, T> {}
@@ -0,0 +1,233 @@
+use crate::*;
+
+/// The base-two logarithm of the number of reader slots.
+const SLOT_BITS: u32 = 10;
+/// The number of reader slots shared by every sharded cell.
+const READER_SLOTS: usize = 1 << SLOT_BITS;
+/// The number of immutable borrows that must take the slow path after a writer revokes the
+/// reader bias of a cell, before the bias is restored.
+const REBIAS_READS: u32 = 256;
+/// The tag bit of a slot whose reader has not yet confirmed that the cell is still biased.
+const PENDING: usize = 1;
+
+/// The slots in which biased readers record their borrows. Each slot holds the address of the
+/// borrowed cell, or zero if it is empty.
+static READERS: [AtomicUsize; READER_SLOTS] = [const { AtomicUsize::new(0) }; READER_SLOTS];
+
+std::thread_local! {
+    /// A value whose address identifies the current thread.
+    static THREAD_KEY: u8 = const { 0 };
+}
+
+/// A cell whose immutable borrows are distributed across a global table of reader slots, rather
+/// than all modifying the same counter. This follows the BRAVO design: while the cell is biased
+/// toward readers, each thread records its borrow in a slot chosen by hashing the thread and
+/// the cell, so readers on different threads rarely touch the same cache line. A mutable borrow
+/// revokes the bias and scans every slot for readers of the cell, which makes it much slower
+/// than a mutable borrow of a [`RwCell`]. The bias is restored after a number of immutable
+/// borrows have been made without it, so cells that are written often stop using the slots.
+///
+/// Readers fall back to the cell's counter whenever their slot is taken, such as when the same
+/// thread borrows the cell twice, or when two cells hash to the same slot. Borrows conflict and
+/// abort exactly as they would for a [`RwCell`].
+#[derive(Debug)]
+pub struct RwCellSharded<T> {
+    /// The underlying cell, which tracks the borrows that do not use a slot.
+    cell: RwCell<T>,
+    /// Whether immutable borrows may be recorded in the reader slots.
+    biased: AtomicBool,
+    /// The number of immutable borrows that must still be made before the bias is restored.
+    inhibited: AtomicU32,
+}
+
+impl<T> RwCellSharded<T> {
+    /// Creates a new cell that wraps the provided value.
+    #[inline(always)]
+    pub const fn new(value: T) -> Self {
+        Self {
+            cell: RwCell::new(value),
+            biased: AtomicBool::new(true),
+            inhibited: AtomicU32::new(0),
+        }
+    }
+
+    /// Consumes this cell, returning the wrapped value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.cell.into_inner()
+    }
+
+    /// Gets a mutable reference to the value. This requires no borrow accounting,
+    /// since the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.cell.get_mut()
+    }
+
+    /// Immutably borrows the value of this cell. Aborts if the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn borrow(&self) -> ShardedReadGuard<'_, T> {
+        match self.try_borrow() {
+            Ok(guard) => guard,
+            Err(_) => AbortPanic::abort("Attempted to immutably borrow cell while it was mutably borrowed."),
+        }
+    }
+
+    /// Attempts to immutably borrow the value of this cell, returning an error
+    /// instead of aborting if the cell is mutably borrowed.
+    #[inline(always)]
+    pub fn try_borrow(&self) -> Result<ShardedReadGuard<'_, T>, BorrowError> {
+        if self.biased.load(Ordering::Relaxed) {
+            let slot = self.slot();
+            let address = self.address();
+            if slot.compare_exchange(0, address | PENDING, Ordering::SeqCst, Ordering::Relaxed).is_ok() {
+                if self.biased.load(Ordering::SeqCst) {
+                    slot.store(address, Ordering::SeqCst);
+                    return Ok(ShardedReadGuard { cell: self, slot: Some(slot) });
+                }
+
+                slot.store(0, Ordering::Release);
+            }
+        }
+
+        forget(self.cell.try_borrow()?);
+        self.rebias();
+        Ok(ShardedReadGuard { cell: self, slot: None })
+    }
+
+    /// Mutably borrows the value of this cell. Aborts if any other borrows exist.
+    #[inline(always)]
+    pub fn borrow_mut(&self) -> RwCellGuard<'_, Mut, T> {
+        match self.try_borrow_mut() {
+            Ok(guard) => guard,
+            Err(_) => AbortPanic::abort("Attempted to mutably borrow cell while other borrows already existed."),
+        }
+    }
+
+    /// Attempts to mutably borrow the value of this cell, returning an error
+    /// instead of aborting if any other borrows exist.
+    #[inline(always)]
+    pub fn try_borrow_mut(&self) -> Result<RwCellGuard<'_, Mut, T>, BorrowMutError> {
+        let guard = self.cell.try_borrow_mut()?;
+        if self.biased.swap(false, Ordering::SeqCst) {
+            self.inhibited.store(REBIAS_READS, Ordering::Relaxed);
+            if self.has_biased_readers() {
+                self.biased.store(true, Ordering::SeqCst);
+                let (_, state) = guard.into_parts();
+                state.get().release_write_unchanged();
+                return Err(BorrowMutError::Reading);
+            }
+        }
+
+        Ok(guard)
+    }
+
+    /// Determines whether immutable borrows of this cell are currently recorded in the reader
+    /// slots. This is false from the time that a mutable borrow revokes the bias until enough
+    /// immutable borrows have been made without it. The result may be outdated as soon as it
+    /// is returned.
+    #[inline(always)]
+    pub fn is_biased(&self) -> bool {
+        self.biased.load(Ordering::Relaxed)
+    }
+
+    /// Gets the address that identifies this cell in the reader slots.
+    #[inline(always)]
+    fn address(&self) -> usize {
+        (self as *const Self).addr()
+    }
+
+    /// Gets the reader slot of this cell for the current thread.
+    #[inline(always)]
+    fn slot(&self) -> &'static AtomicUsize {
+        let thread = THREAD_KEY.with(|key| (key as *const u8).addr());
+        let hash = (thread ^ self.address().rotate_left(usize::BITS / 2)).wrapping_mul(0x9E37_79B9_7F4A_7C15_u64 as usize);
+        &READERS[hash >> (usize::BITS - SLOT_BITS)]
+    }
+
+    /// Determines whether any reader slot holds a borrow of this cell, waiting for readers that
+    /// have not yet confirmed whether the cell is biased. The bias must already be revoked, so
+    /// that those readers fall back to the slow path.
+    #[inline(always)]
+    fn has_biased_readers(&self) -> bool {
+        let address = self.address();
+        READERS.iter().any(|slot| loop {
+            let value = slot.load(Ordering::SeqCst);
+            if value != address | PENDING {
+                break value == address;
+            }
+
+            core::hint::spin_loop();
+        })
+    }
+
+    /// Counts an immutable borrow that was made without the bias, restoring the bias once enough
+    /// such borrows have occurred. The caller must hold an immutable borrow of the underlying
+    /// cell, so that no writer can revoke the bias concurrently.
+    #[inline(always)]
+    fn rebias(&self) {
+        if !self.biased.load(Ordering::Relaxed) && self.inhibited.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |reads| reads.checked_sub(1)).is_err() {
+            self.biased.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+impl<T: Default> Default for RwCellSharded<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for RwCellSharded<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+/// A guard for an immutable borrow of a [`RwCellSharded`], which is recorded either in a reader
+/// slot or in the cell's counter.
+pub struct ShardedReadGuard<'a, T> {
+    /// The borrowed cell.
+    cell: &'a RwCellSharded<T>,
+    /// The reader slot holding the borrow, if any.
+    slot: Option<&'static AtomicUsize>,
+}
+
+impl<'a, T> ShardedReadGuard<'a, T> {
+    /// Determines whether the borrow is recorded in a reader slot, rather than in the cell's
+    /// counter.
+    #[inline(always)]
+    pub fn is_in_slot(orig: &Self) -> bool {
+        orig.slot.is_some()
+    }
+}
+
+impl<'a, T> Deref for ShardedReadGuard<'a, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.cell.cell.as_ptr() }
+    }
+}
+
+impl<'a, T> Drop for ShardedReadGuard<'a, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        match self.slot {
+            Some(slot) => slot.store(0, Ordering::Release),
+            None => self.cell.cell.inner.state.release::<Const>(),
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for ShardedReadGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+unsafe impl<'a, T: Sync> Send for ShardedReadGuard<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for ShardedReadGuard<'a, T> {}
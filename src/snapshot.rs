@@ -0,0 +1,211 @@
+use crate::*;
+use alloc::sync::Arc;
+
+/// A cell for values that are read often and replaced as a whole, in the style of
+/// read-copy-update. Readers load an owned snapshot of the current value, which never conflicts
+/// with anything and stays valid however long it is kept. Writers publish a new value, which
+/// later loads observe, while existing snapshots keep the value that they loaded.
+///
+/// Writers are serialized by a borrow counter, and spin while another writer is active. Loads
+/// are tracked in two alternating counters, so that a writer only waits for the loads that
+/// began before it published, and never indefinitely for the loads that follow.
+pub struct SnapshotCell<T> {
+    /// The current value, which holds one strong count of its [`Arc`].
+    current: AtomicPtr<T>,
+    /// Selects which of the load counters new loads are recorded in.
+    epoch: AtomicUsize,
+    /// The number of loads in progress for each epoch.
+    loads: [AtomicUsize; 2],
+    /// Serializes writers.
+    writer: RwCellState,
+    /// Marks ownership of the current value.
+    marker: PhantomData<Arc<T>>,
+}
+
+impl<T> SnapshotCell<T> {
+    /// Creates a new cell that wraps the provided value.
+    #[inline(always)]
+    pub fn new(value: T) -> Self {
+        Self::from_arc(Arc::new(value))
+    }
+
+    /// Creates a new cell whose current value is the provided snapshot.
+    #[inline(always)]
+    pub fn from_arc(value: Arc<T>) -> Self {
+        Self {
+            current: AtomicPtr::new(Arc::into_raw(value).cast_mut()),
+            epoch: AtomicUsize::new(0),
+            loads: [AtomicUsize::new(0), AtomicUsize::new(0)],
+            writer: RwCellState::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Consumes this cell, returning a snapshot of its current value.
+    #[inline(always)]
+    pub fn into_inner(self) -> Arc<T> {
+        let this = ManuallyDrop::new(self);
+        unsafe { Arc::from_raw(this.current.load(Ordering::Relaxed)) }
+    }
+
+    /// Loads a snapshot of the current value. This never conflicts with writers, and only
+    /// retries if a writer begins a new epoch while the load is being recorded.
+    #[inline(always)]
+    pub fn load(&self) -> Arc<T> {
+        loop {
+            let epoch = self.epoch.load(Ordering::SeqCst);
+            let loads = &self.loads[epoch & 1];
+            loads.fetch_add(1, Ordering::SeqCst);
+            if self.epoch.load(Ordering::SeqCst) == epoch {
+                let current = self.current.load(Ordering::SeqCst);
+                unsafe { Arc::increment_strong_count(current) };
+                loads.fetch_sub(1, Ordering::Release);
+                return unsafe { Arc::from_raw(current) };
+            }
+
+            loads.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Publishes a new value, which all later loads observe.
+    #[inline(always)]
+    pub fn store(&self, value: T) {
+        drop(self.swap(Arc::new(value)));
+    }
+
+    /// Publishes a new snapshot, returning the snapshot that it replaced.
+    #[inline(always)]
+    pub fn swap(&self, value: Arc<T>) -> Arc<T> {
+        let _release = self.lock_writer();
+        unsafe { self.publish(value) }
+    }
+
+    /// Publishes the value returned by applying `f` to the current value. No other writer can
+    /// publish between the call to `f` and the new value being published, so no update is lost.
+    /// Returns the snapshot that was replaced.
+    #[inline(always)]
+    pub fn rcu(&self, f: impl FnOnce(&T) -> T) -> Arc<T> {
+        let _release = self.lock_writer();
+        let value = f(unsafe { &*self.current.load(Ordering::Relaxed) });
+        unsafe { self.publish(Arc::new(value)) }
+    }
+
+    /// Mutably borrows a copy of the current value, spinning while another writer is active.
+    /// The copy is published when the guard is dropped, so the value can be modified in place
+    /// through the guard. The guard only dereferences to the copy: unlike a [`RwCellGuard`], it
+    /// cannot be mapped, split, or downgraded.
+    #[inline(always)]
+    pub fn write(&self) -> SnapshotWriteGuard<'_, T>
+    where
+        T: Clone,
+    {
+        let release = self.lock_writer();
+        let value = unsafe { (*self.current.load(Ordering::Relaxed)).clone() };
+        forget(release);
+        SnapshotWriteGuard {
+            cell: self,
+            value: ManuallyDrop::new(value),
+        }
+    }
+
+    /// Acquires the writer borrow, which is released when the result is dropped.
+    #[inline(always)]
+    fn lock_writer(&self) -> BorrowRelease<'_, Mut> {
+        self.writer.spin_acquire_write();
+        BorrowRelease::new(&self.writer)
+    }
+
+    /// Replaces the current value, waiting for every load that may have observed the old value
+    /// to finish recording it. Such a load may have been recorded in either counter, since it
+    /// may have begun before the previous writer's last epoch, so both counters are drained in
+    /// turn. New loads are recorded in the other counter from the one being drained.
+    ///
+    /// # Safety
+    ///
+    /// For this function to be sound, the caller must hold the writer borrow.
+    #[inline(always)]
+    unsafe fn publish(&self, value: Arc<T>) -> Arc<T> {
+        let previous = self.current.swap(Arc::into_raw(value).cast_mut(), Ordering::SeqCst);
+        for _ in 0..2 {
+            let epoch = self.epoch.fetch_add(1, Ordering::SeqCst);
+            while self.loads[epoch & 1].load(Ordering::SeqCst) != 0 {
+                core::hint::spin_loop();
+            }
+        }
+
+        Arc::from_raw(previous)
+    }
+}
+
+impl<T: Default> Default for SnapshotCell<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SnapshotCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SnapshotCell").field(&self.load()).finish()
+    }
+}
+
+impl<T> Drop for SnapshotCell<T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        unsafe { drop(Arc::from_raw(*self.current.get_mut())) };
+    }
+}
+
+impl<T> From<T> for SnapshotCell<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> From<Arc<T>> for SnapshotCell<T> {
+    #[inline(always)]
+    fn from(value: Arc<T>) -> Self {
+        Self::from_arc(value)
+    }
+}
+
+/// A guard for a copy of the value of a [`SnapshotCell`], which publishes the copy when dropped.
+pub struct SnapshotWriteGuard<'a, T> {
+    /// The cell that the copy is published to.
+    cell: &'a SnapshotCell<T>,
+    /// The copy being modified.
+    value: ManuallyDrop<T>,
+}
+
+impl<'a, T> Deref for SnapshotWriteGuard<'a, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<'a, T> DerefMut for SnapshotWriteGuard<'a, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl<'a, T> Drop for SnapshotWriteGuard<'a, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        let _release = BorrowRelease::<Mut>::new(&self.cell.writer);
+        let value = unsafe { ManuallyDrop::take(&mut self.value) };
+        unsafe { drop(self.cell.publish(Arc::new(value))) };
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for SnapshotWriteGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
@@ -0,0 +1,121 @@
+use crate::*;
+
+/// The identifier that the next [`CellOwner`] will receive.
+static NEXT_OWNER: AtomicUsize = AtomicUsize::new(0);
+
+/// A token that controls access to every [`TokenCell`] created with it. Borrowing the owner
+/// immutably allows any number of its cells to be borrowed immutably, and borrowing it mutably
+/// allows one of its cells to be borrowed mutably, so Rust's borrow checker enforces exclusivity
+/// in place of a borrow counter. Each owner has a unique identifier, which its cells check
+/// when they are borrowed.
+#[derive(Debug)]
+pub struct CellOwner {
+    /// The unique identifier of this owner.
+    id: usize,
+}
+
+impl CellOwner {
+    /// Creates a new, unique owner.
+    #[inline(always)]
+    pub fn new() -> Self {
+        match NEXT_OWNER.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |id| id.checked_add(1)) {
+            Ok(id) => Self { id },
+            Err(_) => AbortPanic::abort("Created too many cell owners."),
+        }
+    }
+
+    /// Creates a new cell that is owned by this owner.
+    #[inline(always)]
+    pub fn cell<T>(&self, value: T) -> TokenCell<T> {
+        TokenCell::new(self, value)
+    }
+}
+
+impl Default for CellOwner {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A cell whose borrows are controlled by a [`CellOwner`], rather than by an atomic counter.
+/// Borrowing the cell only compares the identifier of the owner, so it performs no atomic
+/// operations at all. Unlike a [`RwCell`], the cell has no guard-based borrows, so the two
+/// styles of access can never be mixed on the same cell. It may be converted into a [`RwCell`]
+/// once the owner is no longer needed.
+pub struct TokenCell<T> {
+    /// The identifier of the owner.
+    owner: usize,
+    /// The wrapped value.
+    value: UnsafeCell<T>,
+}
+
+impl<T> TokenCell<T> {
+    /// Creates a new cell that is owned by the given owner.
+    #[inline(always)]
+    pub const fn new(owner: &CellOwner, value: T) -> Self {
+        Self {
+            owner: owner.id,
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Consumes this cell, returning the wrapped value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    /// Consumes this cell, returning a [`RwCell`] with the same value, whose borrows are
+    /// counted instead of being controlled by an owner.
+    #[inline(always)]
+    pub fn into_cell(self) -> RwCell<T> {
+        RwCell::new(self.into_inner())
+    }
+
+    /// Gets a mutable reference to the value. This requires no owner, since the cell is
+    /// mutably borrowed.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// Determines whether this cell belongs to the given owner.
+    #[inline(always)]
+    pub fn is_owned_by(&self, owner: &CellOwner) -> bool {
+        self.owner == owner.id
+    }
+
+    /// Immutably borrows the value of this cell for as long as the owner is immutably borrowed.
+    /// Aborts if the cell belongs to a different owner.
+    #[inline(always)]
+    pub fn borrow_with<'o>(&'o self, owner: &'o CellOwner) -> &'o T {
+        self.check_owner(owner);
+        unsafe { &*self.value.get() }
+    }
+
+    /// Mutably borrows the value of this cell for as long as the owner is mutably borrowed.
+    /// Aborts if the cell belongs to a different owner.
+    #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
+    pub fn borrow_mut_with<'o>(&'o self, owner: &'o mut CellOwner) -> &'o mut T {
+        self.check_owner(owner);
+        unsafe { &mut *self.value.get() }
+    }
+
+    /// Aborts if this cell does not belong to the given owner.
+    #[inline(always)]
+    fn check_owner(&self, owner: &CellOwner) {
+        if !self.is_owned_by(owner) {
+            AbortPanic::abort("Attempted to borrow token cell with an owner that did not create it.");
+        }
+    }
+}
+
+impl<T> fmt::Debug for TokenCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenCell").field("owner", &self.owner).finish_non_exhaustive()
+    }
+}
+
+unsafe impl<T: Send + Sync> Sync for TokenCell<T> {}
@@ -0,0 +1,98 @@
+use crate::*;
+
+/// A cell that carries a name, which is included in the abort message when one of its borrows
+/// conflicts with another. This makes it possible to tell which cell caused the abort in
+/// programs that contain many of them. The name costs a pointer-sized field, so cells that
+/// do not need one should use a plain [`RwCell`].
+///
+/// The cell dereferences to a [`RwCell`], which provides the rest of its API. Only the methods
+/// defined directly on this type report the name when they abort.
+#[derive(Debug, Default)]
+pub struct TrackedRwCell<T> {
+    /// The underlying cell.
+    cell: RwCell<T>,
+    /// The name of the cell, if any.
+    name: Option<&'static str>,
+}
+
+impl<T> TrackedRwCell<T> {
+    /// Creates a new cell without a name that wraps the provided value.
+    #[inline(always)]
+    pub const fn new(value: T) -> Self {
+        Self {
+            cell: RwCell::new(value),
+            name: None,
+        }
+    }
+
+    /// Creates a new cell with the given name that wraps the provided value.
+    #[inline(always)]
+    pub const fn new_named(value: T, name: &'static str) -> Self {
+        Self {
+            cell: RwCell::new(value),
+            name: Some(name),
+        }
+    }
+
+    /// Gets the name of this cell, if it has one.
+    #[inline(always)]
+    pub fn name(&self) -> Option<&'static str> {
+        self.name
+    }
+
+    /// Consumes this cell, returning the wrapped value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.cell.into_inner()
+    }
+
+    /// Consumes this cell, returning the underlying cell without its name.
+    #[inline(always)]
+    pub fn into_cell(self) -> RwCell<T> {
+        self.cell
+    }
+
+    /// Immutably borrows the value of this cell. Aborts with the name of the cell if it is
+    /// mutably borrowed.
+    #[inline(always)]
+    pub fn borrow(&self) -> RwCellGuard<'_, Const, T> {
+        match self.cell.try_borrow() {
+            Ok(guard) => guard,
+            Err(_) => AbortPanic::abort_named("Attempted to immutably borrow cell while it was mutably borrowed.", self.name),
+        }
+    }
+
+    /// Mutably borrows the value of this cell. Aborts with the name of the cell if any other
+    /// borrows exist.
+    #[inline(always)]
+    pub fn borrow_mut(&self) -> RwCellGuard<'_, Mut, T> {
+        match self.cell.try_borrow_mut() {
+            Ok(guard) => guard,
+            Err(BorrowMutError::Frozen) => AbortPanic::abort_named("Attempted to mutably borrow cell after it was frozen.", self.name),
+            Err(_) => AbortPanic::abort_named("Attempted to mutably borrow cell while other borrows already existed.", self.name),
+        }
+    }
+}
+
+impl<T> Deref for TrackedRwCell<T> {
+    type Target = RwCell<T>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.cell
+    }
+}
+
+impl<T> DerefMut for TrackedRwCell<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.cell
+    }
+}
+
+impl<T> From<T> for TrackedRwCell<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "alloc")]
+
+use std::sync::atomic::*;
+use std::sync::Arc;
+use sync_rw_cell::*;
+
+/// Records whether it has been dropped.
+struct DropFlag(Arc<AtomicBool>);
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Asserts at compile time that a type is [`Send`] and [`Sync`].
+fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+#[test]
+fn owned_guard_outlives_last_handle() {
+    let dropped = Arc::new(AtomicBool::new(false));
+    let cell = ArcRwCell::new(DropFlag(dropped.clone()));
+    let weak = ArcRwCell::downgrade(&cell);
+    let guard = cell.borrow_owned();
+    drop(cell);
+
+    assert!(!dropped.load(Ordering::SeqCst));
+    let cell = weak.upgrade().unwrap();
+    assert_eq!(cell.reader_count(), 1);
+    drop(cell);
+
+    drop(guard);
+    assert!(dropped.load(Ordering::SeqCst));
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn owned_guard_releases_borrow_before_handle() {
+    let cell = ArcRwCell::new(1);
+    let guard = cell.borrow_mut_owned();
+    let other = OwnedRwCellGuard::cell(&guard);
+    assert!(other.is_locked_exclusive());
+
+    drop(guard);
+    assert!(other.free());
+    drop(cell);
+    assert_eq!(ArcRwCell::try_unwrap(other).ok(), Some(1));
+}
+
+#[test]
+fn owned_guards_move_between_threads() {
+    let cell = ArcRwCell::new(vec![1, 2]);
+    let read = cell.borrow_owned();
+    assert_send_sync(&read);
+    let read = std::thread::spawn(move || read[0]).join().unwrap();
+    assert_eq!(read, 1);
+
+    let mut write = cell.borrow_mut_owned();
+    assert_send_sync(&write);
+    write.push(3);
+    std::thread::spawn(move || write.push(4)).join().unwrap();
+    assert_eq!(*cell.borrow(), [1, 2, 3, 4]);
+    assert!(cell.free());
+}
+
+#[test]
+fn try_unwrap_returns_same_handle_with_leaked_owned_guard() {
+    let cell = ArcRwCell::new(1);
+    let weak = ArcRwCell::downgrade(&cell);
+    std::mem::forget(cell.borrow_owned());
+
+    let cell = ArcRwCell::try_unwrap(cell).unwrap_err();
+    assert!(ArcRwCell::ptr_eq(&cell, &weak.upgrade().unwrap()));
+    assert_eq!(cell.reader_count(), 1);
+}
+
+#[test]
+fn try_unwrap_returns_same_handle_with_leaked_borrow() {
+    let cell = ArcRwCell::new(1);
+    let weak = ArcRwCell::downgrade(&cell);
+    std::mem::forget(cell.borrow_mut());
+
+    let cell = ArcRwCell::try_unwrap(cell).unwrap_err();
+    assert!(ArcRwCell::ptr_eq(&cell, &weak.upgrade().unwrap()));
+    assert!(cell.is_locked_exclusive());
+}
+
+#[test]
+fn try_unwrap_fails_with_other_handles() {
+    let cell = ArcRwCell::new(1);
+    let other = cell.clone();
+    let cell = ArcRwCell::try_unwrap(cell).unwrap_err();
+    assert!(ArcRwCell::ptr_eq(&cell, &other));
+
+    drop(other);
+    assert_eq!(ArcRwCell::try_unwrap(cell).ok(), Some(1));
+}
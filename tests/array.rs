@@ -0,0 +1,98 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use sync_rw_cell::*;
+
+#[test]
+fn elements_add_only_a_counter() {
+    let per_element = size_of::<RwCellArray<u8, 64>>() - size_of::<RwCellArray<u8, 0>>();
+    assert_eq!(per_element, 64 * (size_of::<u8>() + size_of::<u16>()));
+}
+
+#[test]
+fn borrows_conflict_only_for_the_same_element() {
+    let array = RwCellArray::new([1, 2, 3]);
+    let mut first = array.borrow_element_mut(0);
+    let second = array.borrow_element(1);
+    *first = 4;
+
+    assert_eq!(array.try_borrow_element(0).err(), Some(BorrowError));
+    assert_eq!(array.try_borrow_element_mut(0).err(), Some(BorrowMutError::Writing));
+    assert_eq!(array.try_borrow_element_mut(1).err(), Some(BorrowMutError::Reading));
+    assert_eq!(*array.try_borrow_element(1).unwrap(), *second);
+    assert!(array.try_borrow_all_mut().is_err());
+    drop(first);
+
+    assert_eq!(*array.borrow_element(0), 4);
+    assert!(array.try_borrow_element_mut(2).is_ok());
+    drop(second);
+    assert_eq!(*array.borrow_all_mut(), [4, 2, 3]);
+}
+
+#[test]
+fn failed_borrow_of_all_elements_releases_acquired_elements() {
+    let array = RwCellArray::new([1, 2, 3]);
+    let last = array.borrow_element(2);
+    assert_eq!(array.try_borrow_all_mut().err(), Some(BorrowMutError::Reading));
+    assert!(array.try_borrow_element_mut(0).is_ok());
+    assert!(array.try_borrow_element_mut(1).is_ok());
+    drop(last);
+
+    let mut all = array.borrow_all_mut();
+    all[2] = 4;
+    assert!(array.try_borrow_element(0).is_err());
+    drop(all);
+    assert_eq!(*array.borrow_element(2), 4);
+}
+
+#[test]
+fn mapped_element_guards_release_on_panic() {
+    let array = RwCellArray::new([(1, 2), (3, 4)]);
+    let mut second = RwCellElementGuard::<Mut, _>::map(array.borrow_element_mut(1), |(_, second)| second);
+    *second = 5;
+    drop(second);
+
+    let result = catch_unwind(AssertUnwindSafe(|| RwCellElementGuard::<Mut, _>::map(array.borrow_element_mut(0), |_| -> &mut i32 { panic!() })));
+    assert!(result.is_err());
+    assert_eq!(*RwCellElementGuard::<Const, _>::map(array.borrow_element(1), |(_, second)| second), 5);
+    assert!(array.try_borrow_all_mut().is_ok());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn waiting_borrows_share_one_queue() {
+    let array = RwCellArray::new([0, 0]);
+    std::thread::scope(|scope| {
+        let first = array.borrow_element_mut(0);
+        let second = array.borrow_element(1);
+        let writer = scope.spawn(|| *array.borrow_element_mut_wait(1) += 1);
+        let reader = scope.spawn(|| *array.borrow_element_wait(0));
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        drop(second);
+        writer.join().unwrap();
+        drop(first);
+        assert_eq!(reader.join().unwrap(), 0);
+    });
+
+    assert_eq!(*array.borrow_all_mut(), [0, 1]);
+}
+
+#[test]
+fn threads_hold_different_elements_mutably_at_once() {
+    let array = RwCellArray::new([0, 0]);
+    let barrier = std::sync::Barrier::new(2);
+    std::thread::scope(|scope| {
+        for index in 0..2 {
+            let (array, barrier) = (&array, &barrier);
+            scope.spawn(move || {
+                let mut guard = array.borrow_element_mut(index);
+                *guard = index + 1;
+                barrier.wait();
+                assert_eq!(array.try_borrow_element_mut(index).err(), Some(BorrowMutError::Writing));
+                assert!(array.try_borrow_element(index).is_err());
+                barrier.wait();
+            });
+        }
+    });
+
+    assert_eq!(*array.borrow_all_mut(), [1, 2]);
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "alloc")]
+
+use std::future::Future;
+use std::pin::pin;
+use std::sync::atomic::*;
+use std::sync::Arc;
+use std::task::*;
+use sync_rw_cell::*;
+
+/// Counts the number of times that it is woken.
+#[derive(Default)]
+struct CountingWaker(AtomicUsize);
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn dropping_sync_guard_wakes_pending_write() {
+    let cell = AsyncRwCell::new(0);
+    let counter = Arc::new(CountingWaker::default());
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    let guard = cell.try_write().unwrap();
+    let mut write = pin!(cell.write());
+    assert!(write.as_mut().poll(&mut cx).is_pending());
+
+    drop(guard);
+    assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+    match write.as_mut().poll(&mut cx) {
+        Poll::Ready(mut guard) => *guard = 1,
+        Poll::Pending => panic!("write was not acquired after the cell was released"),
+    }
+    assert!(cell.free());
+    assert_eq!(*cell.try_read().unwrap(), 1);
+}
+
+#[test]
+fn every_mutable_release_is_a_change() {
+    let cell = Arc::new(AsyncRwCell::new((0, 0)));
+    let counter = Arc::new(CountingWaker::default());
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+    let mut subscriber = cell.subscribe();
+
+    let writes: [&dyn Fn(); 4] = [
+        &|| drop(cell.try_write().unwrap()),
+        &|| drop(AsyncWriteGuard::map(cell.try_write().unwrap(), |(a, _)| a)),
+        &|| drop(AsyncWriteGuard::downgrade(cell.try_write().unwrap())),
+        &|| drop(cell.try_write_owned().unwrap()),
+    ];
+
+    for (i, write) in writes.iter().enumerate() {
+        {
+            let mut changed = pin!(subscriber.changed());
+            assert!(changed.as_mut().poll(&mut cx).is_pending());
+            write();
+            assert_eq!(counter.0.load(Ordering::SeqCst), i + 1);
+            assert!(changed.as_mut().poll(&mut cx).is_ready());
+        }
+        assert!(!subscriber.has_changed());
+    }
+
+    drop(cell.try_read().unwrap());
+    assert!(!subscriber.has_changed());
+}
@@ -0,0 +1,99 @@
+use std::sync::atomic::AtomicU32;
+use std::thread;
+use sync_rw_cell::*;
+
+/// A value that is stored in an atomic integer wider than its alignment.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+struct Pair(u16, u16);
+
+unsafe impl AtomicValue for Pair {
+    type Storage = AtomicU32;
+}
+
+#[test]
+fn lock_free_cells_are_the_size_of_their_value() {
+    assert!(AtomicRwCell::<u32>::is_lock_free());
+    assert!(AtomicRwCell::<Pair>::is_lock_free());
+    assert!(!AtomicRwCell::<[u8; 3]>::is_lock_free());
+    assert!(!AtomicRwCell::<u128>::is_lock_free());
+    assert_eq!(size_of::<AtomicRwCell<u8>>(), 1);
+    assert_eq!(size_of::<AtomicRwCell<u32>>(), 4);
+    assert_eq!(size_of::<AtomicRwCell<Pair>>(), 4);
+    assert_eq!(align_of::<AtomicRwCell<Pair>>(), 4);
+}
+
+#[test]
+fn lock_free_operations() {
+    let cell = AtomicRwCell::new(1u32);
+    assert_eq!(cell.swap(2), 1);
+    assert_eq!(cell.compare_exchange(1, 3), Err(2));
+    assert_eq!(cell.compare_exchange(2, 3), Ok(2));
+    assert_eq!(cell.fetch_update(|value| (value < 10).then_some(value * 2)), Ok(3));
+    assert_eq!(cell.fetch_update(|_| None), Err(6));
+    cell.set(7);
+    assert_eq!(cell.into_inner(), 7);
+
+    let pair = AtomicRwCell::new(Pair(1, 2));
+    assert_eq!(pair.swap(Pair(3, 4)), Pair(1, 2));
+    assert_eq!(pair.get(), Pair(3, 4));
+}
+
+#[test]
+fn locking_operations() {
+    let mut cell = AtomicRwCell::new([1u8, 2, 3]);
+    assert_eq!(cell.swap([4, 5, 6]), [1, 2, 3]);
+    assert_eq!(cell.compare_exchange([1, 2, 3], [0; 3]), Err([4, 5, 6]));
+    assert_eq!(cell.compare_exchange([4, 5, 6], [7, 8, 9]), Ok([4, 5, 6]));
+    assert_eq!(cell.fetch_update(|value| Some(value.map(|x| x + 1))), Ok([7, 8, 9]));
+    cell.get_mut()[0] = 0;
+    assert_eq!(cell.get(), [0, 9, 10]);
+
+    let cell = AtomicRwCell::new(u128::MAX);
+    assert_eq!(cell.swap(0), u128::MAX);
+    assert_eq!(cell.fetch_update(|value| Some(value + 1)), Ok(0));
+    assert_eq!(cell.into_inner(), 1);
+}
+
+#[test]
+fn compare_exchange_is_bitwise() {
+    let cell = AtomicRwCell::new(0.0f32);
+    assert_eq!(cell.compare_exchange(-0.0, 1.0), Err(0.0));
+    cell.set(f32::NAN);
+    assert!(cell.compare_exchange(f32::NAN, 1.0).unwrap().is_nan());
+    assert_eq!(cell.get(), 1.0);
+
+    let cell = AtomicRwCell::new([0.0f32; 3]);
+    assert!(cell.compare_exchange([-0.0; 3], [1.0; 3]).is_err());
+    cell.set([f32::NAN; 3]);
+    assert!(cell.compare_exchange([f32::NAN; 3], [1.0; 3]).is_ok());
+    assert_eq!(cell.get(), [1.0; 3]);
+}
+
+/// Increments a counter from several threads with [`AtomicRwCell::fetch_update`], and checks
+/// that no update is lost.
+fn concurrent_updates<T: AtomicValue + Send + std::fmt::Debug>(zero: T, increment: fn(T) -> T, count: fn(T) -> usize) {
+    const THREADS: usize = 4;
+    const UPDATES: usize = 1_000;
+
+    let cell = AtomicRwCell::new(zero);
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|| {
+                for _ in 0..UPDATES {
+                    cell.fetch_update(|value| Some(increment(value))).unwrap();
+                }
+            });
+        }
+    });
+    assert_eq!(count(cell.into_inner()), THREADS * UPDATES);
+}
+
+#[test]
+fn concurrent_updates_are_not_lost() {
+    concurrent_updates(0usize, |value| value + 1, |value| value);
+    concurrent_updates([0u16; 3], |value| value.map(|x| x + 1), |value| {
+        assert!(value.iter().all(|&x| x == value[0]));
+        value[0] as usize
+    });
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "std")]
+
+use std::sync::atomic::*;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use sync_rw_cell::*;
+
+#[test]
+fn readers_do_not_starve_waiting_writer() {
+    let cell = BlockingRwCell::new_fair(0);
+    let stop = AtomicBool::new(false);
+    let reads_while_waiting = AtomicUsize::new(0);
+
+    thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                while !stop.load(Ordering::Relaxed) {
+                    let guard = cell.borrow();
+                    thread::sleep(Duration::from_micros(200));
+                    if cell.waiting_writers() != 0 {
+                        reads_while_waiting.fetch_add(1, Ordering::Relaxed);
+                    }
+                    drop(guard);
+                }
+            });
+        }
+
+        thread::sleep(Duration::from_millis(20));
+        let (sender, receiver) = mpsc::channel();
+        let (cell, stop) = (&cell, &stop);
+        s.spawn(move || {
+            let _stop = StopOnDrop(stop);
+            *cell.borrow_mut() = 1;
+            sender.send(()).unwrap();
+        });
+
+        let written = receiver.recv_timeout(Duration::from_secs(10));
+        stop.store(true, Ordering::Relaxed);
+        assert!(written.is_ok(), "the writer was starved by readers");
+    });
+
+    assert_eq!(*cell.borrow(), 1);
+    assert!(reads_while_waiting.load(Ordering::Relaxed) <= 4);
+}
+
+#[test]
+fn try_borrow_waits_behind_writer() {
+    let cell = BlockingRwCell::new_fair(0);
+    let read = cell.borrow();
+    thread::scope(|s| {
+        let writer = s.spawn(|| *cell.borrow_mut() = 1);
+        while cell.waiting_writers() == 0 {
+            thread::yield_now();
+        }
+
+        assert!(cell.try_borrow().is_err());
+        drop(read);
+        writer.join().unwrap();
+    });
+
+    assert_eq!(*cell.try_borrow().unwrap(), 1);
+}
+
+/// Sets a flag when dropped, so that the other threads stop even if the owner panics.
+struct StopOnDrop<'a>(&'a AtomicBool);
+
+impl Drop for StopOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
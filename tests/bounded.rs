@@ -0,0 +1,31 @@
+use sync_rw_cell::*;
+
+#[test]
+fn cloning_respects_reader_limit() {
+    let cell = BoundedRwCell::with_max_readers(5, 3);
+    let first = cell.borrow();
+    let second = BoundedRwCellGuard::clone(&first);
+    let third = BoundedRwCellGuard::try_clone(&second).unwrap();
+    assert_eq!(cell.reader_count(), 3);
+
+    assert!(BoundedRwCellGuard::try_clone(&first).is_err());
+    assert!(cell.try_borrow().is_err());
+    assert_eq!(cell.reader_count(), 3);
+
+    drop(third);
+    let third = BoundedRwCellGuard::try_clone(&first).unwrap();
+    assert_eq!((*first, *second, *third), (5, 5, 5));
+    drop((first, second, third));
+    assert!(cell.try_borrow_mut().is_ok());
+}
+
+#[test]
+fn downgraded_guard_respects_reader_limit() {
+    let cell = BoundedRwCell::with_max_readers((1, 2), 1);
+    let guard = BoundedRwCellGuard::downgrade(cell.borrow_mut());
+    assert!(BoundedRwCellGuard::try_clone(&guard).is_err());
+
+    let guard = BoundedRwCellGuard::<Const, _>::map(guard, |(_, b)| b);
+    assert!(BoundedRwCellGuard::try_clone(&guard).is_err());
+    assert_eq!(*guard, 2);
+}
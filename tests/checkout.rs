@@ -0,0 +1,63 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use sync_rw_cell::*;
+
+mod common;
+
+/// A value whose default panics.
+#[derive(Debug, PartialEq)]
+struct NoDefault(i32);
+
+impl Default for NoDefault {
+    fn default() -> Self {
+        panic!("no default value");
+    }
+}
+
+#[test]
+fn checkin_releases_borrow() {
+    let cell = RwCell::<_, PanicUnwind>::with_policy(1);
+    let (value, reservation) = cell.checkout();
+    assert!(cell.is_locked_exclusive());
+    assert!(catch_unwind(AssertUnwindSafe(|| cell.borrow())).is_err());
+
+    reservation.checkin(value + 1);
+    assert!(cell.free());
+    assert_eq!(*cell.borrow(), 2);
+}
+
+#[test]
+fn dropped_reservation_restores_default() {
+    let cell = RwCell::new(vec![1]);
+    let (value, reservation) = cell.checkout_or_default();
+    drop(reservation);
+    assert_eq!(value, [1]);
+    assert!(cell.free());
+    assert!(cell.borrow().is_empty());
+}
+
+#[test]
+fn dropped_reservation_restores_default_while_unwinding() {
+    let cell = RwCell::new(vec![1]);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let (_value, _reservation) = cell.checkout_or_default();
+        panic!("work failed");
+    }));
+    assert!(result.is_err());
+    assert!(cell.free());
+    assert!(cell.borrow().is_empty());
+}
+
+#[test]
+fn panicking_restore_aborts() {
+    let output = common::expect_abort("panicking_restore_aborts", || {
+        let cell = RwCell::new(NoDefault(1));
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let (_value, reservation) = cell.checkout_or_default();
+            drop(reservation);
+        }));
+        println!("restore unwound: {result:?}, cell: {:?}", cell.borrow_state());
+    });
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("restore unwound"));
+    assert!(stderr.contains("Failed to restore the value of a cell"), "unexpected output: {stderr}");
+}
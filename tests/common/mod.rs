@@ -0,0 +1,23 @@
+use std::process::{Command, Output};
+
+/// The environment variable that makes a test run the part that is expected to abort.
+const ABORT_VAR: &str = "SYNC_RW_CELL_EXPECT_ABORT";
+
+/// Runs `child` in a copy of the test process, which reruns only the test named `test`, and
+/// returns the output of the copy. The copy calls `child` instead of spawning another copy,
+/// and exits successfully if `child` returns, so the program can abort without taking the
+/// test harness with it. Panics if the copy does not fail.
+pub fn expect_abort(test: &str, child: impl FnOnce()) -> Output {
+    if std::env::var_os(ABORT_VAR).is_some() {
+        child();
+        std::process::exit(0);
+    }
+
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args([test, "--exact", "--nocapture"])
+        .env(ABORT_VAR, "1")
+        .output()
+        .unwrap();
+    assert!(!output.status.success(), "{test} did not abort: {}", String::from_utf8_lossy(&output.stderr));
+    output
+}
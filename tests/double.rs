@@ -0,0 +1,60 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+use sync_rw_cell::*;
+
+#[test]
+fn write_guard_publishes_back_buffer_on_drop() {
+    let cell = DoubleBufferedCell::from_buffers(1, 0);
+    let mut guard = cell.write();
+    assert_eq!(*DoubleBufferedWriteGuard::front(&guard), 1);
+    *guard = 2;
+    assert_eq!(*cell.read(), 1);
+    DoubleBufferedWriteGuard::publish(guard);
+
+    assert_eq!(*cell.read(), 2);
+    assert_eq!(cell.into_buffers(), (2, 1));
+}
+
+#[test]
+fn failed_try_write_allows_later_writers() {
+    let cell = DoubleBufferedCell::new(0);
+    let guard = cell.write();
+    assert_eq!(cell.try_write().err(), Some(BorrowMutError::Writing));
+    drop(guard);
+
+    let reader = cell.read();
+    *cell.write() = 1;
+    assert_eq!(cell.try_write().err(), Some(BorrowMutError::Reading));
+    assert_eq!(cell.try_write().err(), Some(BorrowMutError::Reading));
+    drop(reader);
+
+    *cell.try_write().unwrap() = 2;
+    assert_eq!(*cell.read(), 2);
+}
+
+#[test]
+fn writer_waits_for_reader_of_previous_front() {
+    let cell = DoubleBufferedCell::new(0);
+    let done = AtomicBool::new(false);
+    let reader = cell.read();
+    *cell.write() = 1;
+
+    thread::scope(|scope| {
+        let writer = scope.spawn(|| {
+            *cell.write() = 2;
+            done.store(true, Ordering::SeqCst);
+        });
+
+        thread::sleep(Duration::from_millis(50));
+        assert!(!done.load(Ordering::SeqCst));
+        assert_eq!(*reader, 0);
+        assert_eq!(*cell.read(), 1);
+        drop(reader);
+
+        writer.join().unwrap();
+    });
+
+    assert!(done.load(Ordering::SeqCst));
+    assert_eq!(*cell.read(), 2);
+}
@@ -0,0 +1,27 @@
+use sync_rw_cell::*;
+
+mod common;
+
+#[test]
+fn field_reader_count_saturates_independently() {
+    let cell = RwCell2::new(1, 2);
+    let guards: Vec<_> = (0..254).map(|_| cell.borrow_field::<0>()).collect();
+    assert!(cell.try_borrow_field::<0>().is_err());
+    assert_eq!(*cell.borrow_field::<1>(), 2);
+    *cell.borrow_field_mut::<1>() = 3;
+    drop(guards);
+
+    assert_eq!(*cell.try_borrow_field::<0>().unwrap(), 1);
+    assert_eq!(cell.into_inner(), (1, 3));
+}
+
+#[test]
+fn saturated_field_aborts_with_its_own_message() {
+    let output = common::expect_abort("saturated_field_aborts_with_its_own_message", || {
+        let cell = RwCell2::new(1, 2);
+        let _guards: Vec<_> = (0..255).map(|_| cell.borrow_field::<0>()).collect();
+    });
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("more than 254 times"), "unexpected output: {stderr}");
+    assert!(!stderr.contains("mutably borrowed"), "unexpected output: {stderr}");
+}
@@ -0,0 +1,34 @@
+use sync_rw_cell::*;
+
+#[test]
+fn read_handle_guards_cannot_be_upgraded() {
+    let cell = RwCell::new((1, String::from("a")));
+    let (read, write) = cell.split_handles();
+    let guard = read.borrow();
+    assert_eq!(cell.borrow_state(), BorrowState::Reading(1));
+    let guard = RwCellGuard::try_upgrade(guard).unwrap_err();
+    assert_eq!(guard.0, 1);
+    drop(guard);
+
+    let name = read.clone().map(|value| &value.1);
+    let guard = RwCellGuard::try_upgrade(name.borrow()).unwrap_err();
+    assert_eq!(*guard, "a");
+    assert_eq!(write.try_borrow_mut().err(), Some(BorrowMutError::Reading));
+    drop(guard);
+
+    assert!(RwCellGuard::try_upgrade(cell.borrow()).is_ok());
+}
+
+#[test]
+fn write_handles_borrow_their_projection() {
+    let cell = RwCell::new((1, String::from("a")));
+    let (read, write) = cell.split_handles();
+    let name = write.map(|value| &mut value.1);
+    name.borrow_mut().push('b');
+    assert_eq!(cell.borrow_state(), BorrowState::Free);
+
+    let guard = name.borrow_mut();
+    assert_eq!(read.try_borrow().err(), Some(BorrowError));
+    drop(guard);
+    assert_eq!(*read.borrow(), (1, String::from("ab")));
+}
@@ -0,0 +1,58 @@
+use sync_rw_cell::*;
+
+#[test]
+fn upgradable_borrow_is_not_a_pending_write() {
+    let cell = RwCell::new(1);
+    let upgradable = cell.borrow_upgradable();
+    assert!(!cell.is_write_pending());
+    assert_eq!(cell.try_reserve_write().err(), Some(BorrowMutError::Upgradable));
+    drop(upgradable);
+
+    let intent = cell.reserve_write();
+    assert!(cell.is_write_pending());
+    assert_eq!(cell.try_borrow_upgradable().err(), Some(BorrowMutError::Reserved));
+    assert_eq!(cell.try_reserve_write().err(), Some(BorrowMutError::Reserved));
+    assert_eq!(cell.try_borrow_mut().err(), Some(BorrowMutError::Reserved));
+    assert_eq!(cell.freeze(), Err(BorrowMutError::Reserved));
+    drop(intent);
+
+    assert!(!cell.is_write_pending());
+    assert!(cell.free());
+}
+
+#[test]
+fn intent_waits_for_readers_before_acquiring() {
+    let cell = RwCell::new(1);
+    let reader = cell.borrow();
+    let intent = cell.reserve_write();
+    let other = cell.borrow();
+    assert_eq!(intent.readers(), 2);
+    assert_eq!(cell.borrow_state(), BorrowState::Reading(3));
+
+    let intent = intent.try_acquire().unwrap_err();
+    drop((reader, other));
+    assert_eq!(intent.readers(), 0);
+
+    let mut guard = intent.try_acquire().unwrap();
+    *guard = 2;
+    assert!(!cell.is_write_pending());
+    assert!(cell.is_locked_exclusive());
+    drop(guard);
+    assert_eq!(*cell.borrow(), 2);
+    assert!(cell.free());
+}
+
+#[test]
+fn blocking_acquire_proceeds_once_readers_leave() {
+    let cell = RwCell::new(0);
+    std::thread::scope(|scope| {
+        let reader = cell.borrow();
+        let intent = cell.reserve_write();
+        let writer = scope.spawn(move || *intent.acquire_blocking() += 1);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        assert!(cell.is_write_pending());
+        drop(reader);
+        writer.join().unwrap();
+    });
+    assert_eq!(*cell.borrow(), 1);
+}
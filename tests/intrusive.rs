@@ -0,0 +1,44 @@
+use std::future::Future;
+use std::pin::pin;
+use std::sync::atomic::*;
+use std::sync::Arc;
+use std::task::*;
+use sync_rw_cell::*;
+
+/// Counts the number of times that it is woken.
+#[derive(Default)]
+struct CountingWaker(AtomicUsize);
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn dropping_sync_guard_wakes_linked_waiters() {
+    let cell = IntrusiveRwCell::new(0);
+    let counter = Arc::new(CountingWaker::default());
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    let guard = cell.try_write().unwrap();
+    let mut read = pin!(cell.read());
+    let mut write = pin!(cell.write());
+    assert!(read.as_mut().poll(&mut cx).is_pending());
+    assert!(write.as_mut().poll(&mut cx).is_pending());
+
+    drop(guard);
+    assert!(counter.0.load(Ordering::SeqCst) >= 1);
+    let read = match read.as_mut().poll(&mut cx) {
+        Poll::Ready(guard) => guard,
+        Poll::Pending => panic!("read was not acquired after the cell was released"),
+    };
+    assert!(write.as_mut().poll(&mut cx).is_pending());
+
+    let woken = counter.0.load(Ordering::SeqCst);
+    drop(read);
+    assert!(counter.0.load(Ordering::SeqCst) > woken);
+    assert!(write.as_mut().poll(&mut cx).is_ready());
+    assert!(cell.free());
+}
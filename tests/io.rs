@@ -0,0 +1,37 @@
+#![cfg(feature = "std")]
+
+use std::io::{BufRead, Cursor, Seek, SeekFrom};
+use sync_rw_cell::*;
+
+#[test]
+fn io_copy_between_guards() {
+    let source = RwCell::new(Cursor::new(b"hello world".to_vec()));
+    let sink = RwCell::new(Vec::new());
+
+    let copied = std::io::copy(&mut source.borrow_mut(), &mut sink.borrow_mut()).unwrap();
+    assert_eq!(copied, 11);
+    assert_eq!(*sink.borrow(), b"hello world");
+    assert!(source.free() && sink.free());
+}
+
+#[test]
+fn io_copy_from_immutable_guard() {
+    let source = RwCell::new(std::fs::File::open(file!()).unwrap());
+    let mut sink = Vec::new();
+
+    std::io::copy(&mut source.borrow(), &mut sink).unwrap();
+    assert_eq!(sink, std::fs::read(file!()).unwrap());
+    assert!(source.free());
+}
+
+#[test]
+fn seek_and_buf_read_through_guard() {
+    let cell = RwCell::new(Cursor::new(b"first\nsecond\n".to_vec()));
+    let mut guard = cell.borrow_mut();
+    guard.seek(SeekFrom::Start(6)).unwrap();
+
+    let mut line = String::new();
+    guard.read_line(&mut line).unwrap();
+    assert_eq!(line, "second\n");
+    assert_eq!(guard.position(), 13);
+}
@@ -0,0 +1,40 @@
+use std::cell::Cell;
+use sync_rw_cell::*;
+
+thread_local! {
+    /// Whether the current thread is inside a critical section.
+    static IN_SECTION: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Records whether code runs inside it.
+struct FlagSection;
+
+impl CriticalSection for FlagSection {
+    fn with<R>(f: impl FnOnce() -> R) -> R {
+        IN_SECTION.set(true);
+        let result = f();
+        IN_SECTION.set(false);
+        result
+    }
+}
+
+#[test]
+fn closures_run_inside_critical_section() {
+    let cell = IrqRwCell::<_, FlagSection>::new(1);
+    assert_eq!(cell.with(|value| (*value, IN_SECTION.get())), Ok((1, true)));
+    cell.with_mut(|value| *value = 2).unwrap();
+    assert!(!IN_SECTION.get());
+
+    let guard = cell.try_borrow().unwrap();
+    assert_eq!(cell.with_mut(|_| ()), Err(BorrowMutError::Reading));
+    assert_eq!(cell.with(|value| *value), Ok(2));
+    drop(guard);
+}
+
+#[cfg(feature = "critical-section")]
+#[test]
+fn global_critical_section_forwards_to_crate() {
+    let cell = IrqRwCell::<_, GlobalCriticalSection>::new(1);
+    cell.with_mut(|value| *value += 1).unwrap();
+    assert_eq!(cell.with(|value| *value), Ok(2));
+}
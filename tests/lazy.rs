@@ -0,0 +1,55 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use sync_rw_cell::*;
+
+#[test]
+fn initializes_on_first_access() {
+    let runs = AtomicUsize::new(0);
+    let cell = LazyRwCell::new(|| {
+        runs.fetch_add(1, Ordering::SeqCst);
+        vec![1]
+    });
+    assert_eq!(runs.load(Ordering::SeqCst), 0);
+
+    cell.borrow_mut().push(2);
+    assert_eq!(*LazyRwCell::force(&cell).borrow(), [1, 2]);
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    assert_eq!(LazyRwCell::into_inner(cell).ok(), Some(vec![1, 2]));
+}
+
+#[test]
+fn initializer_runs_once_across_threads() {
+    static RUNS: AtomicUsize = AtomicUsize::new(0);
+    static CELL: LazyRwCell<usize> = LazyRwCell::new(|| RUNS.fetch_add(1, Ordering::SeqCst) + 1);
+
+    // Force the cell first, since concurrent first accesses conflict with the initializer.
+    assert_eq!(*CELL.borrow(), 1);
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| assert_eq!(*CELL.borrow(), 1));
+        }
+    });
+    assert_eq!(RUNS.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn into_inner_returns_unused_initializer() {
+    let cell = LazyRwCell::new(|| 1);
+    let init = LazyRwCell::into_inner(cell).unwrap_err();
+    assert_eq!(init(), 1);
+}
+
+#[test]
+fn unwinding_initializer_is_not_rerun() {
+    let cell = LazyRwCell::new(|| -> i32 { panic!("initializer failed") });
+    let result = catch_unwind(AssertUnwindSafe(|| *cell.borrow()));
+    assert!(result.is_err());
+
+    let result = catch_unwind(AssertUnwindSafe(|| LazyRwCell::force(&cell).borrow_state()));
+    let message = result.unwrap_err();
+    assert_eq!(message.downcast_ref::<String>().map(String::as_str), Some("Initializer of lazy cell previously panicked."));
+
+    let result = catch_unwind(AssertUnwindSafe(|| LazyRwCell::into_inner(cell)));
+    assert!(result.is_err());
+}
@@ -0,0 +1,45 @@
+use std::rc::Rc;
+use sync_rw_cell::*;
+
+#[test]
+fn put_and_take_hand_off_one_value() {
+    let mailbox = MailboxCell::new();
+    assert_eq!(mailbox.take(), None);
+    assert_eq!(mailbox.put(1), Ok(()));
+    assert!(mailbox.is_full());
+    assert_eq!(mailbox.put(2), Err(2));
+    assert_eq!(mailbox.take(), Some(1));
+    assert!(!mailbox.is_full());
+    assert_eq!(mailbox.take(), None);
+
+    assert_eq!(mailbox.put(3), Ok(()));
+    assert_eq!(mailbox.into_inner(), Some(3));
+}
+
+#[test]
+fn dropping_full_mailbox_drops_value() {
+    let value = Rc::new(());
+    drop(MailboxCell::with_value(value.clone()));
+    assert_eq!(Rc::strong_count(&value), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn recv_waits_for_put() {
+    use std::time::{Duration, Instant};
+
+    let mailbox = MailboxCell::new();
+    let start = Instant::now();
+    assert_eq!(mailbox.recv_timeout(Duration::from_millis(20)), None);
+    assert!(start.elapsed() >= Duration::from_millis(20));
+
+    std::thread::scope(|scope| {
+        let receiver = scope.spawn(|| (mailbox.recv(), mailbox.recv_timeout(Duration::from_secs(10))));
+        mailbox.put(1).unwrap();
+        while mailbox.is_full() {
+            std::thread::yield_now();
+        }
+        mailbox.put(2).unwrap();
+        assert_eq!(receiver.join().unwrap(), (1, Some(2)));
+    });
+}
@@ -0,0 +1,45 @@
+use sync_rw_cell::*;
+
+#[test]
+fn borrows_every_cell_at_once() {
+    let cells = [RwCell::new(1), RwCell::new(2), RwCell::new(3)];
+    let [mut a, mut b, c] = RwCell::borrow_many_mut([&cells[0], &cells[1], &cells[2]]).unwrap();
+    *a += *c;
+    *b += *c;
+    assert_eq!(cells[1].borrow_state(), BorrowState::Writing);
+    drop((a, b, c));
+    assert_eq!(cells.map(RwCell::into_inner), [4, 5, 3]);
+
+    let (number, text) = (RwCell::new(1), RwCell::new(String::new()));
+    let (mut number, mut text) = (&number, &text).borrow_many_mut().unwrap();
+    *number += 1;
+    text.push('a');
+}
+
+#[test]
+fn duplicate_cells_are_not_borrowed() {
+    let cell = RwCell::new(0);
+    let other = RwCell::new(1);
+    assert!(RwCell::borrow_many_mut([&cell, &other, &cell]).is_none());
+    assert!((&cell, &other, &cell).borrow_many_mut().is_none());
+    assert!([&other, &other].borrow_many_mut().is_none());
+
+    assert_eq!(cell.borrow_state(), BorrowState::Free);
+    assert_eq!(other.borrow_state(), BorrowState::Free);
+    assert!(cell.try_borrow_mut().is_ok());
+}
+
+#[test]
+fn busy_cell_releases_earlier_borrows() {
+    let cells = [RwCell::new(1), RwCell::new(2), RwCell::new(3)];
+    let middle = cells[1].borrow();
+    assert!(RwCell::borrow_many_mut([&cells[0], &cells[1], &cells[2]]).is_none());
+    assert!((&cells[0], &cells[1], &cells[2]).borrow_many_mut().is_none());
+    assert_eq!(cells[0].borrow_state(), BorrowState::Free);
+    assert_eq!(cells[1].borrow_state(), BorrowState::Reading(1));
+    assert_eq!(cells[2].borrow_state(), BorrowState::Free);
+    drop(middle);
+
+    let guards = RwCell::borrow_many_mut([&cells[0], &cells[1], &cells[2]]).unwrap();
+    assert_eq!(guards.map(|guard| *guard), [1, 2, 3]);
+}
@@ -0,0 +1,67 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use sync_rw_cell::*;
+
+#[test]
+fn set_initializes_only_once() {
+    let cell = OnceRwCell::new();
+    assert_eq!(cell.get(), None);
+    assert_eq!(cell.set(1), Ok(()));
+    assert_eq!(cell.set(2), Err(2));
+    assert_eq!(cell.get_or_init(|| 3), &1);
+    assert_eq!(cell.get(), Some(&1));
+}
+
+#[test]
+fn racing_initializers_run_once() {
+    const THREADS: usize = 4;
+
+    let cell = OnceRwCell::new();
+    let runs = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for i in 0..THREADS {
+            let (cell, runs) = (&cell, &runs);
+            scope.spawn(move || {
+                let value = cell.get_or_init(|| {
+                    runs.fetch_add(1, Ordering::SeqCst);
+                    thread::yield_now();
+                    i
+                });
+                assert_eq!(cell.get(), Some(value));
+            });
+        }
+    });
+
+    assert_eq!(runs.into_inner(), 1);
+    assert!(cell.into_inner().is_some_and(|value| value < THREADS));
+}
+
+#[test]
+fn unwinding_initializer_leaves_cell_empty() {
+    let cell = OnceRwCell::new();
+    let result = catch_unwind(AssertUnwindSafe(|| cell.get_or_init(|| panic!("initializer failed"))));
+    assert!(result.is_err());
+    assert_eq!(cell.get(), None);
+
+    assert_eq!(cell.get_or_init(|| 1), &1);
+    assert_eq!(cell.set(2), Err(2));
+}
+
+#[test]
+fn conversions_keep_the_value() {
+    let cell = OnceRwCell::<String>::new();
+    let cell = cell.into_rw_cell().unwrap_err();
+    assert_eq!(cell.into_inner(), None);
+
+    let cell = OnceRwCell::from(String::from("a"));
+    let cell = cell.into_rw_cell().unwrap();
+    cell.borrow_mut().push('b');
+    assert_eq!(cell.into_inner(), "ab");
+
+    let mut cell = OnceRwCell::new();
+    assert_eq!(cell.get_mut(), None);
+    cell.set(vec![1]).unwrap();
+    cell.get_mut().unwrap().push(2);
+    assert_eq!(cell.into_inner(), Some(vec![1, 2]));
+}
@@ -0,0 +1,43 @@
+#![cfg(feature = "std")]
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use sync_rw_cell::*;
+
+#[test]
+fn unwinding_write_poisons_until_cleared() {
+    let cell = PoisonRwCell::new(vec![1]);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let mut guard = cell.borrow_mut();
+        guard.push(2);
+        panic!("update failed");
+    }));
+    assert!(result.is_err());
+    assert!(cell.is_poisoned());
+    assert_eq!(cell.try_borrow().err(), Some(PoisonBorrowError::Poisoned));
+    assert!(matches!(cell.try_borrow_mut(), Err(PoisonBorrowError::Poisoned)));
+
+    cell.clear_poison();
+    assert!(!cell.is_poisoned());
+    assert_eq!(*cell.borrow(), [1, 2]);
+    cell.borrow_mut().push(3);
+    assert_eq!(cell.into_inner().unwrap(), [1, 2, 3]);
+}
+
+#[test]
+fn unwinding_read_does_not_poison() {
+    let mut cell = PoisonRwCell::new(1);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let _guard = cell.borrow();
+        panic!("read failed");
+    }));
+    assert!(result.is_err());
+    assert!(!cell.is_poisoned());
+    assert_eq!(cell.get_mut().copied().unwrap(), 1);
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let _guard = cell.borrow_mut();
+        panic!("update failed");
+    }));
+    assert!(result.is_err());
+    assert_eq!(cell.into_inner().unwrap_err().into_inner(), 1);
+}
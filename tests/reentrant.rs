@@ -0,0 +1,26 @@
+use sync_rw_cell::*;
+
+#[test]
+fn same_token_nests_and_other_tokens_conflict() {
+    let cell = ReentrantRwCell::new(0);
+    let mut token = AccessToken::new();
+    let mut other = AccessToken::new();
+
+    let mut outer = cell.borrow_mut_with(&mut token);
+    *outer = 1;
+    let mut inner = cell.try_borrow_mut_with(ReentrantGuard::token(&mut outer)).unwrap();
+    *inner += 1;
+    drop(inner);
+
+    assert_eq!(*outer, 2);
+    assert_eq!(cell.try_borrow_mut_with(&mut other).err(), Some(BorrowMutError::Writing));
+    assert!(cell.try_borrow().is_err());
+    assert_eq!(cell.borrow_state(), BorrowState::Writing);
+    drop(outer);
+
+    assert_eq!(cell.borrow_state(), BorrowState::Free);
+    *cell.borrow_mut_with(&mut other) += 1;
+    let reader = cell.borrow();
+    assert_eq!(cell.try_borrow_mut_with(&mut token).err(), Some(BorrowMutError::Reading));
+    assert_eq!(*reader, 3);
+}
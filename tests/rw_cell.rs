@@ -0,0 +1,593 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use sync_rw_cell::*;
+
+/// Determines whether `f` panics.
+fn panics<R>(f: impl FnOnce() -> R) -> bool {
+    catch_unwind(AssertUnwindSafe(f)).is_err()
+}
+
+/// Requires that a type can be sent to and shared between threads.
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn cells_are_send_and_sync_for_send_and_sync_values() {
+    assert_send_sync::<RwCell<i32>>();
+    assert_send_sync::<RwCell<Vec<String>, PanicUnwind>>();
+    assert_send_sync::<RwCell<std::sync::Arc<std::sync::Mutex<i32>>>>();
+}
+
+#[test]
+fn replace_returns_previous_value() {
+    let cell = RwCell::new(String::from("old"));
+    assert_eq!(cell.replace(String::from("new")), "old");
+    assert_eq!(*cell.borrow(), "new");
+    assert!(cell.free());
+}
+
+#[test]
+fn replace_conflicts_with_read_guard() {
+    let cell = RwCell::<_, PanicUnwind>::with_policy(1);
+    let guard = cell.borrow();
+    assert!(panics(|| cell.replace(2)));
+    assert_eq!(*guard, 1);
+    assert_eq!(cell.reader_count(), 1);
+
+    drop(guard);
+    assert_eq!(cell.replace(2), 1);
+    assert_eq!(*cell.borrow(), 2);
+}
+
+#[test]
+fn replace_with_returns_previous_value() {
+    let cell = RwCell::new(vec![1, 2]);
+    let previous = cell.replace_with(|value| {
+        value.push(3);
+        vec![value.len()]
+    });
+    assert_eq!(previous, [1, 2, 3]);
+    assert_eq!(*cell.borrow(), [3]);
+}
+
+#[test]
+fn replace_with_closure_conflicts_with_its_own_cell() {
+    let cell = RwCell::new(1);
+    let conflicted = cell.replace_with(|value| {
+        assert!(cell.try_borrow().is_err());
+        assert_eq!(cell.try_borrow_mut().err(), Some(BorrowMutError::Writing));
+        *value + 1
+    });
+    assert_eq!(conflicted, 1);
+    assert_eq!(*cell.borrow(), 2);
+}
+
+#[test]
+fn replace_with_releases_borrow_on_panic() {
+    let cell = RwCell::new(1);
+    assert!(panics(|| cell.replace_with(|_| panic!("replacement failed"))));
+    assert!(cell.free());
+    assert_eq!(cell.replace(2), 1);
+}
+
+#[test]
+fn swap_exchanges_values() {
+    let front = RwCell::new(vec![1]);
+    let back = RwCell::new(vec![2, 3]);
+    front.swap(&back);
+    assert_eq!(*front.borrow(), [2, 3]);
+    assert_eq!(*back.borrow(), [1]);
+    assert!(front.free() && back.free());
+}
+
+#[test]
+fn swap_with_itself_has_no_effect() {
+    let cell = RwCell::new(1);
+    cell.swap(&cell);
+    assert_eq!(*cell.borrow(), 1);
+    assert!(cell.free());
+
+    let cell = RwCell::<_, PanicUnwind>::with_policy(1);
+    let guard = cell.borrow();
+    assert!(panics(|| cell.swap(&cell)));
+    drop(guard);
+    assert!(cell.free());
+}
+
+#[test]
+fn swap_conflicts_with_read_guard_on_either_side() {
+    let front = RwCell::<_, PanicUnwind>::with_policy(1);
+    let back = RwCell::<_, PanicUnwind>::with_policy(2);
+
+    let guard = back.borrow();
+    assert!(panics(|| front.swap(&back)));
+    assert!(panics(|| back.swap(&front)));
+    drop(guard);
+
+    assert!(front.free() && back.free());
+    assert_eq!((*front.borrow(), *back.borrow()), (1, 2));
+}
+
+#[test]
+fn set_drops_previous_value_while_borrowed() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A value whose destructor checks whether it can borrow [`CELL`].
+    struct Reentrant(u32);
+
+    impl Drop for Reentrant {
+        fn drop(&mut self) {
+            if CELL.try_borrow().is_err() && CELL.try_borrow_mut().is_err() {
+                CONFLICTS.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    static CELL: RwCell<Reentrant> = RwCell::new(Reentrant(0));
+    static CONFLICTS: AtomicUsize = AtomicUsize::new(0);
+
+    CELL.set(Reentrant(1));
+    assert_eq!(CONFLICTS.load(Ordering::SeqCst), 1);
+    assert_eq!(CELL.borrow().0, 1);
+    assert!(CELL.free());
+}
+
+#[test]
+fn with_releases_borrow_after_return_and_panic() {
+    let cell = RwCell::new(1);
+    assert_eq!(cell.with(|value| *value + 1), 2);
+    assert!(cell.free());
+
+    assert!(panics(|| cell.with(|_| panic!("reader failed"))));
+    assert!(cell.free());
+}
+
+#[test]
+fn with_mut_releases_borrow_after_return_and_panic() {
+    let cell = RwCell::new(1);
+    cell.with_mut(|value| *value = 2);
+    assert!(cell.free());
+
+    assert!(panics(|| cell.with_mut(|value| {
+        *value = 3;
+        panic!("writer failed")
+    })));
+    assert!(cell.free());
+    assert_eq!(*cell.borrow(), 3);
+}
+
+#[test]
+fn borrow_state_reports_each_state() {
+    let cell = RwCell::new((1, 2));
+    assert_eq!(cell.borrow_state(), BorrowState::Free);
+    assert_eq!(cell.reader_count(), 0);
+    assert!(!cell.is_locked_exclusive());
+
+    let first = cell.borrow();
+    let second = cell.borrow();
+    assert_eq!(cell.borrow_state(), BorrowState::Reading(2));
+    assert_eq!(cell.reader_count(), 2);
+    assert!(!cell.is_locked_exclusive());
+    drop((first, second));
+
+    let guard = cell.borrow_mut();
+    assert_eq!(cell.borrow_state(), BorrowState::Writing);
+    assert_eq!(cell.reader_count(), 0);
+    assert!(cell.is_locked_exclusive());
+
+    let (left, right) = RwCellGuard::<Mut, _>::map_split(guard, |(a, b)| (a, b));
+    assert_eq!(cell.borrow_state(), BorrowState::Writing);
+    assert_eq!(cell.reader_count(), 0);
+    drop(left);
+    assert_eq!(cell.borrow_state(), BorrowState::Writing);
+    drop(right);
+    assert_eq!(cell.borrow_state(), BorrowState::Free);
+
+    cell.freeze().unwrap();
+    assert_eq!(cell.borrow_state(), BorrowState::Frozen);
+    assert_eq!(cell.reader_count(), 0);
+    assert!(!cell.is_locked_exclusive());
+}
+
+#[test]
+fn clone_inner_releases_borrow_on_panic() {
+    /// A value whose clone always panics.
+    struct Unclonable;
+
+    impl Clone for Unclonable {
+        fn clone(&self) -> Self {
+            panic!("clone failed")
+        }
+    }
+
+    let cell = RwCell::new(Unclonable);
+    assert!(panics(|| cell.clone_inner()));
+    assert!(cell.free());
+    assert!(panics(|| cell.try_clone_inner()));
+    assert!(cell.free());
+}
+
+#[test]
+fn try_clone_inner_fails_while_writing() {
+    let cell = RwCell::new(String::from("value"));
+    let guard = cell.borrow_mut();
+    assert_eq!(cell.try_clone_inner(), None);
+    drop(guard);
+    assert_eq!(cell.try_clone_inner().as_deref(), Some("value"));
+    assert_eq!(cell.clone_inner(), "value");
+    assert!(cell.free());
+}
+
+#[test]
+fn arithmetic_helpers_return_new_value() {
+    let cell = RwCell::new(10u64);
+    assert_eq!(cell.add_assign(5), 15);
+    assert_eq!(cell.sub_assign(3), 12);
+    assert_eq!(cell.bitor_assign(1), 13);
+    assert_eq!(cell.min_assign(20), 13);
+    assert_eq!(cell.min_assign(4), 4);
+    assert_eq!(cell.max_assign(2), 4);
+    assert_eq!(cell.max_assign(9), 9);
+    assert!(cell.free());
+}
+
+#[test]
+fn arithmetic_helpers_on_separate_cells_never_conflict() {
+    const THREADS: usize = 8;
+    const ITERATIONS: u64 = 10_000;
+
+    let cells: Vec<RwCell<u64>> = (0..THREADS).map(|_| RwCell::new(0)).collect();
+    std::thread::scope(|s| {
+        for cell in &cells {
+            s.spawn(move || {
+                for _ in 0..ITERATIONS {
+                    cell.add_assign(1);
+                }
+            });
+        }
+    });
+
+    assert!(cells.iter().all(|cell| cell.get() == ITERATIONS));
+}
+
+#[test]
+fn mutable_borrows_fail_on_frozen_cell() {
+    let cell = RwCell::<_, PanicUnwind>::with_policy(1);
+    cell.freeze().unwrap();
+
+    assert_eq!(cell.try_borrow_mut().err(), Some(BorrowMutError::Frozen));
+    assert!(panics(|| cell.borrow_mut()));
+    assert_eq!(cell.freeze(), Ok(()));
+    assert_eq!(*cell.borrow(), 1);
+    assert_eq!(cell.try_borrow_mut().err(), Some(BorrowMutError::Frozen));
+}
+
+#[test]
+fn freeze_fails_while_borrowed() {
+    let cell = RwCell::new(1);
+    let guard = cell.borrow();
+    assert_eq!(cell.freeze(), Err(BorrowMutError::Reading));
+    drop(guard);
+
+    let guard = cell.borrow_mut();
+    assert_eq!(cell.freeze(), Err(BorrowMutError::Writing));
+    drop(guard);
+
+    assert_eq!(cell.freeze(), Ok(()));
+    assert_eq!(cell.borrow_state(), BorrowState::Frozen);
+}
+
+#[test]
+fn const_filter_map_returns_original_guard_on_none() {
+    let cell = RwCell::new(vec![1, 2, 3]);
+    let guard = RwCellGuard::<Const, _>::filter_map(cell.borrow(), |value| value.get(1)).unwrap();
+    assert_eq!(*guard, 2);
+    assert_eq!(cell.reader_count(), 1);
+    drop(guard);
+
+    let guard = RwCellGuard::<Const, _>::filter_map(cell.borrow(), |value| value.get(5)).unwrap_err();
+    assert_eq!(*guard, [1, 2, 3]);
+    assert_eq!(cell.reader_count(), 1);
+    drop(guard);
+
+    cell.borrow_mut().push(4);
+    assert_eq!(*cell.borrow(), [1, 2, 3, 4]);
+}
+
+#[test]
+fn mut_filter_map_returns_original_guard_on_none() {
+    let cell = RwCell::new(vec![1, 2, 3]);
+    let mut guard = RwCellGuard::<Mut, _>::filter_map(cell.borrow_mut(), |value| value.get_mut(5)).unwrap_err();
+    guard.push(4);
+    assert!(cell.is_locked_exclusive());
+    drop(guard);
+
+    assert_eq!(*cell.borrow(), [1, 2, 3, 4]);
+    assert!(cell.free());
+}
+
+#[test]
+fn mut_filter_map_releases_borrow_on_panic() {
+    let cell = RwCell::new(vec![1, 2, 3]);
+    assert!(panics(|| RwCellGuard::<Mut, _>::filter_map(cell.borrow_mut(), |_| -> Option<&mut i32> { panic!() })));
+    assert!(cell.free());
+    cell.borrow_mut().push(4);
+}
+
+#[test]
+fn mut_filter_map_guards_can_be_mapped_again() {
+    let cell = RwCell::new(vec![(1, 2), (3, 4)]);
+    let guard = RwCellGuard::<Mut, _>::filter_map(cell.borrow_mut(), |value| value.get_mut(1)).unwrap();
+    let mut guard = RwCellGuard::<Mut, _>::map(guard, |(_, second)| second);
+    *guard = 5;
+    assert!(cell.is_locked_exclusive());
+    drop(guard);
+
+    assert_eq!(*cell.borrow(), [(1, 2), (3, 5)]);
+    assert!(cell.free());
+}
+
+#[test]
+fn downgraded_guard_blocks_writers() {
+    let cell = RwCell::new(1);
+    let mut guard = cell.borrow_mut();
+    *guard = 2;
+    let guard = RwCellGuard::downgrade(guard);
+
+    assert_eq!(cell.try_borrow_mut().err(), Some(BorrowMutError::Reading));
+    assert_eq!(cell.borrow_state(), BorrowState::Reading(1));
+    let other = cell.borrow();
+    assert_eq!((*guard, *other), (2, 2));
+    drop(guard);
+
+    assert_eq!(cell.try_borrow_mut().err(), Some(BorrowMutError::Reading));
+    drop(other);
+    assert!(cell.try_borrow_mut().is_ok());
+}
+
+#[test]
+fn downgrade_admits_no_writer_in_between() {
+    let cell = RwCell::new(0);
+    std::thread::scope(|scope| {
+        let writer = scope.spawn(|| {
+            for _ in 0..10_000 {
+                if let Ok(mut guard) = cell.try_borrow_mut() {
+                    *guard += 1;
+                }
+            }
+        });
+
+        for _ in 0..10_000 {
+            if let Ok(mut guard) = cell.try_borrow_mut() {
+                *guard = -1;
+                let guard = RwCellGuard::downgrade(guard);
+                assert_eq!(*guard, -1);
+            }
+        }
+        writer.join().unwrap();
+    });
+}
+
+#[test]
+fn try_upgrade_requires_sole_reader() {
+    let cell = RwCell::new(1);
+    let guard = cell.borrow();
+    let other = cell.borrow();
+    let guard = RwCellGuard::try_upgrade(guard).unwrap_err();
+    assert_eq!(cell.reader_count(), 2);
+    drop(other);
+
+    drop(cell.borrow());
+    let mut guard = RwCellGuard::try_upgrade(guard).unwrap();
+    *guard = 2;
+    assert!(cell.is_locked_exclusive());
+    drop(guard);
+    assert_eq!(*cell.borrow(), 2);
+}
+
+#[test]
+fn try_upgrade_fails_for_mapped_guard() {
+    let cell = RwCell::new((1, 2));
+    let guard = RwCellGuard::<Const, _>::map(cell.borrow(), |(first, _)| first);
+    let guard = RwCellGuard::try_upgrade(guard).unwrap_err();
+    assert_eq!(*guard, 1);
+    assert_eq!(cell.reader_count(), 1);
+}
+
+#[test]
+fn formatting_matches_borrowed_value() {
+    let cell = RwCell::new(0x2au8);
+    let value = &0x2au8;
+    let guard = cell.borrow();
+    assert_eq!(format!("{guard} {guard:?} {guard:>5}"), format!("{value} {value:?} {value:>5}"));
+    assert_eq!(format!("{guard:x} {guard:#X} {guard:08b} {guard:#o}"), format!("{value:x} {value:#X} {value:08b} {value:#o}"));
+    drop(guard);
+
+    let guard = cell.borrow_mut();
+    assert_eq!(format!("{guard:x} {guard:#X} {guard:08b} {guard:#o}"), format!("{value:x} {value:#X} {value:08b} {value:#o}"));
+
+    let cell = RwCell::new(String::from("text"));
+    assert_eq!(format!("{:?} {:<6}|", cell.borrow(), cell.borrow()), format!("{:?} {:<6}|", "text", "text"));
+}
+
+#[test]
+fn downcast_ref_hits_and_misses() {
+    use std::any::Any;
+
+    let cell = RwCell::new(1i32);
+    let guard = RwCellGuard::<Const, _>::map(cell.borrow(), |value| value as &dyn Any);
+    let guard = RwCellGuard::downcast_ref::<u32>(guard).unwrap_err();
+    assert_eq!(cell.reader_count(), 1);
+
+    let guard = RwCellGuard::downcast_ref::<i32>(guard).unwrap();
+    assert_eq!(*guard, 1);
+    drop(guard);
+    assert!(cell.free());
+}
+
+#[test]
+fn downcast_mut_hits_and_misses() {
+    use std::any::Any;
+
+    let cell = RwCell::new(String::from("a"));
+    let guard = RwCellGuard::<Mut, _>::map(cell.borrow_mut(), |value| value as &mut dyn Any);
+    let guard = RwCellGuard::downcast_mut::<&str>(guard).unwrap_err();
+    assert!(cell.is_locked_exclusive());
+
+    let mut guard = RwCellGuard::downcast_mut::<String>(guard).unwrap();
+    guard.push('b');
+    drop(guard);
+    assert_eq!(*cell.borrow(), "ab");
+    assert!(cell.free());
+}
+
+#[test]
+fn mut_map_panic_leaves_cell_usable() {
+    let cell = RwCell::new((1, 2));
+    assert!(panics(|| RwCellGuard::<Mut, _>::map(cell.borrow_mut(), |_| -> &mut i32 { panic!() })));
+    assert!(panics(|| RwCellGuard::<Mut, _>::map_with(cell.borrow_mut(), |_| -> (&mut i32, ()) { panic!() })));
+    assert!(panics(|| RwCellGuard::<Mut, _>::map_split(cell.borrow_mut(), |_| -> (&mut i32, &mut i32) { panic!() })));
+    assert!(cell.free());
+
+    *RwCellGuard::<Mut, _>::map(cell.borrow_mut(), |(_, second)| second) = 3;
+    assert_eq!(*cell.borrow(), (1, 3));
+}
+
+#[test]
+fn get_disjoint_mut_rejects_invalid_indices() {
+    let cell = RwCell::new([1, 2, 3]);
+    let guard = RwCellGuard::<Mut, _>::map(cell.borrow_mut(), |value| value.as_mut_slice());
+    let guard = RwCellGuard::get_disjoint_mut(guard, [0, 0]).unwrap_err();
+    let guard = RwCellGuard::get_disjoint_mut(guard, [1, 3]).unwrap_err();
+    assert_eq!(*guard, [1, 2, 3]);
+
+    let [mut last, mut first] = RwCellGuard::get_disjoint_mut(guard, [2, 0]).unwrap();
+    core::mem::swap(&mut *first, &mut *last);
+    drop(first);
+    assert!(cell.is_locked_exclusive());
+    drop(last);
+
+    assert_eq!(*cell.borrow(), [3, 2, 1]);
+    assert!(cell.free());
+}
+
+#[test]
+fn get_disjoint_mut_with_no_indices_releases_borrow() {
+    let cell = RwCell::new([1, 2, 3]);
+    let guard = RwCellGuard::<Mut, _>::map(cell.borrow_mut(), |value| value.as_mut_slice());
+    let [] = RwCellGuard::get_disjoint_mut(guard, []).unwrap();
+    assert!(cell.free());
+}
+
+#[test]
+fn chunks_mut_yields_shorter_remainder() {
+    let cell = RwCell::new([1, 2, 3, 4, 5]);
+    let guard = RwCellGuard::<Mut, _>::map(cell.borrow_mut(), |value| value.as_mut_slice());
+    let mut chunks = RwCellGuard::chunks_mut(guard, 2);
+    assert_eq!(chunks.size_hint(), (3, Some(3)));
+    assert_eq!(chunks.len(), 3);
+
+    let mut first = chunks.next().unwrap();
+    assert_eq!(chunks.len(), 2);
+    let second = chunks.next().unwrap();
+    let last = chunks.next().unwrap();
+    assert_eq!((chunks.len(), chunks.next().map(|chunk| chunk.len())), (0, None));
+    first[0] = 10;
+    assert_eq!((&*first, &*second, &*last), (&[10, 2][..], &[3, 4][..], &[5][..]));
+    drop((first, second, last));
+
+    assert!(cell.is_locked_exclusive());
+    drop(chunks);
+    assert_eq!(*cell.borrow(), [10, 2, 3, 4, 5]);
+}
+
+#[test]
+fn chunks_mut_holds_borrow_until_every_chunk_is_dropped() {
+    let cell = RwCell::new([1, 2, 3]);
+    let guard = RwCellGuard::<Mut, _>::map(cell.borrow_mut(), |value| value.as_mut_slice());
+    let mut chunks = RwCellGuard::chunks_mut(guard, 2);
+    let first = chunks.next().unwrap();
+    drop(chunks);
+
+    assert!(cell.is_locked_exclusive());
+    assert!(cell.try_borrow().is_err());
+    drop(first);
+    assert!(cell.free());
+
+    let guard = RwCellGuard::<Mut, _>::map(cell.borrow_mut(), |value| value.as_mut_slice());
+    let chunks: Vec<_> = RwCellGuard::chunks_mut(guard, 4).collect();
+    assert_eq!(chunks.len(), 1);
+    assert!(cell.is_locked_exclusive());
+    drop(chunks);
+    assert!(cell.free());
+}
+
+#[test]
+fn chunks_mut_with_zero_size_releases_borrow() {
+    let cell = RwCell::new([1, 2, 3]);
+    assert!(panics(|| RwCellGuard::chunks_mut(RwCellGuard::<Mut, _>::map(cell.borrow_mut(), |value| value.as_mut_slice()), 0)));
+    assert!(cell.free());
+    *cell.borrow_mut() = [4, 5, 6];
+}
+
+#[test]
+fn nested_guard_holds_both_borrows() {
+    let outer = RwCell::new(vec![RwCell::new(1), RwCell::new(2)]);
+    let mut nested = RwCellGuard::and_borrow_mut(outer.borrow(), |cells| &cells[1]);
+    *nested = 3;
+    assert_eq!(outer.reader_count(), 1);
+    assert!(outer.borrow()[1].is_locked_exclusive());
+    assert!(outer.borrow()[0].free());
+    drop(nested);
+
+    assert!(outer.free());
+    assert!(outer.borrow()[1].free());
+    assert_eq!(*outer.borrow()[1].borrow(), 3);
+
+    let nested = RwCellGuard::and_borrow(outer.borrow(), |cells| &cells[0]);
+    assert_eq!((*nested, outer.reader_count()), (1, 1));
+    assert_eq!(outer.borrow()[0].reader_count(), 1);
+    drop(nested);
+    assert!(outer.free() && outer.borrow()[0].free());
+    outer.borrow_mut().clear();
+}
+
+#[test]
+fn failed_inner_borrow_releases_outer_borrow() {
+    let outer = RwCell::new(RwCell::new(1));
+    let inner = outer.borrow();
+    let inner = inner.borrow_mut();
+
+    let error = RwCellGuard::try_and_borrow(outer.borrow(), |cell| cell).err();
+    assert_eq!(error, Some(BorrowError));
+    let error = RwCellGuard::try_and_borrow_mut(outer.borrow(), |cell| cell).err();
+    assert_eq!(error, Some(BorrowMutError::Writing));
+    assert_eq!(outer.reader_count(), 1);
+    drop(inner);
+
+    let nested = RwCellGuard::try_and_borrow_mut(outer.borrow(), |cell| cell).unwrap();
+    assert_eq!(outer.reader_count(), 2);
+    drop(nested);
+    assert_eq!(outer.reader_count(), 1);
+}
+
+#[test]
+fn immutable_borrows_saturate_at_reader_limit() {
+    const MAX_READERS: usize = 4095;
+
+    let cell = RwCell::<_, PanicUnwind>::with_policy(1);
+    let mut guards: Vec<_> = std::iter::from_fn(|| cell.try_borrow().ok()).collect();
+    assert_eq!(guards.len(), MAX_READERS);
+    assert_eq!(cell.reader_count(), MAX_READERS);
+    assert!(cell.try_borrow_mut().is_err());
+
+    let message = catch_unwind(AssertUnwindSafe(|| cell.borrow())).unwrap_err();
+    assert_eq!(message.downcast_ref::<String>().map(String::as_str), Some("Attempted to immutably borrow cell too many times."));
+    assert_eq!(cell.reader_count(), MAX_READERS);
+
+    guards.pop();
+    guards.push(cell.borrow());
+    assert_eq!(cell.reader_count(), MAX_READERS);
+
+    drop(guards);
+    assert!(cell.free());
+    *cell.borrow_mut() = 2;
+}
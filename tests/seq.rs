@@ -0,0 +1,75 @@
+use std::sync::atomic::*;
+use std::thread;
+use sync_rw_cell::*;
+
+/// The number of values in each array stored by the stress tests. Large enough that a copy
+/// takes many byte-wise loads, so that torn reads would be likely if they were possible.
+const WORDS: usize = 16;
+
+#[test]
+fn readers_never_see_torn_values() {
+    const WRITES: usize = 5_000;
+
+    let cell = SeqCell::new([0u64; WORDS]);
+    let done = AtomicBool::new(false);
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                let mut last = 0;
+                while !done.load(Ordering::Relaxed) {
+                    let value = cell.read();
+                    assert!(value.iter().all(|&word| word == value[0]), "torn read: {value:?}");
+                    assert!(value[0] >= last, "reads went backwards");
+                    last = value[0];
+                }
+            });
+        }
+
+        let writers: Vec<_> = (0..2)
+            .map(|_| {
+                scope.spawn(|| {
+                    for _ in 0..WRITES {
+                        cell.update(|value| [value[0] + 1; WORDS]);
+                    }
+                })
+            })
+            .collect();
+
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        done.store(true, Ordering::Relaxed);
+    });
+
+    assert_eq!(cell.read(), [2 * WRITES as u64; WORDS]);
+}
+
+#[test]
+fn concurrent_writes_store_whole_values() {
+    let cell = SeqCell::new([0u8; WORDS]);
+    thread::scope(|scope| {
+        for byte in 1..=4u8 {
+            let cell = &cell;
+            scope.spawn(move || {
+                for _ in 0..1_000 {
+                    cell.write([byte; WORDS]);
+                    let value = cell.read();
+                    assert!(value.iter().all(|&word| word == value[0]), "torn read: {value:?}");
+                }
+            });
+        }
+    });
+
+    let value = cell.read();
+    assert!((1..=4).contains(&value[0]) && value.iter().all(|&word| word == value[0]));
+}
+
+#[test]
+fn panicking_update_leaves_cell_usable() {
+    let cell = SeqCell::new(1);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cell.update(|_| panic!())));
+    assert!(result.is_err());
+    assert_eq!(cell.read(), 1);
+    cell.write(2);
+    assert_eq!(cell.read(), 2);
+}
@@ -0,0 +1,88 @@
+use std::sync::Mutex;
+use sync_rw_cell::*;
+
+/// Serializes the tests in this file, since every shadow cell shares the same side table, and a
+/// borrow held by one test could otherwise conflict with a colliding cell of another.
+static SERIAL: Mutex<()> = Mutex::new(());
+
+/// Finds two cells in a slice whose borrow states share an entry of the side table.
+fn find_collision<T>(cells: &[ShadowRwCell<T>]) -> Option<(usize, usize)> {
+    for i in 0..cells.len() {
+        let guard = cells[i].borrow_mut();
+        let collision = (i + 1..cells.len()).find(|&j| cells[j].borrow_state() == BorrowState::Writing);
+        drop(guard);
+
+        if let Some(j) = collision {
+            return Some((i, j));
+        }
+    }
+
+    None
+}
+
+#[test]
+fn cells_have_the_layout_of_their_value() {
+    assert_eq!(size_of::<ShadowRwCell<u8>>(), size_of::<u8>());
+    assert_eq!(size_of::<ShadowRwCell<[u64; 3]>>(), size_of::<[u64; 3]>());
+    assert_eq!(align_of::<ShadowRwCell<u64>>(), align_of::<u64>());
+}
+
+#[test]
+fn from_slice_mut_round_trips() {
+    let _serial = SERIAL.lock().unwrap();
+    let mut values = [1, 2, 3];
+    let cells = ShadowRwCell::from_slice_mut(&mut values);
+    *cells[1].borrow_mut() = 4;
+    *cells[2].get_mut() = 5;
+    assert_eq!(*ShadowRwCell::cast_slice(cells).unwrap(), [1, 4, 5]);
+
+    ShadowRwCell::cast_slice_mut(cells)[0] = 6;
+    assert_eq!(values, [6, 4, 5]);
+
+    let mut value = 7;
+    *ShadowRwCell::from_mut(&mut value).borrow_mut() += 1;
+    assert_eq!(value, 8);
+}
+
+#[test]
+fn failed_cast_slice_releases_acquired_cells() {
+    let _serial = SERIAL.lock().unwrap();
+    let cells = [ShadowRwCell::new(1), ShadowRwCell::new(2), ShadowRwCell::new(3)];
+    let last = cells[2].borrow_mut();
+    assert_eq!(ShadowRwCell::cast_slice(&cells).err(), Some(BorrowError));
+    assert_eq!(cells[0].borrow_state(), BorrowState::Free);
+    assert_eq!(cells[1].borrow_state(), BorrowState::Free);
+    assert!(cells[0].try_borrow_mut().is_ok());
+    drop(last);
+
+    let all = ShadowRwCell::cast_slice(&cells).unwrap();
+    assert_eq!(cells[0].borrow_state(), BorrowState::Reading(1));
+    assert_eq!(cells[2].try_borrow_mut().err(), Some(BorrowMutError::Reading));
+    drop(all);
+    assert_eq!(cells[2].borrow_state(), BorrowState::Free);
+}
+
+#[test]
+fn colliding_cells_share_a_borrow_state() {
+    let _serial = SERIAL.lock().unwrap();
+
+    // The side table has 1024 entries, so some pair of this many cells must collide.
+    let cells: Vec<_> = (0..1025).map(ShadowRwCell::new).collect();
+    let (i, j) = find_collision(&cells).expect("no cells collided");
+
+    let guard = cells[i].borrow_mut();
+    assert_eq!(cells[j].try_borrow().err(), Some(BorrowError));
+    drop(guard);
+
+    let guard = cells[j].borrow();
+    assert_eq!(cells[i].borrow_state(), BorrowState::Reading(1));
+    assert_eq!(cells[i].try_borrow_mut().err(), Some(BorrowMutError::Reading));
+    assert_eq!(*cells[i].borrow(), i);
+    drop(guard);
+
+    let range = ShadowRwCell::cast_slice(&cells[i..=j]).unwrap();
+    assert_eq!((range[0], range[j - i]), (i, j));
+    assert!(matches!(cells[i].borrow_state(), BorrowState::Reading(count) if count >= 2));
+    drop(range);
+    assert_eq!(cells[i].borrow_state(), BorrowState::Free);
+}
@@ -0,0 +1,141 @@
+#![cfg(feature = "std")]
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use sync_rw_cell::*;
+
+/// The number of immutable borrows that take the slow path after the bias of a cell is revoked.
+const REBIAS_READS: usize = 256;
+
+#[test]
+fn first_borrow_uses_a_slot() {
+    let cell = RwCellSharded::new(1);
+    assert!(cell.is_biased());
+    let guard = cell.borrow();
+    assert!(ShardedReadGuard::is_in_slot(&guard));
+    assert_eq!(*guard, 1);
+}
+
+#[test]
+fn writer_revokes_bias_until_enough_slow_reads() {
+    let cell = RwCellSharded::new(0);
+    *cell.borrow_mut() = 1;
+    assert!(!cell.is_biased());
+
+    for _ in 0..REBIAS_READS {
+        let guard = cell.borrow();
+        assert!(!ShardedReadGuard::is_in_slot(&guard));
+        assert_eq!(*guard, 1);
+    }
+    assert!(!cell.is_biased());
+
+    assert!(!ShardedReadGuard::is_in_slot(&cell.borrow()));
+    assert!(cell.is_biased());
+    assert!(ShardedReadGuard::is_in_slot(&cell.borrow()));
+
+    *cell.borrow_mut() = 2;
+    assert!(!cell.is_biased());
+    assert!(!ShardedReadGuard::is_in_slot(&cell.borrow()));
+}
+
+#[test]
+fn slot_reader_blocks_writer_and_keeps_bias() {
+    let cell = RwCellSharded::new(1);
+    let guard = cell.borrow();
+    assert!(ShardedReadGuard::is_in_slot(&guard));
+
+    assert_eq!(cell.try_borrow_mut().unwrap_err(), BorrowMutError::Reading);
+    assert!(cell.is_biased());
+    assert_eq!(*guard, 1);
+
+    let other = cell.borrow();
+    assert!(!ShardedReadGuard::is_in_slot(&other));
+    drop(other);
+
+    drop(guard);
+    *cell.try_borrow_mut().unwrap() = 2;
+    assert_eq!(*cell.borrow(), 2);
+}
+
+#[test]
+fn second_borrow_on_same_thread_falls_back_to_counter() {
+    let cell = RwCellSharded::new(1);
+    let first = cell.borrow();
+    let second = cell.borrow();
+    assert!(ShardedReadGuard::is_in_slot(&first));
+    assert!(!ShardedReadGuard::is_in_slot(&second));
+    assert_eq!(cell.try_borrow_mut().unwrap_err(), BorrowMutError::Reading);
+
+    drop(first);
+    assert_eq!(cell.try_borrow_mut().unwrap_err(), BorrowMutError::Reading);
+    drop(second);
+    assert!(cell.try_borrow_mut().is_ok());
+}
+
+#[test]
+fn slot_is_freed_when_guard_is_dropped() {
+    let cell = RwCellSharded::new(1);
+    drop(cell.borrow());
+    let guard = cell.borrow();
+    assert!(ShardedReadGuard::is_in_slot(&guard));
+}
+
+#[test]
+fn readers_on_other_threads_use_their_own_slots() {
+    let cell = RwCellSharded::new(1);
+    let guard = cell.borrow();
+    thread::scope(|scope| {
+        scope.spawn(|| assert!(ShardedReadGuard::is_in_slot(&cell.borrow())));
+    });
+    assert!(ShardedReadGuard::is_in_slot(&guard));
+}
+
+#[test]
+fn writer_never_coexists_with_slot_reader() {
+    const READERS: usize = 3;
+    const WRITES: usize = 500;
+
+    let cell = RwCellSharded::new((0usize, 0usize));
+    let done = AtomicBool::new(false);
+    let slot_reads = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for _ in 0..READERS {
+            scope.spawn(|| {
+                while !done.load(Ordering::Relaxed) {
+                    if let Ok(guard) = cell.try_borrow() {
+                        let (a, b) = *guard;
+                        std::hint::spin_loop();
+                        assert_eq!((a, b), *guard, "value changed while it was borrowed");
+                        assert_eq!(a, b, "torn write observed");
+                        if ShardedReadGuard::is_in_slot(&guard) {
+                            slot_reads.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                    thread::yield_now();
+                }
+            });
+        }
+
+        let mut writes = 0;
+        while writes < WRITES {
+            if let Ok(mut guard) = cell.try_borrow_mut() {
+                guard.0 += 1;
+                thread::yield_now();
+                guard.1 += 1;
+                writes += 1;
+                drop(guard);
+
+                while !cell.is_biased() {
+                    thread::yield_now();
+                }
+            }
+            else {
+                thread::yield_now();
+            }
+        }
+        done.store(true, Ordering::Relaxed);
+    });
+
+    assert_eq!(*cell.borrow(), (WRITES, WRITES));
+    assert!(slot_reads.load(Ordering::Relaxed) > 0);
+}
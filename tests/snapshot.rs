@@ -0,0 +1,151 @@
+#![cfg(feature = "std")]
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use sync_rw_cell::*;
+
+/// A value that records whether it has been dropped, so that a load which observes a value
+/// after its last snapshot was released can be detected.
+struct Tracked {
+    /// The sequence number of the value.
+    value: usize,
+    /// Whether the value is still alive.
+    alive: AtomicBool,
+}
+
+impl Tracked {
+    /// Creates a new, live value.
+    fn new(value: usize) -> Self {
+        Self { value, alive: AtomicBool::new(true) }
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.alive.store(false, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn snapshots_keep_the_value_they_loaded() {
+    let cell = SnapshotCell::new(1);
+    let first = cell.load();
+    assert_eq!(*cell.swap(Arc::new(2)), 1);
+    assert_eq!(*first, 1);
+    assert_eq!(*cell.load(), 2);
+
+    drop(first);
+    cell.store(3);
+    assert_eq!(*cell.into_inner(), 3);
+}
+
+#[test]
+fn write_guard_publishes_copy_on_drop() {
+    let cell = SnapshotCell::new(vec![1]);
+    let before = cell.load();
+    let mut guard = cell.write();
+    guard.push(2);
+    assert_eq!(*cell.load(), [1]);
+    drop(guard);
+
+    assert_eq!(*before, [1]);
+    assert_eq!(*cell.load(), [1, 2]);
+}
+
+#[test]
+fn loads_during_swaps_never_observe_released_values() {
+    const LOADERS: usize = 3;
+    const SWAPS: usize = 2_000;
+
+    let cell = SnapshotCell::new(Tracked::new(0));
+    let done = AtomicBool::new(false);
+    thread::scope(|scope| {
+        for _ in 0..LOADERS {
+            scope.spawn(|| {
+                let mut last = 0;
+                while !done.load(Ordering::Relaxed) {
+                    let snapshot = cell.load();
+                    assert!(snapshot.alive.load(Ordering::SeqCst), "loaded a value that was already dropped");
+                    assert!(snapshot.value >= last, "loads went backwards");
+                    last = snapshot.value;
+                }
+            });
+        }
+
+        for i in 1..=SWAPS {
+            let previous = cell.swap(Arc::new(Tracked::new(i)));
+            assert_eq!(previous.value, i - 1);
+            if i % 64 == 0 {
+                thread::yield_now();
+            }
+        }
+        done.store(true, Ordering::Relaxed);
+    });
+
+    assert_eq!(cell.load().value, SWAPS);
+}
+
+#[test]
+fn publish_waits_for_loads_from_both_epochs() {
+    const LOADERS: usize = 4;
+    const WRITES: usize = 1_000;
+
+    let cell = SnapshotCell::new(Tracked::new(0));
+    let done = AtomicBool::new(false);
+    thread::scope(|scope| {
+        for _ in 0..LOADERS {
+            scope.spawn(|| {
+                while !done.load(Ordering::Relaxed) {
+                    let snapshot = cell.load();
+                    assert!(snapshot.alive.load(Ordering::SeqCst), "loaded a value that was already dropped");
+                }
+            });
+        }
+
+        let writers: Vec<_> = (0..2)
+            .map(|_| {
+                scope.spawn(|| {
+                    for _ in 0..WRITES {
+                        let previous = cell.rcu(|current| Tracked::new(current.value + 1));
+                        if let Some(previous) = Arc::into_inner(previous) {
+                            assert!(previous.alive.load(Ordering::SeqCst));
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        done.store(true, Ordering::Relaxed);
+    });
+
+    assert_eq!(cell.load().value, 2 * WRITES);
+}
+
+#[test]
+fn rcu_loses_no_updates_under_racing_writers() {
+    const WRITERS: usize = 4;
+    const UPDATES: usize = 500;
+
+    let cell = SnapshotCell::new(0usize);
+    thread::scope(|scope| {
+        for i in 0..WRITERS {
+            let cell = &cell;
+            scope.spawn(move || {
+                for _ in 0..UPDATES {
+                    if i % 2 == 0 {
+                        cell.rcu(|value| value + 1);
+                    }
+                    else {
+                        *cell.write() += 1;
+                    }
+                }
+            });
+        }
+    });
+
+    assert_eq!(*cell.load(), WRITERS * UPDATES);
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "stable_deref")]
+
+use ouroboros::self_referencing;
+use stable_deref_trait::{CloneStableDeref, StableDeref};
+use sync_rw_cell::*;
+
+/// The cell that owns the text borrowed by each [`Words`].
+static TEXT: RwCell<String> = RwCell::new(String::new());
+
+/// Holds an immutable guard along with references into the borrowed value.
+#[self_referencing]
+struct Words {
+    /// The guard that keeps the text borrowed.
+    guard: RwCellGuard<'static, Const, String>,
+    /// The words of the text.
+    #[borrows(guard)]
+    #[covariant]
+    words: Vec<&'this str>,
+}
+
+/// Clones an owner along with a reference derived from it, which remains valid for the clone.
+fn clone_with<T: CloneStableDeref>(owner: &T) -> (T, *const T::Target) {
+    let clone = owner.clone();
+    let target: *const T::Target = &*clone;
+    (clone, target)
+}
+
+/// Requires an owner that does not move its target.
+fn require_stable<T: StableDeref>(owner: T) -> T {
+    owner
+}
+
+#[test]
+fn guard_owns_self_referencing_borrows() {
+    TEXT.set(String::from("borrowed by a guard"));
+    let words = WordsBuilder {
+        guard: TEXT.borrow_static(),
+        words_builder: |guard| guard.split(' ').collect(),
+    }
+    .build();
+
+    assert_eq!(words.borrow_words(), &["borrowed", "by", "a", "guard"]);
+    assert_eq!(TEXT.reader_count(), 1);
+    drop(words);
+    assert!(TEXT.free());
+}
+
+#[test]
+fn immutable_guards_are_clone_stable_deref() {
+    let cell = RwCell::new(vec![1, 2, 3]);
+    let guard = require_stable(cell.borrow());
+    let (clone, target) = clone_with(&guard);
+    assert_eq!(target, &*guard as *const _);
+    assert_eq!(cell.reader_count(), 2);
+
+    drop(guard);
+    assert_eq!(unsafe { &*target }, &[1, 2, 3]);
+    drop(clone);
+    assert!(cell.free());
+
+    require_stable(cell.borrow_mut()).push(4);
+    assert_eq!(cell.borrow().len(), 4);
+}
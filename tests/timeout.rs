@@ -0,0 +1,126 @@
+#![cfg(feature = "alloc")]
+
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::sync::atomic::*;
+use std::sync::{Arc, Mutex};
+use std::task::*;
+use std::time::Duration;
+use sync_rw_cell::*;
+
+/// Counts the number of times that it is woken.
+#[derive(Default)]
+struct CountingWaker(AtomicUsize);
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// A timer whose delays complete only when the test fires them.
+#[derive(Clone, Default)]
+struct MockTimer {
+    /// Whether the delays have completed.
+    fired: Arc<AtomicBool>,
+    /// The durations of the requested delays.
+    requested: Arc<Mutex<Vec<Duration>>>,
+}
+
+impl Timer for MockTimer {
+    type Delay = MockDelay;
+
+    fn delay(&mut self, duration: Duration) -> Self::Delay {
+        self.requested.lock().unwrap().push(duration);
+        MockDelay(self.fired.clone())
+    }
+}
+
+/// A delay created by a [`MockTimer`].
+struct MockDelay(Arc<AtomicBool>);
+
+impl Future for MockDelay {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+        if self.0.load(Ordering::SeqCst) {
+            Poll::Ready(())
+        }
+        else {
+            Poll::Pending
+        }
+    }
+}
+
+#[test]
+fn borrow_acquired_before_deadline() {
+    let cell = AsyncRwCell::new(0);
+    let timer = MockTimer::default();
+    let counter = Arc::new(CountingWaker::default());
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    let guard = cell.try_read().unwrap();
+    let mut write = pin!(cell.write_timeout(Duration::from_secs(3), timer.clone()));
+    assert!(write.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(*timer.requested.lock().unwrap(), [Duration::from_secs(3)]);
+
+    drop(guard);
+    assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+    match write.as_mut().poll(&mut cx) {
+        Poll::Ready(Some(mut guard)) => *guard = 1,
+        _ => panic!("write was not acquired before the deadline"),
+    }
+    assert_eq!(*cell.try_read().unwrap(), 1);
+}
+
+#[test]
+fn timeout_deregisters_waker() {
+    let cell = AsyncRwCell::new(0);
+    let timer = MockTimer::default();
+    let counter = Arc::new(CountingWaker::default());
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    let guard = cell.try_write().unwrap();
+    let mut read = pin!(cell.read_timeout(Duration::from_millis(10), timer.clone()));
+    assert!(read.as_mut().poll(&mut cx).is_pending());
+
+    timer.fired.store(true, Ordering::SeqCst);
+    assert!(matches!(read.as_mut().poll(&mut cx), Poll::Ready(None)));
+
+    drop(guard);
+    assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+    assert!(cell.free());
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn tokio_timer_times_out() {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_time().start_paused(true).build().unwrap();
+    runtime.block_on(async {
+        let cell = AsyncRwCell::new(0);
+        let guard = cell.try_write().unwrap();
+        assert!(cell.read_timeout(Duration::from_secs(60), TokioTimer).await.is_none());
+        drop(guard);
+        assert!(cell.read_timeout(Duration::from_secs(60), TokioTimer).await.is_some());
+    });
+}
+
+#[cfg(feature = "embassy-time")]
+#[test]
+fn embassy_timer_times_out() {
+    let cell = AsyncRwCell::new(0);
+    let counter = Arc::new(CountingWaker::default());
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    let guard = cell.try_write().unwrap();
+    let mut read = pin!(cell.read_timeout(Duration::from_millis(5), EmbassyTimer));
+    assert!(read.as_mut().poll(&mut cx).is_pending());
+    assert!(read.as_mut().poll(&mut cx).is_pending());
+
+    embassy_time::MockDriver::get().advance(embassy_time::Duration::from_millis(5));
+    assert!(matches!(read.as_mut().poll(&mut cx), Poll::Ready(None)));
+    drop(guard);
+}
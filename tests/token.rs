@@ -0,0 +1,29 @@
+use sync_rw_cell::*;
+
+mod common;
+
+#[test]
+fn owner_controls_borrows() {
+    let mut owner = CellOwner::new();
+    let first = owner.cell(1);
+    let second = owner.cell(2);
+    *first.borrow_mut_with(&mut owner) += 10;
+    assert_eq!(first.borrow_with(&owner) + second.borrow_with(&owner), 13);
+    assert!(first.is_owned_by(&owner));
+    assert!(!first.is_owned_by(&CellOwner::new()));
+
+    let cell = first.into_cell();
+    assert_eq!(*cell.borrow(), 11);
+}
+
+#[test]
+fn foreign_owner_aborts() {
+    let output = common::expect_abort("foreign_owner_aborts", || {
+        let owner = CellOwner::new();
+        let mut other = CellOwner::new();
+        let cell = owner.cell(1);
+        *cell.borrow_mut_with(&mut other) = 2;
+    });
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("with an owner that did not create it"), "unexpected output: {stderr}");
+}
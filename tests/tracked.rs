@@ -0,0 +1,42 @@
+use sync_rw_cell::*;
+
+mod common;
+
+#[test]
+fn tracked_cells_borrow_like_plain_cells() {
+    let cell = TrackedRwCell::new_named(1, "counter");
+    assert_eq!(cell.name(), Some("counter"));
+    *cell.borrow_mut() += 1;
+    let first = cell.borrow();
+    let second = cell.borrow();
+    assert_eq!(*first + *second, 4);
+    assert!(cell.try_borrow_mut().is_err());
+    drop((first, second));
+
+    assert_eq!(TrackedRwCell::new(()).name(), None);
+    assert_eq!(cell.into_cell().into_inner(), 2);
+}
+
+#[test]
+fn conflicting_mutable_borrow_aborts_with_name() {
+    let output = common::expect_abort("conflicting_mutable_borrow_aborts_with_name", || {
+        let cell = TrackedRwCell::new_named(1, "player position");
+        let _reader = cell.borrow();
+        let _writer = cell.borrow_mut();
+    });
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("while other borrows already existed"), "unexpected output: {stderr}");
+    assert!(stderr.contains("(cell \"player position\")"), "unexpected output: {stderr}");
+}
+
+#[test]
+fn conflicting_immutable_borrow_aborts_with_name() {
+    let output = common::expect_abort("conflicting_immutable_borrow_aborts_with_name", || {
+        let cell = TrackedRwCell::new_named(1, "inventory");
+        let _writer = cell.borrow_mut();
+        let _reader = cell.borrow();
+    });
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("while it was mutably borrowed"), "unexpected output: {stderr}");
+    assert!(stderr.contains("(cell \"inventory\")"), "unexpected output: {stderr}");
+}
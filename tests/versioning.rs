@@ -0,0 +1,59 @@
+#![cfg(feature = "versioning")]
+
+use sync_rw_cell::*;
+
+#[test]
+fn reads_never_change_version() {
+    let cell = RwCell::new((1, 2));
+    let last = cell.version();
+
+    drop(cell.borrow());
+    let guard = cell.borrow();
+    drop(RwCellGuard::clone(&guard));
+    drop(RwCellGuard::<Const, _>::map_split(guard, |(a, b)| (a, b)));
+    assert_eq!(cell.get(), (1, 2));
+    assert_eq!(cell.clone_inner(), (1, 2));
+    drop(cell.try_borrow().unwrap());
+
+    assert_eq!(cell.version(), last);
+    assert!(!cell.changed_since(last));
+}
+
+#[test]
+fn mutable_borrows_change_version() {
+    let mut cell = RwCell::new(0);
+    let mut last = cell.version();
+    let writes: [fn(&mut RwCell<i32>); 6] = [
+        |cell| *cell.borrow_mut() = 1,
+        |cell| cell.set(2),
+        |cell| drop(RwCellGuard::downgrade(cell.borrow_mut())),
+        |cell| *cell.get_mut() = 3,
+        |cell| *cell.borrow_mut_tracked() = 4,
+        |cell| {
+            let mut guard = cell.borrow_mut_speculative();
+            *guard = 5;
+            SpeculativeGuard::commit(guard);
+        },
+    ];
+
+    for write in writes {
+        write(&mut cell);
+        assert!(cell.changed_since(last));
+        last = cell.version();
+    }
+    assert_eq!(cell.get(), 5);
+}
+
+#[test]
+fn unchanged_guards_keep_version() {
+    let cell = RwCell::new(0);
+    let last = cell.version();
+
+    drop(cell.borrow_mut_tracked());
+    let mut guard = cell.borrow_mut_speculative();
+    *guard = 1;
+    drop(guard);
+
+    assert_eq!(cell.get(), 0);
+    assert_eq!(cell.version(), last);
+}
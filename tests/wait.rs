@@ -0,0 +1,148 @@
+#![cfg(feature = "std")]
+
+use std::thread;
+use std::time::{Duration, Instant};
+use sync_rw_cell::*;
+
+#[test]
+fn waiting_borrows_under_contention() {
+    const THREADS: usize = 4;
+    const ITERATIONS: usize = 2_000;
+
+    let cell = RwCell::new((0, 0));
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|| {
+                for _ in 0..ITERATIONS {
+                    let mut guard = cell.borrow_mut_wait();
+                    guard.0 += 1;
+                    thread::yield_now();
+                    guard.1 += 1;
+                }
+            });
+
+            scope.spawn(|| {
+                for _ in 0..ITERATIONS {
+                    let guard = cell.borrow_wait();
+                    assert_eq!(guard.0, guard.1);
+                }
+            });
+        }
+    });
+
+    assert_eq!(*cell.borrow(), (THREADS * ITERATIONS, THREADS * ITERATIONS));
+    assert!(cell.free());
+}
+
+#[test]
+fn wait_until_sees_predicate_become_true() {
+    let cell = RwCell::new(0);
+    thread::scope(|scope| {
+        let waiter = scope.spawn(|| *cell.wait_until(|value| *value == 100));
+        for _ in 0..100 {
+            *cell.borrow_mut_wait() += 1;
+        }
+        assert_eq!(waiter.join().unwrap(), 100);
+    });
+}
+
+#[test]
+fn timed_borrow_expires_while_reader_holds_cell() {
+    let cell = RwCell::new(0);
+    let guard = cell.borrow();
+    let start = Instant::now();
+    assert!(cell.try_borrow_mut_for(Duration::from_millis(50)).is_none());
+    assert!(start.elapsed() >= Duration::from_millis(50));
+    assert_eq!(cell.reader_count(), 1);
+    drop(guard);
+
+    let guard = cell.borrow_mut();
+    let deadline = Instant::now() + Duration::from_millis(50);
+    assert!(cell.try_borrow_until(deadline).is_none());
+    assert!(Instant::now() >= deadline);
+    drop(guard);
+    assert!(cell.free());
+}
+
+#[test]
+fn timed_borrow_succeeds_when_released_before_deadline() {
+    let cell = RwCell::new(0);
+    thread::scope(|scope| {
+        let guard = cell.borrow();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        scope.spawn(move || {
+            receiver.recv().unwrap();
+            thread::sleep(Duration::from_millis(100));
+            drop(guard);
+        });
+
+        sender.send(()).unwrap();
+        let mut guard = cell.try_borrow_mut_for(Duration::from_secs(10)).unwrap();
+        *guard = 1;
+    });
+
+    thread::scope(|scope| {
+        let guard = cell.borrow_mut();
+        let deadline = Instant::now() + Duration::from_millis(400);
+        scope.spawn(move || {
+            thread::sleep(deadline.saturating_duration_since(Instant::now()) / 2);
+            drop(guard);
+        });
+
+        assert_eq!(cell.try_borrow_until(deadline).map(|guard| *guard), Some(1));
+    });
+}
+
+#[test]
+#[cfg(not(any(feature = "versioning", feature = "waker")))]
+fn waiting_adds_no_storage_to_cells() {
+    assert_eq!(size_of::<RwCell<u32>>(), 2 * size_of::<u32>());
+    assert_eq!(size_of::<RwCell<u8>>(), 2 * size_of::<u16>());
+}
+
+#[test]
+fn reader_parked_on_saturated_count_is_woken() {
+    let cell = RwCell::new(0);
+    let mut guards: Vec<_> = std::iter::from_fn(|| cell.try_borrow().ok()).collect();
+    assert!(cell.try_borrow().is_err());
+
+    thread::scope(|scope| {
+        let reader = scope.spawn(|| *cell.borrow_wait());
+        thread::sleep(Duration::from_millis(50));
+        guards.pop();
+        assert_eq!(reader.join().unwrap(), 0);
+    });
+}
+
+#[test]
+fn writer_is_woken_while_another_thread_waits_until() {
+    let cell = RwCell::new(0);
+    thread::scope(|scope| {
+        let waiter = scope.spawn(|| *cell.wait_until(|value| *value == 2));
+        for _ in 0..2 {
+            let guard = cell.borrow();
+            let writer = scope.spawn(|| *cell.borrow_mut_wait() += 1);
+            thread::sleep(Duration::from_millis(20));
+            drop(guard);
+            writer.join().unwrap();
+        }
+        assert_eq!(waiter.join().unwrap(), 2);
+    });
+}
+
+#[test]
+fn waiters_on_many_cells_share_the_parking_table() {
+    const CELLS: usize = 256;
+
+    let cells: Vec<_> = (0..CELLS).map(RwCell::new).collect();
+    let guards: Vec<_> = cells.iter().map(RwCell::borrow_mut).collect();
+    thread::scope(|scope| {
+        let readers: Vec<_> = cells.iter().map(|cell| scope.spawn(move || *cell.borrow_wait())).collect();
+        thread::sleep(Duration::from_millis(20));
+        drop(guards);
+        for (i, reader) in readers.into_iter().enumerate() {
+            assert_eq!(reader.join().unwrap(), i);
+        }
+    });
+    assert!(cells.iter().all(RwCell::free));
+}